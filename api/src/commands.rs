@@ -0,0 +1,57 @@
+use crate::configuration::Settings;
+use crate::contextswitch::{admin, attachments, ContextswitchError};
+use anyhow::Context;
+
+const USAGE: &str = "Usage: contextswitch-api [cleanup-attachments | migrate] [--dry-run]";
+
+fn data_location(settings: &Settings) -> &str {
+    settings
+        .taskwarrior
+        .data_location
+        .as_deref()
+        .expect("Expecting taskwarrior.data_location setting to be set")
+}
+
+fn cleanup_attachments(settings: &Settings, args: &[String]) -> Result<(), ContextswitchError> {
+    let attachment_store =
+        attachments::new_attachment_store(data_location(settings), &settings.attachments);
+    let cleanup = attachments::cleanup_attachments(&attachment_store, is_dry_run(args))?;
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&cleanup)
+            .context("Cannot serialize Contextswitch attachments cleanup")?
+    );
+    Ok(())
+}
+
+fn is_dry_run(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == "--dry-run")
+}
+
+async fn migrate(args: &[String]) -> Result<(), ContextswitchError> {
+    let report = admin::migrate_tasks(is_dry_run(args)).await?;
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report)
+            .context("Cannot serialize Contextswitch migration report")?
+    );
+    Ok(())
+}
+
+// Maintenance commands run instead of the server
+pub async fn run_command(
+    command: &str,
+    args: &[String],
+    settings: &Settings,
+) -> Result<(), ContextswitchError> {
+    match command {
+        "cleanup-attachments" => cleanup_attachments(settings, args),
+        "migrate" => migrate(args).await,
+        _ => Err(ContextswitchError::InvalidInputError(format!(
+            "Unknown command {}\n{}",
+            command, USAGE
+        ))),
+    }
+}
@@ -1,11 +1,25 @@
 use config::{Config, ConfigError, Environment, File};
-use serde::Deserialize;
+use contextswitch::{Rule, WebhookEvent};
+use serde::{Deserialize, Serialize};
 use std::env;
 
 #[derive(Deserialize)]
 pub struct Settings {
     pub application: ApplicationSettings,
     pub taskwarrior: TaskwarriorSettings,
+    pub webhooks: WebhooksSettings,
+    pub github: GithubSettings,
+    pub slack: SlackSettings,
+    #[serde(default)]
+    pub bookmarks: BookmarksSettings,
+    #[serde(default)]
+    pub notes: NotesSettings,
+    #[serde(default)]
+    pub attachments: AttachmentsSettings,
+    pub providers: ProvidersSettings,
+    pub pomodoro: PomodoroSettings,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
 }
 
 #[derive(Deserialize)]
@@ -13,6 +27,8 @@ pub struct ApplicationSettings {
     pub port: u16,
     pub log_directive: String,
     pub front_base_url: String,
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
     pub api_path: String,
     pub static_path: Option<String>,
     pub static_dir: Option<String>,
@@ -22,6 +38,67 @@ pub struct ApplicationSettings {
 pub struct TaskwarriorSettings {
     pub data_location: Option<String>,
     pub taskrc: Option<String>,
+    #[serde(default)]
+    pub watch: bool,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct WebhooksSettings {
+    pub max_attempts: u32,
+    pub retry_delay_ms: u64,
+    #[serde(default)]
+    pub subscriptions: Vec<WebhookSettings>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebhookSettings {
+    pub name: String,
+    pub url: String,
+    pub secret: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct GithubSettings {
+    pub api_base_url: String,
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct SlackSettings {
+    pub api_base_url: String,
+    pub token: Option<String>,
+    pub signing_secret: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct NotesSettings {
+    pub directory: Option<String>,
+    pub template: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct BookmarksSettings {
+    pub tracking_params: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct AttachmentsSettings {
+    pub directory: Option<String>,
+    pub max_file_size_mb: Option<u64>,
+    pub max_task_size_mb: Option<u64>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ProvidersSettings {
+    pub enabled: Vec<String>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PomodoroSettings {
+    pub work_minutes: u32,
+    pub break_minutes: u32,
 }
 
 impl Settings {
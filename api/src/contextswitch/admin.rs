@@ -0,0 +1,497 @@
+use super::taskwarrior::{TaskwarriorTask, TaskwarriorTaskId};
+use super::{modify_task, run_task_commands, taskwarrior, ContextswitchError};
+use contextswitch::canonical::UrlCanonicalizer;
+use contextswitch::migrations::{self, CURRENT_VERSION};
+use contextswitch::{
+    ContextswitchData, IntegrityIssue, IntegrityIssueKind, IntegrityReport, MigrationReport,
+    RepairReport, RepairRequest, Status, Task, TaskId,
+};
+use http::uri::Uri;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use tracing::warn;
+use uuid::Uuid;
+
+fn all_tasks() -> Result<Vec<TaskwarriorTask>, ContextswitchError> {
+    taskwarrior::list_tasks(vec![]).map_err(|e| ContextswitchError::UnexpectedError(e.into()))
+}
+
+fn contextswitch_string(tw_task: &TaskwarriorTask) -> Option<&str> {
+    tw_task
+        .contextswitch
+        .as_deref()
+        .filter(|cs_string| !cs_string.trim().is_empty())
+}
+
+// Data is migrated when it is read already, writing it back makes the
+// migration permanent
+#[tracing::instrument(level = "debug")]
+pub async fn migrate_tasks(dry_run: bool) -> Result<MigrationReport, ContextswitchError> {
+    let tasks = all_tasks()?;
+
+    let mut report = MigrationReport {
+        dry_run,
+        version: CURRENT_VERSION,
+        ..Default::default()
+    };
+    for tw_task in tasks.iter() {
+        let cs_string = match contextswitch_string(tw_task) {
+            Some(cs_string) => cs_string,
+            None => {
+                report.up_to_date += 1;
+                continue;
+            }
+        };
+        let task = Task::from(tw_task);
+        match migrations::from_json(cs_string) {
+            Ok(migrated) if migrated.was_migrated() => {
+                let task_id = task.id.clone();
+                if !dry_run {
+                    modify_task(&task_id, |task| Ok(Some(task))).await?;
+                }
+                report.migrated.push(task_id);
+            }
+            Ok(_) => report.up_to_date += 1,
+            Err(e) => {
+                warn!(
+                    "Cannot migrate Contextswitch data of task {}: {}",
+                    task.id, e
+                );
+                report.invalid.push(task.id);
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn task_ids(tasks: &[TaskwarriorTask]) -> BTreeSet<Uuid> {
+    tasks.iter().map(|tw_task| tw_task.uuid.0).collect()
+}
+
+fn issue(task_id: &TaskId, kind: IntegrityIssueKind, message: String) -> IntegrityIssue {
+    IntegrityIssue {
+        task_id: task_id.clone(),
+        kind,
+        message,
+    }
+}
+
+fn bookmark_uri(bookmark: &Value) -> Option<&str> {
+    bookmark.get("uri").and_then(Value::as_str)
+}
+
+fn is_valid_uri(uri: &str) -> bool {
+    uri.parse::<Uri>().is_ok()
+}
+
+// A single unparseable bookmark URI makes the whole data unreadable
+fn invalid_bookmark_uris(value: &Value) -> Vec<String> {
+    value
+        .get("bookmarks")
+        .and_then(Value::as_array)
+        .map(|bookmarks| {
+            bookmarks
+                .iter()
+                .map(bookmark_uri)
+                .filter(|uri| !uri.map(is_valid_uri).unwrap_or(false))
+                .map(|uri| uri.unwrap_or_default().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn without_invalid_bookmarks(mut value: Value) -> Value {
+    if let Some(bookmarks) = value.get_mut("bookmarks").and_then(Value::as_array_mut) {
+        bookmarks.retain(|bookmark| bookmark_uri(bookmark).map(is_valid_uri).unwrap_or(false));
+    }
+    value
+}
+
+fn duplicate_bookmarks(data: &ContextswitchData, canonicalizer: &UrlCanonicalizer) -> Vec<String> {
+    let mut urls = BTreeSet::new();
+    data.bookmarks
+        .iter()
+        .filter(|bookmark| !urls.insert(canonicalizer.canonical_url(&bookmark.uri)))
+        .map(|bookmark| bookmark.uri.to_string())
+        .collect()
+}
+
+// The first bookmark of each page is kept
+fn without_duplicate_bookmarks(
+    data: ContextswitchData,
+    canonicalizer: &UrlCanonicalizer,
+) -> ContextswitchData {
+    let mut deduplicated = ContextswitchData {
+        bookmarks: vec![],
+        ..data.clone()
+    };
+    for bookmark in data.bookmarks {
+        deduplicated.add_bookmark(bookmark, canonicalizer);
+    }
+    deduplicated
+}
+
+fn check_data(
+    task_id: &TaskId,
+    cs_string: &str,
+    canonicalizer: &UrlCanonicalizer,
+) -> Vec<IntegrityIssue> {
+    let value = match serde_json::from_str::<Value>(cs_string) {
+        Ok(value) => value,
+        Err(e) => {
+            return vec![issue(
+                task_id,
+                IntegrityIssueKind::InvalidData,
+                format!("Invalid JSON: {}", e),
+            )]
+        }
+    };
+
+    let mut issues: Vec<IntegrityIssue> = invalid_bookmark_uris(&value)
+        .into_iter()
+        .map(|uri| {
+            issue(
+                task_id,
+                IntegrityIssueKind::InvalidBookmarkUri,
+                format!("Invalid bookmark URI '{}'", uri),
+            )
+        })
+        .collect();
+    match migrations::migrate(without_invalid_bookmarks(value)) {
+        Ok(migrated) => issues.extend(
+            duplicate_bookmarks(&migrated.data, canonicalizer)
+                .into_iter()
+                .map(|uri| {
+                    issue(
+                        task_id,
+                        IntegrityIssueKind::DuplicateBookmark,
+                        format!("Duplicate bookmark {}", uri),
+                    )
+                }),
+        ),
+        Err(e) => issues.push(issue(
+            task_id,
+            IntegrityIssueKind::InvalidData,
+            e.to_string(),
+        )),
+    }
+    issues
+}
+
+fn missing_dependencies<'a>(
+    tw_task: &'a TaskwarriorTask,
+    task_ids: &'a BTreeSet<Uuid>,
+) -> impl Iterator<Item = &'a TaskwarriorTaskId> {
+    tw_task
+        .depends
+        .iter()
+        .flatten()
+        .filter(move |dependency| !task_ids.contains(&dependency.0))
+}
+
+// Recurring tasks are templates, their occurrences point to them as parent
+fn check_links(
+    task_id: &TaskId,
+    tw_task: &TaskwarriorTask,
+    task_ids: &BTreeSet<Uuid>,
+) -> Vec<IntegrityIssue> {
+    let mut issues = vec![];
+    match &tw_task.parent {
+        Some(parent) if !task_ids.contains(&parent.0) => issues.push(issue(
+            task_id,
+            IntegrityIssueKind::MissingParent,
+            format!("Parent task {} not found", parent),
+        )),
+        None if tw_task.recur.is_some() && tw_task.status != Status::Recurring => {
+            issues.push(issue(
+                task_id,
+                IntegrityIssueKind::OrphanedRecurrence,
+                "Recurring task occurrence without a parent task".to_string(),
+            ))
+        }
+        _ => {}
+    }
+    issues.extend(missing_dependencies(tw_task, task_ids).map(|dependency| {
+        issue(
+            task_id,
+            IntegrityIssueKind::MissingDependency,
+            format!("Dependency {} not found", dependency),
+        )
+    }));
+    issues
+}
+
+fn check_task(
+    tw_task: &TaskwarriorTask,
+    task_ids: &BTreeSet<Uuid>,
+    canonicalizer: &UrlCanonicalizer,
+) -> Vec<IntegrityIssue> {
+    let task_id: TaskId = tw_task.uuid.clone().into();
+    let data_issues = contextswitch_string(tw_task)
+        .map(|cs_string| check_data(&task_id, cs_string, canonicalizer))
+        .unwrap_or_default();
+
+    [data_issues, check_links(&task_id, tw_task, task_ids)].concat()
+}
+
+#[tracing::instrument(level = "debug", skip(canonicalizer))]
+pub fn check_tasks(
+    canonicalizer: &UrlCanonicalizer,
+) -> Result<IntegrityReport, ContextswitchError> {
+    let tasks = all_tasks()?;
+    let task_ids = task_ids(&tasks);
+
+    Ok(IntegrityReport {
+        checked: tasks.len(),
+        issues: tasks
+            .iter()
+            .flat_map(|tw_task| check_task(tw_task, &task_ids, canonicalizer))
+            .collect(),
+    })
+}
+
+const DATA_ISSUES: [IntegrityIssueKind; 3] = [
+    IntegrityIssueKind::InvalidData,
+    IntegrityIssueKind::InvalidBookmarkUri,
+    IntegrityIssueKind::DuplicateBookmark,
+];
+
+// None when the data cannot be made readable with the selected fixes, the
+// invalid data fix replaces unreadable data with empty data
+fn repaired_data(
+    cs_string: &str,
+    fixes: &[IntegrityIssueKind],
+    canonicalizer: &UrlCanonicalizer,
+) -> Option<ContextswitchData> {
+    let value = serde_json::from_str::<Value>(cs_string).ok().map(|value| {
+        if fixes.contains(&IntegrityIssueKind::InvalidBookmarkUri) {
+            without_invalid_bookmarks(value)
+        } else {
+            value
+        }
+    });
+    let data = value
+        .and_then(|value| migrations::migrate(value).ok())
+        .map(|migrated| migrated.data)
+        .or_else(|| {
+            fixes
+                .contains(&IntegrityIssueKind::InvalidData)
+                .then(ContextswitchData::default)
+        })?;
+
+    Some(if fixes.contains(&IntegrityIssueKind::DuplicateBookmark) {
+        without_duplicate_bookmarks(data, canonicalizer)
+    } else {
+        data
+    })
+}
+
+async fn repair_task(
+    tw_task: &TaskwarriorTask,
+    task_ids: &BTreeSet<Uuid>,
+    fixes: &[IntegrityIssueKind],
+    dry_run: bool,
+    canonicalizer: &UrlCanonicalizer,
+) -> Result<(Vec<IntegrityIssue>, Vec<IntegrityIssue>), ContextswitchError> {
+    let issues: Vec<IntegrityIssue> = check_task(tw_task, task_ids, canonicalizer)
+        .into_iter()
+        .filter(|issue| fixes.contains(&issue.kind))
+        .collect();
+    let has_issue = |kind| issues.iter().any(|issue| issue.kind == kind);
+    let data = contextswitch_string(tw_task)
+        .filter(|_| DATA_ISSUES.iter().any(|kind| has_issue(*kind)))
+        .and_then(|cs_string| repaired_data(cs_string, fixes, canonicalizer));
+    let missing_parent = has_issue(IntegrityIssueKind::MissingParent);
+    let orphaned_recurrence = has_issue(IntegrityIssueKind::OrphanedRecurrence);
+    let dependency_commands: Vec<Vec<String>> = missing_dependencies(tw_task, task_ids)
+        .filter(|_| has_issue(IntegrityIssueKind::MissingDependency))
+        .map(|dependency| vec!["mod".to_string(), format!("depends:-{}", dependency)])
+        .collect();
+    let (repaired, skipped): (Vec<IntegrityIssue>, Vec<IntegrityIssue>) = issues
+        .into_iter()
+        .partition(|issue| !DATA_ISSUES.contains(&issue.kind) || data.is_some());
+    if dry_run || repaired.is_empty() {
+        return Ok((repaired, skipped));
+    }
+
+    let checked_task = Task::from(tw_task);
+    let mut changed = false;
+    if data.is_some() || missing_parent || orphaned_recurrence {
+        let data_repaired = data.is_some();
+        modify_task(&checked_task.id, |task| {
+            // The issues were found in data which has changed since
+            if task.contextswitch != checked_task.contextswitch
+                || task.invalid_contextswitch != checked_task.invalid_contextswitch
+            {
+                changed = true;
+                return Ok(None);
+            }
+
+            // Occurrences cannot recur without their parent task
+            Ok(Some(Task {
+                parent: task.parent.clone().filter(|_| !missing_parent),
+                recur: task
+                    .recur
+                    .filter(|_| !missing_parent && !orphaned_recurrence),
+                contextswitch: data.or(task.contextswitch.clone()),
+                invalid_contextswitch: task
+                    .invalid_contextswitch
+                    .clone()
+                    .filter(|_| !data_repaired),
+                ..task
+            }))
+        })
+        .await?;
+    }
+    if changed {
+        return Ok((vec![], [repaired, skipped].concat()));
+    }
+    run_task_commands(&checked_task.id, |_| dependency_commands).await?;
+
+    Ok((repaired, skipped))
+}
+
+#[tracing::instrument(level = "debug", skip(canonicalizer))]
+pub async fn repair_tasks(
+    canonicalizer: &UrlCanonicalizer,
+    repair_request: RepairRequest,
+) -> Result<RepairReport, ContextswitchError> {
+    if repair_request.fixes.is_empty() {
+        return Err(ContextswitchError::InvalidInputError(
+            "At least one fix must be selected".to_string(),
+        ));
+    }
+
+    let tasks = all_tasks()?;
+    let task_ids = task_ids(&tasks);
+    let mut report = RepairReport {
+        dry_run: repair_request.dry_run,
+        ..Default::default()
+    };
+    for tw_task in tasks.iter() {
+        let (repaired, skipped) = repair_task(
+            tw_task,
+            &task_ids,
+            &repair_request.fixes,
+            repair_request.dry_run,
+            canonicalizer,
+        )
+        .await?;
+        report.repaired.extend(repaired);
+        report.skipped.extend(skipped);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers::tw_task;
+    use contextswitch::Recurrence;
+
+    fn issue_kinds(issues: &[IntegrityIssue]) -> Vec<IntegrityIssueKind> {
+        issues.iter().map(|issue| issue.kind).collect()
+    }
+
+    #[test]
+    fn test_check_data() {
+        let task_id = TaskId(Uuid::new_v4());
+        let canonicalizer = UrlCanonicalizer::default();
+        let data = r#"{"bookmarks": [
+            {"uri": "https://example.com"},
+            {"uri": "https://exa mple.com"},
+            {"uri": "https://EXAMPLE.com/?utm_source=news"}
+        ]}"#;
+
+        assert_eq!(
+            issue_kinds(&check_data(&task_id, data, &canonicalizer)),
+            vec![
+                IntegrityIssueKind::InvalidBookmarkUri,
+                IntegrityIssueKind::DuplicateBookmark
+            ]
+        );
+        assert_eq!(
+            issue_kinds(&check_data(
+                &task_id,
+                r#"{"bookmarks": 42}"#,
+                &canonicalizer
+            )),
+            vec![IntegrityIssueKind::InvalidData]
+        );
+        assert_eq!(
+            issue_kinds(&check_data(&task_id, "{", &canonicalizer)),
+            vec![IntegrityIssueKind::InvalidData]
+        );
+        assert!(check_data(&task_id, "{}", &canonicalizer).is_empty());
+    }
+
+    #[test]
+    fn test_check_links() {
+        let parent = tw_task("{}");
+        let mut child = tw_task("{}");
+        child.parent = Some(parent.uuid.clone());
+        child.recur = Some(Recurrence::Daily);
+        child.depends = Some(vec![parent.uuid.clone()]);
+        let task_ids = task_ids(&[parent, tw_task("{}")]);
+        let canonicalizer = UrlCanonicalizer::default();
+
+        assert!(check_task(&child, &task_ids, &canonicalizer).is_empty());
+        assert_eq!(
+            issue_kinds(&check_task(&child, &BTreeSet::new(), &canonicalizer)),
+            vec![
+                IntegrityIssueKind::MissingParent,
+                IntegrityIssueKind::MissingDependency
+            ]
+        );
+
+        child.parent = None;
+        assert_eq!(
+            issue_kinds(&check_task(&child, &task_ids, &canonicalizer)),
+            vec![IntegrityIssueKind::OrphanedRecurrence]
+        );
+        child.status = Status::Recurring;
+        assert!(check_task(&child, &task_ids, &canonicalizer).is_empty());
+    }
+
+    #[test]
+    fn test_repaired_data() {
+        let canonicalizer = UrlCanonicalizer::default();
+        let data = r#"{"bookmarks": [{"uri": "https://exa mple.com"}], "history": 42}"#;
+
+        assert_eq!(
+            repaired_data(
+                data,
+                &[IntegrityIssueKind::InvalidBookmarkUri],
+                &canonicalizer
+            ),
+            None
+        );
+        assert_eq!(
+            repaired_data(
+                data,
+                &[
+                    IntegrityIssueKind::InvalidBookmarkUri,
+                    IntegrityIssueKind::InvalidData
+                ],
+                &canonicalizer
+            ),
+            Some(ContextswitchData::default())
+        );
+
+        let duplicates =
+            r#"{"bookmarks": [{"uri": "https://example.com"}, {"uri": "https://example.com"}]}"#;
+        assert_eq!(
+            repaired_data(
+                duplicates,
+                &[IntegrityIssueKind::DuplicateBookmark],
+                &canonicalizer
+            )
+            .unwrap()
+            .bookmarks
+            .len(),
+            1
+        );
+    }
+}
@@ -1,6 +1,14 @@
-use crate::contextswitch::taskwarrior;
-use contextswitch::Task;
+use crate::contextswitch::{events, taskwarrior, time};
+use chrono::Utc;
+use contextswitch::{ActiveContext, Context, ContextswitchData, NewContext, Task, TaskId};
 use serde_json;
+use tokio::sync::Mutex;
+
+lazy_static! {
+    // Tasks are read, changed and written back as a whole, a change made in
+    // between would be lost
+    static ref TASK_LOCK: Mutex<()> = Mutex::new(());
+}
 
 fn error_chain_fmt(
     e: &impl std::error::Error,
@@ -25,6 +33,12 @@ impl std::fmt::Debug for ContextswitchError {
 pub enum ContextswitchError {
     #[error("Invalid Contextswitch data")]
     InvalidDataError(#[from] serde_json::Error),
+    #[error("{0}")]
+    InvalidInputError(String),
+    #[error("{0} not found")]
+    NotFoundError(String),
+    #[error("{0}")]
+    UnauthorizedError(String),
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -39,18 +53,304 @@ pub fn list_tasks(filters: Vec<&str>) -> Result<Vec<Task>, ContextswitchError> {
     Ok(tasks)
 }
 
+// The user filter is grouped on its own so that an `or` in it cannot widen
+// the context
+fn context_filters<'a>(read_filter: Option<&'a str>, filters: Vec<&'a str>) -> Vec<&'a str> {
+    let read_filter: Vec<&str> = read_filter
+        .map(|read_filter| read_filter.split_whitespace().collect())
+        .unwrap_or_default();
+    match (read_filter.is_empty(), filters.is_empty()) {
+        (true, _) => filters,
+        (false, true) => [vec!["("], read_filter, vec![")"]].concat(),
+        (false, false) => [vec!["("], read_filter, vec![")", "("], filters, vec![")"]].concat(),
+    }
+}
+
+// Tasks shown to the user are restricted to the active context, internal
+// features keep working on all tasks through list_tasks
+#[tracing::instrument(level = "debug")]
+pub fn list_context_tasks(filters: Vec<&str>) -> Result<Vec<Task>, ContextswitchError> {
+    let active_context = active_context()?;
+    list_tasks(context_filters(
+        active_context
+            .as_ref()
+            .map(|context| context.read_filter.as_str()),
+        filters,
+    ))
+}
+
+#[tracing::instrument(level = "debug")]
+pub fn get_task(task_id: &TaskId) -> Result<Task, ContextswitchError> {
+    taskwarrior::get_task_by_id(&task_id.clone().into())
+        .map_err(|e| ContextswitchError::UnexpectedError(e.into()))?
+        .map(Task::from)
+        .ok_or_else(|| ContextswitchError::NotFoundError(format!("Task {}", task_id)))
+}
+
+async fn add_context_task(
+    add_args: Vec<&str>,
+    description_args: Vec<&str>,
+) -> Result<Task, ContextswitchError> {
+    let active_context = active_context()?;
+    let context_args = active_context
+        .as_ref()
+        .and_then(|context| context.write_filter.as_ref())
+        .map(|write_filter| write_filter.split_whitespace().collect::<Vec<&str>>())
+        .unwrap_or_default();
+    let taskwarrior_task =
+        taskwarrior::add_task([add_args, context_args, description_args].concat())
+            .await
+            .map_err(|e| ContextswitchError::UnexpectedError(e.into()))?;
+    let task: Task = taskwarrior_task.into();
+    events::publish_created(&task);
+    Ok(task)
+}
+
 #[tracing::instrument(level = "debug")]
 pub async fn add_task(add_args: Vec<&str>) -> Result<Task, ContextswitchError> {
-    let taskwarrior_task = taskwarrior::add_task(add_args)
-        .await
-        .map_err(|e| ContextswitchError::UnexpectedError(e.into()))?;
-    Ok(taskwarrior_task.into())
+    add_context_task(add_args, vec![]).await
+}
+
+// Words following the -- terminator are not parsed as attributes or tags.
+// Configuration overrides are rejected as Taskwarrior may still apply them.
+fn description_args(description: &str) -> Result<Vec<&str>, ContextswitchError> {
+    let words: Vec<&str> = description.split_whitespace().collect();
+    if words.is_empty() {
+        return Err(ContextswitchError::InvalidInputError(
+            "Task description cannot be empty".to_string(),
+        ));
+    }
+    if let Some(word) = words
+        .iter()
+        .find(|word| word.starts_with("rc.") || word.starts_with("rc:"))
+    {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Configuration override '{}' is not allowed in a task description",
+            word
+        )));
+    }
+
+    Ok([vec!["--"], words].concat())
+}
+
+// Free text, from third parties or checklist items, only ever becomes the task
+// description, attributes can only be given through add_args
+#[tracing::instrument(level = "debug")]
+pub async fn add_task_with_description(
+    add_args: Vec<&str>,
+    description: &str,
+) -> Result<Task, ContextswitchError> {
+    add_context_task(add_args, description_args(description)?).await
+}
+
+// Unreadable Contextswitch data is only replaced on purpose, by clearing
+// invalid_contextswitch along with the new data
+fn keep_invalid_data(previous_task: &Task, task: Task) -> Result<Task, ContextswitchError> {
+    match (&task.contextswitch, &task.invalid_contextswitch) {
+        (Some(_), Some(_)) => Err(ContextswitchError::InvalidInputError(format!(
+            "Contextswitch data of task {} cannot be read and must be repaired first",
+            task.id
+        ))),
+        (None, _) => Ok(Task {
+            invalid_contextswitch: previous_task.invalid_contextswitch.clone(),
+            ..task
+        }),
+        (Some(_), None) => Ok(task),
+    }
 }
 
 #[tracing::instrument(level = "debug")]
 pub async fn update_task(task_to_update: Task) -> Result<Task, ContextswitchError> {
+    let previous_task = taskwarrior::get_task_by_id(&task_to_update.id.clone().into())
+        .map_err(|e| ContextswitchError::UnexpectedError(e.into()))?
+        .map(Task::from);
+    let task_to_update = match &previous_task {
+        Some(previous_task) if previous_task.invalid_contextswitch.is_some() => {
+            keep_invalid_data(previous_task, task_to_update)?
+        }
+        Some(previous_task) => time::close_session(previous_task, task_to_update, Utc::now()),
+        None => task_to_update,
+    };
     let taskwarrior_task = taskwarrior::update_task(task_to_update.try_into()?)
         .await
         .map_err(|e| ContextswitchError::UnexpectedError(e.into()))?;
-    Ok(taskwarrior_task.into())
+    let task: Task = taskwarrior_task.into();
+    events::publish_updated(&task);
+    Ok(task)
+}
+
+// The task is read and written back under the same lock, the change returns
+// None to leave the task as it is
+pub async fn modify_task<F>(task_id: &TaskId, modify: F) -> Result<Task, ContextswitchError>
+where
+    F: FnOnce(Task) -> Result<Option<Task>, ContextswitchError>,
+{
+    let _lock = TASK_LOCK.lock().await;
+
+    let task = get_task(task_id)?;
+    match modify(task.clone())? {
+        Some(modified_task) => update_task(modified_task).await,
+        None => Ok(task),
+    }
+}
+
+// Contextswitch data is only written when it changed
+pub async fn update_contextswitch<F>(
+    task_id: &TaskId,
+    update: F,
+) -> Result<Task, ContextswitchError>
+where
+    F: FnOnce(&Task, &mut ContextswitchData) -> Result<(), ContextswitchError>,
+{
+    modify_task(task_id, |task| {
+        let previous_contextswitch = task.contextswitch.clone().unwrap_or_default();
+        let mut contextswitch = previous_contextswitch.clone();
+        update(&task, &mut contextswitch)?;
+        if contextswitch == previous_contextswitch {
+            return Ok(None);
+        }
+
+        Ok(Some(Task {
+            contextswitch: Some(contextswitch),
+            ..task
+        }))
+    })
+    .await
+}
+
+// Taskwarrior commands are computed from the current task under the same lock,
+// no command is run when the list is empty
+pub async fn run_task_commands<F>(task_id: &TaskId, commands: F) -> Result<Task, ContextswitchError>
+where
+    F: FnOnce(&Task) -> Vec<Vec<String>>,
+{
+    let _lock = TASK_LOCK.lock().await;
+
+    let task = get_task(task_id)?;
+    let commands = commands(&task);
+    if commands.is_empty() {
+        return Ok(task);
+    }
+    let task: Task = taskwarrior::run_task_commands(&task.id.clone().into(), commands)
+        .await
+        .map_err(|e| ContextswitchError::UnexpectedError(e.into()))?
+        .into();
+    events::publish_updated(&task);
+
+    Ok(task)
+}
+
+fn active_context() -> Result<Option<taskwarrior::TaskwarriorContext>, ContextswitchError> {
+    let contexts =
+        taskwarrior::list_contexts().map_err(|e| ContextswitchError::UnexpectedError(e.into()))?;
+    Ok(contexts.into_iter().find(|context| context.active))
+}
+
+fn is_valid_context_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "none"
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+#[tracing::instrument(level = "debug")]
+pub fn list_contexts() -> Result<Vec<Context>, ContextswitchError> {
+    let contexts = taskwarrior::list_contexts()
+        .map_err(|e| ContextswitchError::UnexpectedError(e.into()))?
+        .into_iter()
+        .map(Context::from)
+        .collect();
+    Ok(contexts)
+}
+
+#[tracing::instrument(level = "debug", skip(new_context), fields(name = %new_context.name))]
+pub async fn define_context(new_context: NewContext) -> Result<Context, ContextswitchError> {
+    if !is_valid_context_name(&new_context.name) {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Invalid context name '{}'",
+            new_context.name
+        )));
+    }
+    if new_context.read_filter.trim().is_empty() {
+        return Err(ContextswitchError::InvalidInputError(
+            "Context read filter cannot be empty".to_string(),
+        ));
+    }
+
+    let name = new_context.name.clone();
+    taskwarrior::define_context(new_context.into())
+        .await
+        .map_err(|e| ContextswitchError::UnexpectedError(e.into()))?;
+
+    list_contexts()?
+        .into_iter()
+        .find(|context| context.name == name)
+        .ok_or_else(|| ContextswitchError::NotFoundError(format!("Context {}", name)))
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn set_active_context(
+    active_context: ActiveContext,
+) -> Result<ActiveContext, ContextswitchError> {
+    if let Some(name) = &active_context.name {
+        if !list_contexts()?.iter().any(|context| &context.name == name) {
+            return Err(ContextswitchError::NotFoundError(format!(
+                "Context {}",
+                name
+            )));
+        }
+    }
+
+    taskwarrior::use_context(active_context.name.as_deref())
+        .await
+        .map_err(|e| ContextswitchError::UnexpectedError(e.into()))?;
+    Ok(active_context)
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn delete_context(name: &str) -> Result<(), ContextswitchError> {
+    if !list_contexts()?.iter().any(|context| context.name == name) {
+        return Err(ContextswitchError::NotFoundError(format!(
+            "Context {}",
+            name
+        )));
+    }
+
+    taskwarrior::delete_context(name)
+        .await
+        .map_err(|e| ContextswitchError::UnexpectedError(e.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_description_args() {
+        assert_eq!(
+            description_args("Deploy project:foo +urgent").unwrap(),
+            vec!["--", "Deploy", "project:foo", "+urgent"]
+        );
+        assert!(description_args("x rc.data.location=/tmp").is_err());
+        assert!(description_args("x rc:/tmp/taskrc").is_err());
+        assert!(description_args("  ").is_err());
+    }
+
+    #[test]
+    fn test_context_filters() {
+        assert_eq!(
+            context_filters(Some("+work"), vec!["+a", "or", "+b"]),
+            vec!["(", "+work", ")", "(", "+a", "or", "+b", ")"]
+        );
+        assert_eq!(
+            context_filters(Some("+work or +meeting"), vec![]),
+            vec!["(", "+work", "or", "+meeting", ")"]
+        );
+        assert_eq!(
+            context_filters(Some(" "), vec!["+a", "or", "+b"]),
+            vec!["+a", "or", "+b"]
+        );
+        assert_eq!(context_filters(None, vec!["+a"]), vec!["+a"]);
+    }
 }
@@ -0,0 +1,361 @@
+use super::store::store_path;
+use super::{get_task, taskwarrior, update_contextswitch, ContextswitchError};
+use crate::configuration::AttachmentsSettings;
+use anyhow::Context;
+use chrono::Utc;
+use contextswitch::{Attachment, AttachmentsCleanup, Task, TaskId};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+use uuid::Uuid;
+
+const ATTACHMENTS_DIRECTORY: &str = "attachments";
+const BYTES_PER_MB: u64 = 1024 * 1024;
+const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+// Content is written before the task referencing it is updated, recent files
+// are never considered orphans
+const ORPHAN_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug)]
+pub struct AttachmentStore {
+    directory: PathBuf,
+    max_file_size: Option<u64>,
+    max_task_size: Option<u64>,
+}
+
+pub fn new_attachment_store(
+    data_location: &str,
+    settings: &AttachmentsSettings,
+) -> AttachmentStore {
+    AttachmentStore {
+        directory: settings
+            .directory
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| store_path(data_location, ATTACHMENTS_DIRECTORY)),
+        max_file_size: settings.max_file_size_mb.map(|size| size * BYTES_PER_MB),
+        max_task_size: settings.max_task_size_mb.map(|size| size * BYTES_PER_MB),
+    }
+}
+
+pub struct NewAttachment {
+    pub name: String,
+    pub mime_type: Option<String>,
+    pub content: Vec<u8>,
+}
+
+fn content_hash(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+// File names only keep their last path component
+fn attachment_name(name: &str) -> String {
+    let name = name.rsplit(&['/', '\\'][..]).next().unwrap_or("").trim();
+    if name.is_empty() {
+        "attachment".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn task_attachments(task: &Task) -> &[Attachment] {
+    task.contextswitch
+        .as_ref()
+        .map(|contextswitch| contextswitch.attachments.as_slice())
+        .unwrap_or_default()
+}
+
+fn find_attachment<'a>(
+    task: &'a Task,
+    attachment_id: &Uuid,
+) -> Result<&'a Attachment, ContextswitchError> {
+    task_attachments(task)
+        .iter()
+        .find(|attachment| &attachment.id == attachment_id)
+        .ok_or_else(|| ContextswitchError::NotFoundError(format!("Attachment {}", attachment_id)))
+}
+
+impl AttachmentStore {
+    pub fn max_file_size(&self) -> Option<u64> {
+        self.max_file_size
+    }
+
+    // Contents are spread in sub-directories named after the hash prefix
+    fn content_path(&self, hash: &str) -> PathBuf {
+        self.directory.join(&hash[..2]).join(hash)
+    }
+
+    fn check_quotas(&self, task: &Task, size: u64) -> Result<(), ContextswitchError> {
+        if let Some(max_file_size) = self.max_file_size.filter(|max| size > *max) {
+            return Err(ContextswitchError::InvalidInputError(format!(
+                "Attachment size {} exceeds the {} bytes limit",
+                size, max_file_size
+            )));
+        }
+        let task_size: u64 = task_attachments(task)
+            .iter()
+            .map(|attachment| attachment.size)
+            .sum();
+        if let Some(max_task_size) = self.max_task_size.filter(|max| task_size + size > *max) {
+            return Err(ContextswitchError::InvalidInputError(format!(
+                "Attachments of task {} would exceed the {} bytes limit",
+                task.id, max_task_size
+            )));
+        }
+
+        Ok(())
+    }
+
+    // Existing content is written again so that its grace period starts over,
+    // each upload goes through its own temporary file
+    fn write_content(&self, hash: &str, content: &[u8]) -> Result<(), ContextswitchError> {
+        let path = self.content_path(hash);
+        let tmp_path = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Cannot create directory {}", parent.display()))?;
+        }
+        fs::write(&tmp_path, content)
+            .and_then(|_| fs::rename(&tmp_path, &path))
+            .with_context(|| format!("Cannot write attachment {}", path.display()))?;
+
+        Ok(())
+    }
+
+    fn stored_files(&self) -> Result<Vec<PathBuf>, std::io::Error> {
+        if !self.directory.exists() {
+            return Ok(vec![]);
+        }
+        let mut files = vec![];
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                for entry in fs::read_dir(&path)? {
+                    files.push(entry?.path());
+                }
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+#[tracing::instrument(level = "debug", skip(attachment_store, new_attachment), fields(name = %new_attachment.name))]
+pub async fn add_attachment(
+    attachment_store: &AttachmentStore,
+    task_id: &TaskId,
+    new_attachment: NewAttachment,
+) -> Result<Task, ContextswitchError> {
+    let size = new_attachment.content.len() as u64;
+    let hash = content_hash(&new_attachment.content);
+    let attachment = Attachment {
+        id: Uuid::new_v4(),
+        name: attachment_name(&new_attachment.name),
+        mime_type: new_attachment
+            .mime_type
+            .unwrap_or_else(|| DEFAULT_MIME_TYPE.to_string()),
+        size,
+        hash: hash.clone(),
+        added_at: Utc::now(),
+    };
+
+    update_contextswitch(task_id, |task, contextswitch| {
+        attachment_store.check_quotas(task, size)?;
+        attachment_store.write_content(&hash, &new_attachment.content)?;
+        contextswitch.attachments.push(attachment);
+        Ok(())
+    })
+    .await
+}
+
+#[tracing::instrument(level = "debug", skip(attachment_store))]
+pub fn get_attachment(
+    attachment_store: &AttachmentStore,
+    task_id: &TaskId,
+    attachment_id: &Uuid,
+) -> Result<(Attachment, Vec<u8>), ContextswitchError> {
+    let task = get_task(task_id)?;
+    let attachment = find_attachment(&task, attachment_id)?.clone();
+    let path = attachment_store.content_path(&attachment.hash);
+    let content =
+        fs::read(&path).with_context(|| format!("Cannot read attachment {}", path.display()))?;
+
+    Ok((attachment, content))
+}
+
+// Tasks outside of the active context reference attachments as well
+fn all_tasks() -> Result<Vec<Task>, ContextswitchError> {
+    Ok(taskwarrior::list_tasks(vec![])
+        .map_err(|e| ContextswitchError::UnexpectedError(e.into()))?
+        .iter()
+        .map(Task::from)
+        .collect())
+}
+
+fn referenced_hashes(tasks: &[Task]) -> BTreeSet<String> {
+    tasks
+        .iter()
+        .flat_map(task_attachments)
+        .map(|attachment| attachment.hash.clone())
+        .collect()
+}
+
+// The content is kept as long as another task references it
+#[tracing::instrument(level = "debug", skip(attachment_store))]
+pub async fn remove_attachment(
+    attachment_store: &AttachmentStore,
+    task_id: &TaskId,
+    attachment_id: &Uuid,
+) -> Result<Task, ContextswitchError> {
+    let mut hash = String::new();
+    let task = update_contextswitch(task_id, |task, contextswitch| {
+        hash = find_attachment(task, attachment_id)?.hash.clone();
+        contextswitch
+            .attachments
+            .retain(|attachment| &attachment.id != attachment_id);
+        Ok(())
+    })
+    .await?;
+
+    // Content just written for another task is left to the cleanup
+    let path = attachment_store.content_path(&hash);
+    if !referenced_hashes(&all_tasks()?).contains(&hash) && !is_recent_file(&path) {
+        if let Err(e) = fs::remove_file(&path) {
+            warn!("Cannot remove attachment {}: {}", path.display(), e);
+        }
+    }
+
+    Ok(task)
+}
+
+// Interrupted uploads leave temporary files behind, they are orphans as well
+fn orphaned_files(files: Vec<PathBuf>, referenced_hashes: &BTreeSet<String>) -> Vec<PathBuf> {
+    files
+        .into_iter()
+        .filter(|path| {
+            let is_content = path.extension().is_none();
+            let file_name = path
+                .file_name()
+                .map(|file_name| file_name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            !is_content || !referenced_hashes.contains(&file_name)
+        })
+        .collect()
+}
+
+fn is_recent(modified: SystemTime, now: SystemTime) -> bool {
+    now.duration_since(modified)
+        .map(|age| age < ORPHAN_GRACE_PERIOD)
+        .unwrap_or(true)
+}
+
+fn is_recent_file(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map(|modified| is_recent(modified, SystemTime::now()))
+        .unwrap_or(false)
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+#[tracing::instrument(level = "debug", skip(attachment_store))]
+pub fn cleanup_attachments(
+    attachment_store: &AttachmentStore,
+    dry_run: bool,
+) -> Result<AttachmentsCleanup, ContextswitchError> {
+    // Deleted tasks can be restored, their attachments are kept
+    let tasks = all_tasks()?;
+    let files = attachment_store.stored_files().with_context(|| {
+        format!(
+            "Cannot scan attachments directory {}",
+            attachment_store.directory.display()
+        )
+    })?;
+
+    let mut cleanup = AttachmentsCleanup {
+        dry_run,
+        ..Default::default()
+    };
+    for path in orphaned_files(files, &referenced_hashes(&tasks))
+        .into_iter()
+        .filter(|path| !is_recent_file(path))
+    {
+        let size = file_size(&path);
+        if !dry_run {
+            fs::remove_file(&path)
+                .with_context(|| format!("Cannot remove attachment {}", path.display()))?;
+        }
+        cleanup.removed.push(path.to_string_lossy().to_string());
+        cleanup.freed_bytes += size;
+    }
+
+    Ok(cleanup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment_store(max_file_size: Option<u64>, max_task_size: Option<u64>) -> AttachmentStore {
+        AttachmentStore {
+            directory: PathBuf::from("/attachments"),
+            max_file_size,
+            max_task_size,
+        }
+    }
+
+    #[test]
+    fn test_content_path() {
+        let hash = content_hash(b"contextswitch");
+
+        assert_eq!(hash.len(), 64);
+        assert_eq!(
+            attachment_store(None, None).content_path(&hash),
+            PathBuf::from(format!("/attachments/{}/{}", &hash[..2], hash))
+        );
+    }
+
+    #[test]
+    fn test_attachment_name() {
+        assert_eq!(attachment_name("../../etc/passwd"), "passwd");
+        assert_eq!(attachment_name("C:\\logs\\build.log"), "build.log");
+        assert_eq!(attachment_name(" "), "attachment");
+    }
+
+    #[test]
+    fn test_is_recent() {
+        let now = SystemTime::now();
+
+        assert!(is_recent(now - Duration::from_secs(60), now));
+        assert!(is_recent(now + Duration::from_secs(60), now));
+        assert!(!is_recent(now - ORPHAN_GRACE_PERIOD, now));
+    }
+
+    #[test]
+    fn test_orphaned_files() {
+        let referenced = content_hash(b"referenced");
+        let orphan = content_hash(b"orphan");
+        let referenced_hashes: BTreeSet<String> = vec![referenced.clone()].into_iter().collect();
+        let store = attachment_store(None, None);
+        let files = vec![
+            store.content_path(&referenced),
+            store.content_path(&orphan),
+            store.content_path(&referenced).with_extension("tmp"),
+        ];
+
+        assert_eq!(
+            orphaned_files(files, &referenced_hashes),
+            vec![
+                store.content_path(&orphan),
+                store.content_path(&referenced).with_extension("tmp")
+            ]
+        );
+    }
+}
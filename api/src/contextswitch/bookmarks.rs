@@ -0,0 +1,261 @@
+use super::index::{self, LookupMatch};
+use super::providers::ProviderRegistry;
+use super::rules::{self, RuleEngine};
+use super::{get_task, list_tasks, update_contextswitch, ContextswitchError};
+use crate::configuration::BookmarksSettings;
+use chrono::Utc;
+use contextswitch::canonical::UrlCanonicalizer;
+use contextswitch::{Bookmark, BookmarkDetails, BookmarkPin, NewBookmark, Task, TaskId};
+use http::uri::Uri;
+use std::collections::BTreeSet;
+use tracing::warn;
+
+const CONTENT_PREVIEW_LENGTH: usize = 200;
+
+pub fn new_url_canonicalizer(settings: &BookmarksSettings) -> UrlCanonicalizer {
+    settings
+        .tracking_params
+        .clone()
+        .map(UrlCanonicalizer::new)
+        .unwrap_or_default()
+}
+
+pub fn content_preview(body: Option<&str>) -> Option<String> {
+    body.map(|body| body.trim())
+        .filter(|body| !body.is_empty())
+        .map(|body| body.chars().take(CONTENT_PREVIEW_LENGTH).collect())
+}
+
+fn has_refreshable_bookmarks(registry: &ProviderRegistry, task: &Task) -> bool {
+    task.contextswitch.iter().any(|contextswitch| {
+        contextswitch
+            .bookmarks
+            .iter()
+            .any(|bookmark| registry.find(&bookmark.uri).is_some())
+    })
+}
+
+async fn refresh_task(
+    registry: &ProviderRegistry,
+    engine: &RuleEngine,
+    task: Task,
+) -> Result<Task, ContextswitchError> {
+    let bookmarks = match &task.contextswitch {
+        Some(contextswitch) => contextswitch.bookmarks.clone(),
+        None => return Ok(task),
+    };
+    let now = Utc::now();
+    let mut bookmark_events = vec![];
+    let mut refreshed_bookmarks = vec![];
+    for bookmark in bookmarks.iter() {
+        // A provider failure should not prevent other bookmarks from being refreshed
+        match registry.enrich(bookmark, now).await {
+            Some(Ok((enriched, bookmark_event))) => {
+                bookmark_events.extend(bookmark_event);
+                refreshed_bookmarks.push(enriched);
+            }
+            Some(Err(e)) => warn!("Cannot refresh bookmark {}: {:?}", bookmark.uri, e),
+            None => (),
+        }
+    }
+    let rule_matches = rules::match_rules(engine, &task, &bookmark_events);
+    let history_entries = rules::history_entries(&rule_matches, now);
+
+    // Providers are not queried while the task is locked, only what they
+    // returned is applied to the current bookmarks
+    let task = update_contextswitch(&task.id, |_, contextswitch| {
+        for bookmark in contextswitch.bookmarks.iter_mut() {
+            if let Some(refreshed_bookmark) = refreshed_bookmarks
+                .iter()
+                .find(|refreshed_bookmark| refreshed_bookmark.uri == bookmark.uri)
+            {
+                bookmark.content = refreshed_bookmark.content.clone();
+                bookmark.kind = refreshed_bookmark.kind;
+            }
+        }
+        contextswitch.history.extend(history_entries);
+        Ok(())
+    })
+    .await?;
+    // Rule actions are applied last so that they are not reverted by the update
+    rules::apply_rule_matches(task, &rule_matches).await
+}
+
+pub async fn update_bookmarks<F>(task_id: &TaskId, update: F) -> Result<Task, ContextswitchError>
+where
+    F: FnOnce(Vec<Bookmark>) -> Result<Vec<Bookmark>, ContextswitchError>,
+{
+    update_contextswitch(task_id, |_, contextswitch| {
+        contextswitch.bookmarks = update(std::mem::take(&mut contextswitch.bookmarks))?;
+        Ok(())
+    })
+    .await
+}
+
+// Adding a page already bookmarked leaves the task unchanged
+#[tracing::instrument(level = "debug", skip(registry, canonicalizer, new_bookmark), fields(uri = %new_bookmark.uri))]
+pub async fn add_bookmark(
+    registry: &ProviderRegistry,
+    canonicalizer: &UrlCanonicalizer,
+    task_id: &TaskId,
+    new_bookmark: NewBookmark,
+) -> Result<Task, ContextswitchError> {
+    let bookmark = Bookmark {
+        kind: Some(registry.bookmark_kind(&new_bookmark.uri)),
+        uri: new_bookmark.uri,
+        label: new_bookmark
+            .label
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty()),
+        added_at: Some(Utc::now()),
+        ..Default::default()
+    };
+
+    update_contextswitch(task_id, |_, contextswitch| {
+        contextswitch.add_bookmark(bookmark, canonicalizer);
+        Ok(())
+    })
+    .await
+}
+
+#[tracing::instrument(level = "debug", skip(canonicalizer, bookmark_pin), fields(uri = %bookmark_pin.uri))]
+pub async fn pin_bookmark(
+    canonicalizer: &UrlCanonicalizer,
+    task_id: &TaskId,
+    bookmark_pin: BookmarkPin,
+) -> Result<Task, ContextswitchError> {
+    update_bookmarks(task_id, |mut bookmarks| {
+        let bookmark = bookmarks
+            .iter_mut()
+            .find(|bookmark| canonicalizer.same_url(&bookmark.uri, &bookmark_pin.uri))
+            .ok_or_else(|| {
+                ContextswitchError::NotFoundError(format!("Bookmark {}", bookmark_pin.uri))
+            })?;
+        bookmark.pinned = bookmark_pin.pinned;
+        Ok(bookmarks)
+    })
+    .await
+}
+
+// The new order must list every bookmark, positions follow it
+fn reorder(
+    bookmarks: Vec<Bookmark>,
+    order: &[String],
+    canonicalizer: &UrlCanonicalizer,
+) -> Result<Vec<Bookmark>, ContextswitchError> {
+    let order = order
+        .iter()
+        .map(|uri| {
+            uri.parse::<Uri>()
+                .map(|uri| canonicalizer.canonical_url(&uri))
+                .map_err(|_| ContextswitchError::InvalidInputError(format!("Invalid URL {}", uri)))
+        })
+        .collect::<Result<Vec<String>, ContextswitchError>>()?;
+    let uris: BTreeSet<String> = bookmarks
+        .iter()
+        .map(|bookmark| canonicalizer.canonical_url(&bookmark.uri))
+        .collect();
+    let ordered_uris: BTreeSet<String> = order.iter().cloned().collect();
+    if uris != ordered_uris {
+        return Err(ContextswitchError::InvalidInputError(
+            "Bookmark order must list every bookmark".to_string(),
+        ));
+    }
+
+    let mut bookmarks: Vec<Bookmark> = bookmarks
+        .into_iter()
+        .map(|bookmark| {
+            let canonical_url = canonicalizer.canonical_url(&bookmark.uri);
+            let position = order
+                .iter()
+                .position(|uri| uri == &canonical_url)
+                .and_then(|position| u32::try_from(position).ok());
+            Bookmark {
+                position,
+                ..bookmark
+            }
+        })
+        .collect();
+    bookmarks.sort_by_key(|bookmark| bookmark.position);
+    Ok(bookmarks)
+}
+
+#[tracing::instrument(level = "debug", skip(canonicalizer, order))]
+pub async fn reorder_bookmarks(
+    canonicalizer: &UrlCanonicalizer,
+    task_id: &TaskId,
+    order: &[String],
+) -> Result<Task, ContextswitchError> {
+    update_bookmarks(task_id, |bookmarks| {
+        reorder(bookmarks, order, canonicalizer)
+    })
+    .await
+}
+
+#[tracing::instrument(level = "debug", skip(registry, engine))]
+pub async fn refresh_task_bookmarks(
+    registry: &ProviderRegistry,
+    engine: &RuleEngine,
+    task_id: &TaskId,
+) -> Result<Task, ContextswitchError> {
+    let task = get_task(task_id)?;
+
+    refresh_task(registry, engine, task).await
+}
+
+#[tracing::instrument(level = "debug", skip(registry, engine))]
+pub async fn refresh_bookmarks(
+    registry: &ProviderRegistry,
+    engine: &RuleEngine,
+) -> Result<Vec<Task>, ContextswitchError> {
+    let mut tasks = vec![];
+    for task in list_tasks(vec!["status:pending"])?
+        .into_iter()
+        .filter(|task| has_refreshable_bookmarks(registry, task))
+    {
+        tasks.push(refresh_task(registry, engine, task).await?);
+    }
+
+    Ok(tasks)
+}
+
+#[tracing::instrument(level = "debug")]
+pub fn lookup_bookmarks(
+    url: &str,
+    lookup_match: LookupMatch,
+) -> Result<Vec<Task>, ContextswitchError> {
+    let uri = url
+        .parse::<Uri>()
+        .map_err(|_| ContextswitchError::InvalidInputError(format!("Invalid URL {}", url)))?;
+
+    let mut tasks = vec![];
+    for task_id in index::lookup(&uri, lookup_match) {
+        match get_task(&task_id) {
+            Ok(task) => tasks.push(task),
+            // The task may have been purged since it was indexed
+            Err(ContextswitchError::NotFoundError(_)) => (),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(tasks)
+}
+
+fn mark_seen(bookmark: &mut Bookmark) {
+    if let Some(BookmarkDetails::Slack(details)) = bookmark
+        .content
+        .as_mut()
+        .and_then(|content| content.details.as_mut())
+    {
+        details.seen_reply_count = details.reply_count;
+    }
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn mark_bookmarks_seen(task_id: &TaskId) -> Result<Task, ContextswitchError> {
+    update_contextswitch(task_id, |_, contextswitch| {
+        contextswitch.bookmarks.iter_mut().for_each(mark_seen);
+        Ok(())
+    })
+    .await
+}
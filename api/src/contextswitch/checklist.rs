@@ -0,0 +1,209 @@
+use super::{
+    add_task_with_description, get_task, run_task_commands, update_contextswitch,
+    ContextswitchError,
+};
+use chrono::{DateTime, Utc};
+use contextswitch::{ChecklistItem, Task, TaskId};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+fn find_item<'a>(
+    checklist: &'a mut [ChecklistItem],
+    item_id: &Uuid,
+) -> Result<&'a mut ChecklistItem, ContextswitchError> {
+    checklist
+        .iter_mut()
+        .find(|item| &item.id == item_id)
+        .ok_or_else(|| ContextswitchError::NotFoundError(format!("Checklist item {}", item_id)))
+}
+
+fn unpromoted_item<'a>(
+    checklist: &'a mut [ChecklistItem],
+    item_id: &Uuid,
+) -> Result<&'a mut ChecklistItem, ContextswitchError> {
+    let item = find_item(checklist, item_id)?;
+    if let Some(child_id) = &item.task_id {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Checklist item {} was already promoted to task {}",
+            item_id, child_id
+        )));
+    }
+
+    Ok(item)
+}
+
+fn toggle_item(
+    checklist: &mut [ChecklistItem],
+    item_id: &Uuid,
+    now: DateTime<Utc>,
+) -> Result<(), ContextswitchError> {
+    let item = find_item(checklist, item_id)?;
+    item.done = !item.done;
+    item.done_at = item.done.then_some(now);
+
+    Ok(())
+}
+
+// The new order must list every item exactly once
+fn reorder_items(
+    checklist: Vec<ChecklistItem>,
+    order: &[Uuid],
+) -> Result<Vec<ChecklistItem>, ContextswitchError> {
+    let item_ids: BTreeSet<&Uuid> = checklist.iter().map(|item| &item.id).collect();
+    let ordered_ids: BTreeSet<&Uuid> = order.iter().collect();
+    if order.len() != checklist.len() || item_ids != ordered_ids {
+        return Err(ContextswitchError::InvalidInputError(
+            "Checklist order must list every item once".to_string(),
+        ));
+    }
+
+    let mut checklist = checklist;
+    checklist.sort_by_key(|item| order.iter().position(|id| id == &item.id));
+    Ok(checklist)
+}
+
+async fn update_checklist<F>(task_id: &TaskId, update: F) -> Result<Task, ContextswitchError>
+where
+    F: FnOnce(Vec<ChecklistItem>) -> Result<Vec<ChecklistItem>, ContextswitchError>,
+{
+    update_contextswitch(task_id, |_, contextswitch| {
+        contextswitch.checklist = update(std::mem::take(&mut contextswitch.checklist))?;
+        Ok(())
+    })
+    .await
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn add_item(task_id: &TaskId, text: &str) -> Result<Task, ContextswitchError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(ContextswitchError::InvalidInputError(
+            "Checklist item text cannot be empty".to_string(),
+        ));
+    }
+
+    update_checklist(task_id, |mut checklist| {
+        checklist.push(ChecklistItem {
+            id: Uuid::new_v4(),
+            text: text.to_string(),
+            done: false,
+            done_at: None,
+            task_id: None,
+        });
+        Ok(checklist)
+    })
+    .await
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn toggle_item_done(
+    task_id: &TaskId,
+    item_id: &Uuid,
+) -> Result<Task, ContextswitchError> {
+    update_checklist(task_id, |mut checklist| {
+        toggle_item(&mut checklist, item_id, Utc::now())?;
+        Ok(checklist)
+    })
+    .await
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn reorder_checklist(
+    task_id: &TaskId,
+    order: &[Uuid],
+) -> Result<Task, ContextswitchError> {
+    update_checklist(task_id, |checklist| reorder_items(checklist, order)).await
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn remove_item(task_id: &TaskId, item_id: &Uuid) -> Result<Task, ContextswitchError> {
+    update_checklist(task_id, |mut checklist| {
+        find_item(&mut checklist, item_id)?;
+        checklist.retain(|item| &item.id != item_id);
+        Ok(checklist)
+    })
+    .await
+}
+
+// The child task inherits the parent project, and the parent task depends on
+// it so that Taskwarrior blocks the parent until the child is done
+#[tracing::instrument(level = "debug")]
+pub async fn promote_item(task_id: &TaskId, item_id: &Uuid) -> Result<Task, ContextswitchError> {
+    let task = get_task(task_id)?;
+    let mut checklist = task
+        .contextswitch
+        .map(|contextswitch| contextswitch.checklist)
+        .unwrap_or_default();
+    let text = unpromoted_item(&mut checklist, item_id)?.text.clone();
+
+    let project_arg = task
+        .project
+        .as_ref()
+        .map(|project| format!("project:{}", project));
+    let child =
+        add_task_with_description(project_arg.iter().map(String::as_str).collect(), &text).await?;
+    // The item may have been promoted while the child task was created
+    let task = update_contextswitch(task_id, |_, contextswitch| {
+        unpromoted_item(&mut contextswitch.checklist, item_id)?.task_id = Some(child.id.clone());
+        Ok(())
+    })
+    .await?;
+    run_task_commands(&task.id, |_| {
+        vec![vec!["mod".to_string(), format!("depends:{}", child.id)]]
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn checklist(texts: &[&str]) -> Vec<ChecklistItem> {
+        texts
+            .iter()
+            .map(|text| ChecklistItem {
+                id: Uuid::new_v4(),
+                text: text.to_string(),
+                done: false,
+                done_at: None,
+                task_id: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_toggle_item() {
+        let now = Utc.ymd(2022, 1, 1).and_hms(10, 0, 0);
+        let mut checklist = checklist(&["write", "review"]);
+        let item_id = checklist[1].id;
+
+        toggle_item(&mut checklist, &item_id, now).unwrap();
+        assert!(checklist[1].done);
+        assert_eq!(checklist[1].done_at, Some(now));
+
+        toggle_item(&mut checklist, &item_id, now).unwrap();
+        assert!(!checklist[1].done);
+        assert_eq!(checklist[1].done_at, None);
+
+        assert!(toggle_item(&mut checklist, &Uuid::new_v4(), now).is_err());
+    }
+
+    #[test]
+    fn test_reorder_items() {
+        let checklist = checklist(&["write", "review", "merge"]);
+        let order = vec![checklist[2].id, checklist[0].id, checklist[1].id];
+
+        let reordered = reorder_items(checklist.clone(), &order).unwrap();
+        assert_eq!(
+            reordered
+                .iter()
+                .map(|item| item.text.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["merge", "write", "review"]
+        );
+
+        assert!(reorder_items(checklist.clone(), &order[1..]).is_err());
+        assert!(reorder_items(checklist, &[order[0], order[0], order[1]]).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use super::index;
+use contextswitch::{Status, Task, TaskEvent};
+use tokio::sync::broadcast;
+
+const EVENTS_CAPACITY: usize = 256;
+
+lazy_static! {
+    static ref TASK_EVENTS: broadcast::Sender<TaskEvent> = broadcast::channel(EVENTS_CAPACITY).0;
+}
+
+pub fn subscribe() -> broadcast::Receiver<TaskEvent> {
+    TASK_EVENTS.subscribe()
+}
+
+#[tracing::instrument(level = "debug", skip(event), fields(task_id = %event.task().id))]
+pub fn publish(event: TaskEvent) {
+    // Every write path goes through here, keeping the bookmark index up to date
+    index::record(&event);
+    // Sending only fails when nobody is listening
+    let _ = TASK_EVENTS.send(event);
+}
+
+pub fn publish_created(task: &Task) {
+    publish(TaskEvent::Created(task.clone()));
+}
+
+pub fn publish_updated(task: &Task) {
+    publish(if task.status == Status::Deleted {
+        TaskEvent::Deleted(task.clone())
+    } else {
+        TaskEvent::Updated(task.clone())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+
+    fn task(status: Status) -> Task {
+        Task {
+            status,
+            ..test_helpers::task("events")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_deleted_task_as_deleted_event() {
+        let mut receiver = subscribe();
+        let task = task(Status::Deleted);
+
+        publish_updated(&task);
+
+        // Other tests may publish on the same channel concurrently
+        loop {
+            let event = receiver.recv().await.unwrap();
+            if event.task().id == task.id {
+                assert_eq!(event, TaskEvent::Deleted(task));
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use super::{get_task, list_tasks, modify_task, time, ContextswitchError};
+use chrono::{DateTime, Utc};
+use contextswitch::{ContextSnapshot, Focus, FocusRequest, Status, Task, TaskId};
+
+fn snapshot(focus_request: FocusRequest, now: DateTime<Utc>) -> ContextSnapshot {
+    ContextSnapshot {
+        date: now,
+        tabs: focus_request.tabs,
+        note: focus_request
+            .note
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty()),
+    }
+}
+
+fn stop_task(task: Task, snapshot: Option<ContextSnapshot>, now: DateTime<Utc>) -> Task {
+    // The note is kept with the work session as well
+    let task = match (task.start, &snapshot) {
+        (Some(start), Some(snapshot)) => {
+            time::record_session(task, start, now, snapshot.note.clone())
+        }
+        _ => task,
+    };
+    let contextswitch = match snapshot {
+        Some(snapshot) => {
+            let mut contextswitch = task.contextswitch.clone().unwrap_or_default();
+            contextswitch.snapshot = Some(snapshot);
+            Some(contextswitch)
+        }
+        None => task.contextswitch.clone(),
+    };
+
+    Task {
+        start: None,
+        contextswitch,
+        ..task
+    }
+}
+
+// Only the most recently started task receives the snapshot, other active
+// tasks are stopped as well
+#[tracing::instrument(level = "debug", skip(focus_request))]
+pub async fn focus_task(
+    task_id: &TaskId,
+    focus_request: FocusRequest,
+) -> Result<Focus, ContextswitchError> {
+    let task = get_task(task_id)?;
+    if task.status != Status::Pending {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Task {} is not pending",
+            task_id
+        )));
+    }
+    let now = Utc::now();
+    let mut active_tasks: Vec<Task> = list_tasks(vec!["+ACTIVE"])?
+        .into_iter()
+        .filter(|active_task| active_task.id != task.id)
+        .collect();
+    active_tasks.sort_by_key(|active_task| active_task.start);
+
+    let mut snapshot = Some(snapshot(focus_request, now));
+    let mut previous_task = None;
+    while let Some(active_task) = active_tasks.pop() {
+        let stopped_task = modify_task(&active_task.id, |active_task| {
+            Ok(Some(stop_task(active_task, snapshot.take(), now)))
+        })
+        .await?;
+        previous_task.get_or_insert(stopped_task);
+    }
+
+    let task = modify_task(&task.id, |task| {
+        Ok(task.start.is_none().then(|| Task {
+            start: Some(now),
+            ..task
+        }))
+    })
+    .await?;
+    let snapshot = task
+        .contextswitch
+        .as_ref()
+        .and_then(|contextswitch| contextswitch.snapshot.clone());
+
+    Ok(Focus {
+        task,
+        previous_task,
+        snapshot,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use contextswitch::Tab;
+    use http::uri::Uri;
+
+    #[test]
+    fn test_snapshot_ignores_blank_note() {
+        let now = Utc.ymd(2022, 1, 1).and_hms(1, 0, 0);
+        let tabs = vec![Tab {
+            url: "https://example.com/".parse::<Uri>().unwrap(),
+            title: None,
+        }];
+
+        assert_eq!(
+            snapshot(
+                FocusRequest {
+                    tabs: tabs.clone(),
+                    note: Some("  ".to_string()),
+                },
+                now
+            ),
+            ContextSnapshot {
+                date: now,
+                tabs,
+                note: None,
+            }
+        );
+        assert_eq!(
+            snapshot(
+                FocusRequest {
+                    tabs: vec![],
+                    note: Some(" halfway through the review\n".to_string()),
+                },
+                now
+            )
+            .note,
+            Some("halfway through the review".to_string())
+        );
+    }
+}
@@ -0,0 +1,605 @@
+use super::bookmarks::content_preview;
+use super::providers::BookmarkProvider;
+use crate::configuration::GithubSettings;
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use contextswitch::canonical::canonical_host;
+use contextswitch::{
+    Bookmark, BookmarkContent, BookmarkDetails, BookmarkEvent, BookmarkKind, GithubDetails,
+    GithubKind, ProviderInfo, StateChange,
+};
+use http::uri::Uri;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug)]
+pub struct GithubClient {
+    client: reqwest::Client,
+    api_base_url: String,
+    token: Option<String>,
+}
+
+pub fn new_github_client(settings: &GithubSettings) -> GithubClient {
+    GithubClient {
+        client: reqwest::Client::new(),
+        api_base_url: settings.api_base_url.trim_end_matches('/').to_string(),
+        token: settings.token.clone(),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GithubReference {
+    pub owner: String,
+    pub repository: String,
+    pub kind: GithubKind,
+    pub number: u64,
+}
+
+pub fn parse_github_uri(uri: &Uri) -> Option<GithubReference> {
+    match canonical_host(uri).as_deref() {
+        Some("github.com") | Some("www.github.com") => (),
+        _ => return None,
+    }
+    let segments: Vec<&str> = uri.path().split('/').filter(|s| !s.is_empty()).collect();
+    match segments[..] {
+        [owner, repository, kind, number, ..] => Some(GithubReference {
+            owner: owner.to_string(),
+            repository: repository.to_string(),
+            kind: match kind {
+                "issues" => GithubKind::Issue,
+                "pull" => GithubKind::PullRequest,
+                "discussions" => GithubKind::Discussion,
+                _ => return None,
+            },
+            number: number.parse().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubLabel {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubUser {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubIssue {
+    title: String,
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    labels: Vec<GithubLabel>,
+    #[serde(default)]
+    assignees: Vec<GithubUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubCommitRef {
+    sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubPullRequest {
+    title: String,
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    merged: bool,
+    #[serde(default)]
+    labels: Vec<GithubLabel>,
+    #[serde(default)]
+    assignees: Vec<GithubUser>,
+    head: GithubCommitRef,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubCombinedStatus {
+    state: String,
+    #[serde(default)]
+    total_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubCheckRun {
+    status: String,
+    conclusion: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubCheckRuns {
+    #[serde(default)]
+    check_runs: Vec<GithubCheckRun>,
+}
+
+// Commit statuses and GitHub Actions check runs are reported separately,
+// a commit without any of them has no CI status
+fn ci_status(status: &GithubCombinedStatus, check_runs: &GithubCheckRuns) -> Option<String> {
+    let commit_status = match status.state.as_str() {
+        "error" => "failure",
+        state => state,
+    };
+    let statuses: Vec<&str> = (status.total_count > 0)
+        .then_some(commit_status)
+        .into_iter()
+        .chain(check_runs.check_runs.iter().map(|check_run| {
+            match (check_run.status.as_str(), check_run.conclusion.as_deref()) {
+                ("completed", Some("success" | "neutral" | "skipped")) => "success",
+                ("completed", _) => "failure",
+                _ => "pending",
+            }
+        }))
+        .collect();
+
+    ["failure", "pending", "success"]
+        .into_iter()
+        .find(|state| statuses.contains(state))
+        .map(String::from)
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubLabelNodes {
+    nodes: Vec<GithubLabel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubDiscussion {
+    title: String,
+    body: Option<String>,
+    closed: bool,
+    labels: GithubLabelNodes,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubDiscussionRepository {
+    discussion: Option<GithubDiscussion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubDiscussionData {
+    repository: GithubDiscussionRepository,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlResponse<T> {
+    data: T,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GithubItem {
+    pub title: String,
+    pub body: Option<String>,
+    pub state: String,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    pub ci_status: Option<String>,
+}
+
+const DISCUSSION_QUERY: &str = "query($owner: String!, $repository: String!, $number: Int!) {
+  repository(owner: $owner, name: $repository) {
+    discussion(number: $number) { title body closed labels(first: 20) { nodes { name } } }
+  }
+}";
+
+impl GithubClient {
+    fn request(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let request = request
+            .header(reqwest::header::USER_AGENT, "contextswitch")
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json");
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, anyhow::Error> {
+        let url = format!("{}{}", self.api_base_url, path);
+        self.request(self.client.get(&url))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Cannot fetch {}", url))?
+            .json()
+            .await
+            .with_context(|| format!("Cannot parse {}", url))
+    }
+
+    async fn fetch_issue(&self, reference: &GithubReference) -> Result<GithubItem, anyhow::Error> {
+        let issue: GithubIssue = self
+            .get(&format!(
+                "/repos/{}/{}/issues/{}",
+                reference.owner, reference.repository, reference.number
+            ))
+            .await?;
+
+        Ok(GithubItem {
+            title: issue.title,
+            body: issue.body,
+            state: issue.state,
+            labels: issue.labels.into_iter().map(|label| label.name).collect(),
+            assignees: issue.assignees.into_iter().map(|user| user.login).collect(),
+            ci_status: None,
+        })
+    }
+
+    async fn fetch_pull_request(
+        &self,
+        reference: &GithubReference,
+    ) -> Result<GithubItem, anyhow::Error> {
+        let pull_request: GithubPullRequest = self
+            .get(&format!(
+                "/repos/{}/{}/pulls/{}",
+                reference.owner, reference.repository, reference.number
+            ))
+            .await?;
+        let status: GithubCombinedStatus = self
+            .get(&format!(
+                "/repos/{}/{}/commits/{}/status",
+                reference.owner, reference.repository, pull_request.head.sha
+            ))
+            .await?;
+        let check_runs: GithubCheckRuns = self
+            .get(&format!(
+                "/repos/{}/{}/commits/{}/check-runs",
+                reference.owner, reference.repository, pull_request.head.sha
+            ))
+            .await?;
+
+        Ok(GithubItem {
+            title: pull_request.title,
+            body: pull_request.body,
+            state: if pull_request.merged {
+                "merged".to_string()
+            } else {
+                pull_request.state
+            },
+            labels: pull_request
+                .labels
+                .into_iter()
+                .map(|label| label.name)
+                .collect(),
+            assignees: pull_request
+                .assignees
+                .into_iter()
+                .map(|user| user.login)
+                .collect(),
+            ci_status: ci_status(&status, &check_runs),
+        })
+    }
+
+    // Discussions are only available through the GraphQL API
+    async fn fetch_discussion(
+        &self,
+        reference: &GithubReference,
+    ) -> Result<GithubItem, anyhow::Error> {
+        let url = format!("{}/graphql", self.api_base_url);
+        let response: GraphqlResponse<GithubDiscussionData> = self
+            .request(self.client.post(&url))
+            .json(&json!({
+                "query": DISCUSSION_QUERY,
+                "variables": {
+                    "owner": reference.owner,
+                    "repository": reference.repository,
+                    "number": reference.number,
+                }
+            }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Cannot fetch {}", url))?
+            .json()
+            .await
+            .with_context(|| format!("Cannot parse {}", url))?;
+        let discussion = response.data.repository.discussion.with_context(|| {
+            format!(
+                "Discussion {}/{}#{} not found",
+                reference.owner, reference.repository, reference.number
+            )
+        })?;
+
+        Ok(GithubItem {
+            title: discussion.title,
+            body: discussion.body,
+            state: if discussion.closed { "closed" } else { "open" }.to_string(),
+            labels: discussion
+                .labels
+                .nodes
+                .into_iter()
+                .map(|label| label.name)
+                .collect(),
+            assignees: vec![],
+            ci_status: None,
+        })
+    }
+
+    pub async fn fetch(&self, reference: &GithubReference) -> Result<GithubItem, anyhow::Error> {
+        match reference.kind {
+            GithubKind::Issue => self.fetch_issue(reference).await,
+            GithubKind::PullRequest => self.fetch_pull_request(reference).await,
+            GithubKind::Discussion => self.fetch_discussion(reference).await,
+        }
+    }
+}
+
+pub fn bookmark_content(
+    bookmark: &Bookmark,
+    reference: &GithubReference,
+    item: GithubItem,
+    now: DateTime<Utc>,
+) -> BookmarkContent {
+    let mut state_history = github_details(bookmark)
+        .map(|details| details.state_history.clone())
+        .unwrap_or_default();
+    if state_history.last().map(|change| &change.state) != Some(&item.state) {
+        state_history.push(StateChange {
+            state: item.state.clone(),
+            date: now,
+        });
+    }
+
+    BookmarkContent {
+        content_preview: content_preview(item.body.as_deref()),
+        title: item.title,
+        details: Some(BookmarkDetails::Github(GithubDetails {
+            kind: reference.kind,
+            repository: format!("{}/{}", reference.owner, reference.repository),
+            number: reference.number,
+            state: item.state,
+            labels: item.labels,
+            assignees: item.assignees,
+            ci_status: item.ci_status,
+            state_history,
+        })),
+        badge: None,
+    }
+}
+
+fn github_details(bookmark: &Bookmark) -> Option<&GithubDetails> {
+    match bookmark
+        .content
+        .as_ref()
+        .and_then(|content| content.details.as_ref())
+    {
+        Some(BookmarkDetails::Github(details)) => Some(details),
+        _ => None,
+    }
+}
+
+// Only state transitions are events, not the first enrichment of a bookmark
+pub fn bookmark_event(previous: &Bookmark, enriched: &Bookmark) -> Option<BookmarkEvent> {
+    let previous_state = &github_details(previous)?.state;
+    let details = github_details(enriched)?;
+    if previous_state == &details.state {
+        return None;
+    }
+
+    Some(BookmarkEvent {
+        uri: enriched.uri.clone(),
+        provider: "github".to_string(),
+        kind: details.kind.to_string(),
+        state: details.state.clone(),
+    })
+}
+
+#[async_trait]
+impl BookmarkProvider for GithubClient {
+    fn info(&self) -> ProviderInfo {
+        ProviderInfo {
+            name: "github".to_string(),
+            icon: "github".to_string(),
+            uri_patterns: ["issues", "pull", "discussions"]
+                .iter()
+                .map(|kind| {
+                    format!(
+                        "https://github.com/{{owner}}/{{repository}}/{}/{{number}}",
+                        kind
+                    )
+                })
+                .collect(),
+            kinds: [
+                GithubKind::Issue,
+                GithubKind::PullRequest,
+                GithubKind::Discussion,
+            ]
+            .iter()
+            .map(GithubKind::to_string)
+            .collect(),
+            events: vec![
+                "open".to_string(),
+                "closed".to_string(),
+                "merged".to_string(),
+            ],
+        }
+    }
+
+    fn handles(&self, uri: &Uri) -> bool {
+        parse_github_uri(uri).is_some()
+    }
+
+    fn kind(&self, uri: &Uri) -> String {
+        parse_github_uri(uri)
+            .map(|reference| reference.kind.to_string())
+            .unwrap_or_default()
+    }
+
+    fn bookmark_kind(&self) -> BookmarkKind {
+        BookmarkKind::Issue
+    }
+
+    async fn enrich(
+        &self,
+        bookmark: &Bookmark,
+        now: DateTime<Utc>,
+    ) -> Result<BookmarkContent, anyhow::Error> {
+        let reference = parse_github_uri(&bookmark.uri)
+            .with_context(|| format!("{} is not a GitHub URI", bookmark.uri))?;
+        let item = self.fetch(&reference).await?;
+
+        Ok(bookmark_content(bookmark, &reference, item, now))
+    }
+
+    fn bookmark_event(&self, previous: &Bookmark, enriched: &Bookmark) -> Option<BookmarkEvent> {
+        bookmark_event(previous, enriched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn reference(kind: GithubKind) -> GithubReference {
+        GithubReference {
+            owner: "dax".to_string(),
+            repository: "contextswitch".to_string(),
+            kind,
+            number: 1,
+        }
+    }
+
+    fn item(state: &str) -> GithubItem {
+        GithubItem {
+            title: "Issue".to_string(),
+            body: Some("Issue description".to_string()),
+            state: state.to_string(),
+            labels: vec!["bug".to_string()],
+            assignees: vec!["dax".to_string()],
+            ci_status: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_github_uri() {
+        let parse = |uri: &str| parse_github_uri(&uri.parse::<Uri>().unwrap());
+
+        assert_eq!(
+            parse("https://github.com/dax/contextswitch/issues/1"),
+            Some(reference(GithubKind::Issue))
+        );
+        assert_eq!(
+            parse("https://github.com/dax/contextswitch/pull/1/files"),
+            Some(reference(GithubKind::PullRequest))
+        );
+        assert_eq!(
+            parse("https://www.github.com/dax/contextswitch/discussions/1"),
+            Some(reference(GithubKind::Discussion))
+        );
+        assert_eq!(
+            parse("https://GitHub.com/dax/contextswitch/issues/1"),
+            Some(reference(GithubKind::Issue))
+        );
+        assert_eq!(parse("https://github.com/dax/contextswitch"), None);
+        assert_eq!(parse("https://github.com/dax/contextswitch/wiki/1"), None);
+        assert_eq!(parse("https://gitlab.com/dax/contextswitch/issues/1"), None);
+    }
+
+    #[test]
+    fn test_ci_status() {
+        let status = |state: &str, total_count: u64| GithubCombinedStatus {
+            state: state.to_string(),
+            total_count,
+        };
+        let check_runs = |runs: &[(&str, Option<&str>)]| GithubCheckRuns {
+            check_runs: runs
+                .iter()
+                .map(|(status, conclusion)| GithubCheckRun {
+                    status: status.to_string(),
+                    conclusion: conclusion.map(String::from),
+                })
+                .collect(),
+        };
+
+        assert_eq!(ci_status(&status("pending", 0), &check_runs(&[])), None);
+        assert_eq!(
+            ci_status(
+                &status("pending", 0),
+                &check_runs(&[("completed", Some("success"))])
+            ),
+            Some("success".to_string())
+        );
+        assert_eq!(
+            ci_status(
+                &status("success", 1),
+                &check_runs(&[("in_progress", None), ("completed", Some("skipped"))])
+            ),
+            Some("pending".to_string())
+        );
+        assert_eq!(
+            ci_status(
+                &status("error", 1),
+                &check_runs(&[("completed", Some("success"))])
+            ),
+            Some("failure".to_string())
+        );
+    }
+
+    #[test]
+    fn test_enrich_bookmark_tracks_state_changes() {
+        let bookmark = Bookmark {
+            uri: "https://github.com/dax/contextswitch/issues/1"
+                .parse::<Uri>()
+                .unwrap(),
+            content: None,
+            ..Default::default()
+        };
+        let reference = reference(GithubKind::Issue);
+        let opened_at = Utc.ymd(2022, 1, 1).and_hms(1, 0, 0);
+        let closed_at = Utc.ymd(2022, 1, 2).and_hms(1, 0, 0);
+
+        let enrich = |bookmark: &Bookmark, state: &str, date: DateTime<Utc>| Bookmark {
+            content: Some(bookmark_content(bookmark, &reference, item(state), date)),
+            ..bookmark.clone()
+        };
+
+        let opened = enrich(&bookmark, "open", opened_at);
+        let still_opened = enrich(&opened, "open", closed_at);
+        let closed = enrich(&still_opened, "closed", closed_at);
+
+        assert_eq!(still_opened, opened);
+        assert_eq!(bookmark_event(&bookmark, &opened), None);
+        assert_eq!(bookmark_event(&opened, &still_opened), None);
+        assert_eq!(
+            bookmark_event(&still_opened, &closed),
+            Some(BookmarkEvent {
+                uri: bookmark.uri.clone(),
+                provider: "github".to_string(),
+                kind: "issue".to_string(),
+                state: "closed".to_string(),
+            })
+        );
+        let content = closed.content.unwrap();
+        assert_eq!(content.title, "Issue");
+        assert_eq!(
+            content.content_preview,
+            Some("Issue description".to_string())
+        );
+        assert_eq!(
+            content.details,
+            Some(BookmarkDetails::Github(GithubDetails {
+                kind: GithubKind::Issue,
+                repository: "dax/contextswitch".to_string(),
+                number: 1,
+                state: "closed".to_string(),
+                labels: vec!["bug".to_string()],
+                assignees: vec!["dax".to_string()],
+                ci_status: None,
+                state_history: vec![
+                    StateChange {
+                        state: "open".to_string(),
+                        date: opened_at
+                    },
+                    StateChange {
+                        state: "closed".to_string(),
+                        date: closed_at
+                    },
+                ],
+            }))
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use super::taskwarrior;
+use contextswitch::canonical::{canonical_host, UrlCanonicalizer};
+use contextswitch::{Status, Task, TaskEvent, TaskId};
+use http::uri::Uri;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::RwLock;
+use uuid::Uuid;
+
+lazy_static! {
+    static ref BOOKMARK_INDEX: RwLock<BookmarkIndex> = RwLock::new(BookmarkIndex::default());
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LookupMatch {
+    #[default]
+    Exact,
+    Prefix,
+    Host,
+}
+
+#[derive(Default)]
+pub struct BookmarkIndex {
+    canonicalizer: UrlCanonicalizer,
+    urls: BTreeMap<String, BTreeSet<Uuid>>,
+    hosts: BTreeMap<String, BTreeSet<Uuid>>,
+    task_uris: BTreeMap<Uuid, Vec<Uri>>,
+}
+
+fn add_entry(entries: &mut BTreeMap<String, BTreeSet<Uuid>>, key: String, uuid: Uuid) {
+    entries.entry(key).or_default().insert(uuid);
+}
+
+fn remove_entry(entries: &mut BTreeMap<String, BTreeSet<Uuid>>, key: &str, uuid: &Uuid) {
+    if let Some(uuids) = entries.get_mut(key) {
+        uuids.remove(uuid);
+        if uuids.is_empty() {
+            entries.remove(key);
+        }
+    }
+}
+
+// Completed and deleted tasks are not what the current page belongs to
+fn indexed_uris(task: &Task) -> Vec<Uri> {
+    match (&task.status, &task.contextswitch) {
+        (Status::Pending | Status::Recurring, Some(contextswitch)) => contextswitch
+            .bookmarks
+            .iter()
+            .map(|bookmark| bookmark.uri.clone())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+impl BookmarkIndex {
+    fn remove_task(&mut self, uuid: &Uuid) {
+        for uri in self.task_uris.remove(uuid).unwrap_or_default() {
+            remove_entry(
+                &mut self.urls,
+                &self.canonicalizer.canonical_url(&uri),
+                uuid,
+            );
+            if let Some(host) = canonical_host(&uri) {
+                remove_entry(&mut self.hosts, &host, uuid);
+            }
+        }
+    }
+
+    pub fn index_task(&mut self, task: &Task) {
+        let uuid = task.id.0;
+        self.remove_task(&uuid);
+        let uris = indexed_uris(task);
+        if uris.is_empty() {
+            return;
+        }
+        for uri in uris.iter() {
+            add_entry(&mut self.urls, self.canonicalizer.canonical_url(uri), uuid);
+            if let Some(host) = canonical_host(uri) {
+                add_entry(&mut self.hosts, host, uuid);
+            }
+        }
+        self.task_uris.insert(uuid, uris);
+    }
+
+    pub fn record(&mut self, task_event: &TaskEvent) {
+        match task_event {
+            TaskEvent::Created(task)
+            | TaskEvent::Updated(task)
+            | TaskEvent::PomodoroCompleted(task) => self.index_task(task),
+            TaskEvent::Deleted(task) => self.remove_task(&task.id.0),
+        }
+    }
+
+    pub fn lookup(&self, uri: &Uri, lookup_match: LookupMatch) -> Vec<TaskId> {
+        let uuids: BTreeSet<Uuid> = match lookup_match {
+            LookupMatch::Exact => self
+                .urls
+                .get(&self.canonicalizer.canonical_url(uri))
+                .cloned()
+                .unwrap_or_default(),
+            LookupMatch::Prefix => {
+                let prefix = self.canonicalizer.canonical_url(uri);
+                self.urls
+                    .range(prefix.clone()..)
+                    .take_while(|(url, _)| url.starts_with(&prefix))
+                    .flat_map(|(_, uuids)| uuids.iter().copied())
+                    .collect()
+            }
+            LookupMatch::Host => canonical_host(uri)
+                .and_then(|host| self.hosts.get(&host).cloned())
+                .unwrap_or_default(),
+        };
+
+        uuids.into_iter().map(TaskId).collect()
+    }
+}
+
+pub fn rebuild(canonicalizer: &UrlCanonicalizer) -> Result<(), taskwarrior::TaskwarriorError> {
+    let mut index = BookmarkIndex {
+        canonicalizer: canonicalizer.clone(),
+        ..Default::default()
+    };
+    for task in taskwarrior::list_tasks(vec![])?.iter() {
+        index.index_task(&Task::from(task));
+    }
+
+    *BOOKMARK_INDEX.write().unwrap() = index;
+    Ok(())
+}
+
+pub fn record(task_event: &TaskEvent) {
+    BOOKMARK_INDEX.write().unwrap().record(task_event);
+}
+
+pub fn lookup(uri: &Uri, lookup_match: LookupMatch) -> Vec<TaskId> {
+    BOOKMARK_INDEX.read().unwrap().lookup(uri, lookup_match)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+    use contextswitch::{Bookmark, ContextswitchData};
+
+    fn task(status: Status, urls: &[&str]) -> Task {
+        Task {
+            status,
+            contextswitch: Some(ContextswitchData {
+                bookmarks: urls
+                    .iter()
+                    .map(|url| Bookmark {
+                        uri: url.parse::<Uri>().unwrap(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+            ..test_helpers::task("index")
+        }
+    }
+
+    fn uri(url: &str) -> Uri {
+        url.parse::<Uri>().unwrap()
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        let canonicalizer = UrlCanonicalizer::default();
+
+        assert_eq!(
+            canonicalizer.canonical_url(&uri("HTTPS://Example.COM:443/Docs/?page=2")),
+            "https://example.com/Docs?page=2"
+        );
+        assert_eq!(
+            canonicalizer.canonical_url(&uri("http://example.com:8080/")),
+            "http://example.com:8080"
+        );
+        assert_eq!(
+            canonicalizer.canonical_url(&uri(
+                "https://example.com/post?utm_source=news&id=42&UTM_Medium=email&fbclid=abc#top"
+            )),
+            "https://example.com/post?id=42"
+        );
+    }
+
+    #[test]
+    fn test_configured_tracking_params() {
+        let mut index = BookmarkIndex {
+            canonicalizer: UrlCanonicalizer::new(vec!["ref".to_string()]),
+            ..Default::default()
+        };
+        let shared = task(Status::Pending, &["https://example.com/?ref=homepage"]);
+        index.index_task(&shared);
+
+        assert_eq!(
+            index.lookup(&uri("https://example.com"), LookupMatch::Exact),
+            vec![shared.id.clone()]
+        );
+        assert!(index
+            .lookup(
+                &uri("https://example.com/?utm_source=news"),
+                LookupMatch::Exact
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn test_lookup_matches() {
+        let mut index = BookmarkIndex::default();
+        let docs = task(Status::Pending, &["https://example.com/docs/intro"]);
+        let blog = task(Status::Pending, &["https://example.com/blog"]);
+        index.index_task(&docs);
+        index.index_task(&blog);
+
+        assert_eq!(
+            index.lookup(&uri("https://EXAMPLE.com/docs/intro/"), LookupMatch::Exact),
+            vec![docs.id.clone()]
+        );
+        assert_eq!(
+            index.lookup(&uri("https://example.com/docs"), LookupMatch::Prefix),
+            vec![docs.id.clone()]
+        );
+        assert_eq!(
+            index.lookup(&uri("example.com"), LookupMatch::Host).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_record_task_events() {
+        let mut index = BookmarkIndex::default();
+        let pending = task(Status::Pending, &["https://example.com/a"]);
+        index.record(&TaskEvent::Created(pending.clone()));
+
+        let moved = Task {
+            contextswitch: task(Status::Pending, &["https://example.com/b"]).contextswitch,
+            ..pending.clone()
+        };
+        index.record(&TaskEvent::Updated(moved.clone()));
+        assert!(index
+            .lookup(&uri("https://example.com/a"), LookupMatch::Exact)
+            .is_empty());
+        assert_eq!(
+            index.lookup(&uri("https://example.com/b"), LookupMatch::Exact),
+            vec![pending.id.clone()]
+        );
+
+        index.record(&TaskEvent::Updated(Task {
+            status: Status::Completed,
+            ..moved
+        }));
+        assert!(index
+            .lookup(&uri("example.com"), LookupMatch::Host)
+            .is_empty());
+    }
+}
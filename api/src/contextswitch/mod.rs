@@ -1,4 +1,29 @@
+pub mod admin;
 mod api;
+pub mod attachments;
+pub mod bookmarks;
+pub mod checklist;
+pub mod events;
+pub mod focus;
+pub mod github;
+pub mod index;
+pub mod notes;
+pub mod pomodoro;
+pub mod projects;
+pub mod providers;
+pub mod rules;
+pub mod slack;
+pub mod stats;
+pub mod store;
+pub mod tabs;
+pub mod tags;
+pub mod task_notes;
 pub mod taskwarrior;
+#[cfg(test)]
+mod test_helpers;
+pub mod time;
+pub mod views;
+pub mod watcher;
+pub mod webhooks;
 
 pub use api::*;
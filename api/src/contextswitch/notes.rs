@@ -0,0 +1,474 @@
+use super::providers::{self, BookmarkProvider};
+use super::{get_task, update_contextswitch, ContextswitchError};
+use crate::configuration::NotesSettings;
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use contextswitch::{
+    Bookmark, BookmarkContent, BookmarkDetails, BookmarkKind, NoteDetails, ProviderInfo, Task,
+    TaskId,
+};
+use http::uri::Uri;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tracing::warn;
+use uuid::Uuid;
+
+const NOTE_EXTENSION: &str = "md";
+const MAX_SLUG_LENGTH: usize = 50;
+const DEFAULT_TEMPLATE: &str = "# {{description}}
+
+- Task: {{uuid}}
+- Project: {{project}}
+- Created: {{date}}
+
+## Bookmarks
+
+{{bookmarks}}
+
+## Notes
+
+";
+
+lazy_static! {
+    static ref TASK_ID_RE: Regex =
+        Regex::new(r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}")
+            .unwrap();
+}
+
+#[derive(Debug)]
+pub struct Notebook {
+    directory: Option<PathBuf>,
+    template: String,
+}
+
+pub fn new_notebook(settings: &NotesSettings) -> Result<Notebook, std::io::Error> {
+    let template = match &settings.template {
+        Some(template_path) => fs::read_to_string(template_path)?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+
+    Ok(Notebook {
+        directory: settings.directory.as_ref().map(PathBuf::from),
+        template,
+    })
+}
+
+impl Notebook {
+    fn directory(&self) -> Result<&Path, ContextswitchError> {
+        self.directory.as_deref().ok_or_else(|| {
+            ContextswitchError::InvalidInputError("Notes directory is not configured".to_string())
+        })
+    }
+}
+
+fn slugify(description: &str) -> String {
+    let slug = description
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-");
+    let slug: String = slug.chars().take(MAX_SLUG_LENGTH).collect();
+    let slug = slug.trim_end_matches('-');
+
+    if slug.is_empty() {
+        "task".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+fn note_file_name(task: &Task) -> String {
+    let task_id = task.id.to_string();
+    format!(
+        "{}-{}.{}",
+        slugify(&task.description),
+        &task_id[..8],
+        NOTE_EXTENSION
+    )
+}
+
+fn render_bookmarks(task: &Task) -> String {
+    task.contextswitch
+        .iter()
+        .flat_map(|contextswitch| contextswitch.bookmarks.iter())
+        .filter(|bookmark| note_details(bookmark).is_none())
+        .map(|bookmark| match &bookmark.content {
+            Some(content) => format!("- [{}]({})", content.title, bookmark.uri),
+            None => format!("- <{}>", bookmark.uri),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn render_note(template: &str, task: &Task, now: DateTime<Utc>) -> String {
+    template
+        .replace("{{description}}", &task.description)
+        .replace("{{uuid}}", &task.id.to_string())
+        .replace("{{project}}", task.project.as_deref().unwrap_or(""))
+        .replace("{{date}}", &now.format("%Y-%m-%d").to_string())
+        .replace("{{bookmarks}}", &render_bookmarks(task))
+}
+
+// Paths are percent-encoded as a Uri only accepts a subset of ASCII characters
+fn note_uri(path: &Path) -> Result<Uri, ContextswitchError> {
+    let encoded_path: String = path
+        .to_string_lossy()
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'.' | b'-' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect();
+
+    Ok(format!("file://localhost{}", encoded_path)
+        .parse::<Uri>()
+        .with_context(|| format!("Cannot build note URI for {}", path.display()))?)
+}
+
+fn note_title(path: &Path, content: &str) -> String {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+        .or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+        })
+        .unwrap_or_default()
+}
+
+fn note_path(uri: &Uri) -> Option<PathBuf> {
+    if uri.scheme_str() != Some("file") || uri.host() != Some("localhost") {
+        return None;
+    }
+    let encoded_path = uri.path().as_bytes();
+    let mut path = Vec::with_capacity(encoded_path.len());
+    let mut index = 0;
+    while index < encoded_path.len() {
+        match encoded_path[index] {
+            b'%' => {
+                let hex_byte = encoded_path.get(index + 1..index + 3)?;
+                path.push(u8::from_str_radix(std::str::from_utf8(hex_byte).ok()?, 16).ok()?);
+                index += 3;
+            }
+            byte => {
+                path.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    // Relative components could escape the notes directory
+    String::from_utf8(path)
+        .ok()
+        .map(PathBuf::from)
+        .filter(|path| {
+            path.components()
+                .all(|component| matches!(component, Component::RootDir | Component::Normal(_)))
+        })
+}
+
+fn note_content(path: &Path, content: &str) -> BookmarkContent {
+    BookmarkContent {
+        title: note_title(path, content),
+        content_preview: None,
+        details: Some(BookmarkDetails::Note(NoteDetails {
+            path: path.to_string_lossy().to_string(),
+        })),
+        badge: None,
+    }
+}
+
+fn note_bookmark(
+    notebook: &Notebook,
+    path: &Path,
+    content: &str,
+) -> Result<Bookmark, ContextswitchError> {
+    let uri = note_uri(path)?;
+
+    Ok(Bookmark {
+        content: Some(BookmarkContent {
+            badge: Some(providers::badge(notebook, &uri)),
+            ..note_content(path, content)
+        }),
+        uri,
+        kind: Some(BookmarkKind::Note),
+        added_at: Some(Utc::now()),
+        ..Default::default()
+    })
+}
+
+fn note_details(bookmark: &Bookmark) -> Option<&NoteDetails> {
+    match bookmark
+        .content
+        .as_ref()
+        .and_then(|content| content.details.as_ref())
+    {
+        Some(BookmarkDetails::Note(details)) => Some(details),
+        _ => None,
+    }
+}
+
+// Notes already linked to the task are skipped
+async fn add_bookmarks(
+    task_id: &TaskId,
+    bookmarks: Vec<Bookmark>,
+) -> Result<Task, ContextswitchError> {
+    update_contextswitch(task_id, |_, contextswitch| {
+        for bookmark in bookmarks {
+            if !contextswitch
+                .bookmarks
+                .iter()
+                .any(|existing_bookmark| existing_bookmark.uri == bookmark.uri)
+            {
+                contextswitch.bookmarks.push(bookmark);
+            }
+        }
+        Ok(())
+    })
+    .await
+}
+
+#[tracing::instrument(level = "debug", skip(notebook))]
+pub async fn open_task_note(
+    notebook: &Notebook,
+    task_id: &TaskId,
+) -> Result<Task, ContextswitchError> {
+    let directory = notebook.directory()?;
+    let task = get_task(task_id)?;
+    let has_existing_note = task
+        .contextswitch
+        .iter()
+        .flat_map(|contextswitch| contextswitch.bookmarks.iter())
+        .filter_map(note_details)
+        .any(|details| Path::new(&details.path).exists());
+    if has_existing_note {
+        return Ok(task);
+    }
+
+    let path = directory.join(note_file_name(&task));
+    // An existing note is linked again rather than overwritten
+    let content = if path.exists() {
+        fs::read_to_string(&path).with_context(|| format!("Cannot read note {}", path.display()))?
+    } else {
+        let content = render_note(&notebook.template, &task, Utc::now());
+        fs::create_dir_all(directory)
+            .and_then(|_| fs::write(&path, &content))
+            .with_context(|| format!("Cannot write note {}", path.display()))?;
+        content
+    };
+    let bookmark = note_bookmark(notebook, &path, &content)?;
+
+    add_bookmarks(&task.id, vec![bookmark]).await
+}
+
+#[async_trait]
+impl BookmarkProvider for Notebook {
+    fn info(&self) -> ProviderInfo {
+        ProviderInfo {
+            name: "notes".to_string(),
+            icon: "file-text".to_string(),
+            uri_patterns: vec![format!("file://localhost/{{path}}.{}", NOTE_EXTENSION)],
+            kinds: vec!["note".to_string()],
+            events: vec![],
+        }
+    }
+
+    // Only notes of the configured directory are handled
+    fn handles(&self, uri: &Uri) -> bool {
+        match (&self.directory, note_path(uri)) {
+            (Some(directory), Some(path)) => {
+                path.starts_with(directory)
+                    && path.extension().and_then(|extension| extension.to_str())
+                        == Some(NOTE_EXTENSION)
+            }
+            _ => false,
+        }
+    }
+
+    fn kind(&self, _uri: &Uri) -> String {
+        "note".to_string()
+    }
+
+    fn bookmark_kind(&self) -> BookmarkKind {
+        BookmarkKind::Note
+    }
+
+    async fn enrich(
+        &self,
+        bookmark: &Bookmark,
+        _now: DateTime<Utc>,
+    ) -> Result<BookmarkContent, anyhow::Error> {
+        let path = note_path(&bookmark.uri)
+            .with_context(|| format!("{} is not a note URI", bookmark.uri))?;
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read note {}", path.display()))?;
+
+        Ok(note_content(&path, &content))
+    }
+}
+
+fn find_task_ids(content: &str) -> Vec<Uuid> {
+    let mut task_ids: Vec<Uuid> = TASK_ID_RE
+        .find_iter(content)
+        .filter_map(|task_id| Uuid::parse_str(task_id.as_str()).ok())
+        .collect();
+    task_ids.sort();
+    task_ids.dedup();
+    task_ids
+}
+
+// Hidden directories hold application data, like .obsidian or .git
+fn note_files(directory: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = vec![];
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_hidden = path
+            .file_name()
+            .map(|name| name.to_string_lossy().starts_with('.'))
+            .unwrap_or(false);
+        if is_hidden {
+            continue;
+        }
+        if path.is_dir() {
+            files.extend(note_files(&path)?);
+        } else if path.extension().and_then(|extension| extension.to_str()) == Some(NOTE_EXTENSION)
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+#[tracing::instrument(level = "debug", skip(notebook))]
+pub async fn scan_notes(notebook: &Notebook) -> Result<Vec<Task>, ContextswitchError> {
+    let directory = notebook.directory()?;
+    let files = note_files(directory)
+        .with_context(|| format!("Cannot scan notes directory {}", directory.display()))?;
+
+    let mut notes_by_task: BTreeMap<Uuid, Vec<Bookmark>> = BTreeMap::new();
+    for path in files {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("Cannot read note {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        for task_id in find_task_ids(&content) {
+            notes_by_task
+                .entry(task_id)
+                .or_default()
+                .push(note_bookmark(notebook, &path, &content)?);
+        }
+    }
+
+    let mut tasks = vec![];
+    for (task_id, bookmarks) in notes_by_task {
+        let task_id = TaskId(task_id);
+        let task = match get_task(&task_id) {
+            Ok(task) => task,
+            // UUIDs in notes do not always refer to tasks
+            Err(ContextswitchError::NotFoundError(_)) => continue,
+            Err(e) => return Err(e),
+        };
+        let updated_task = add_bookmarks(&task_id, bookmarks).await?;
+        if updated_task.contextswitch != task.contextswitch {
+            tasks.push(updated_task);
+        }
+    }
+
+    Ok(tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+    use chrono::TimeZone;
+    use contextswitch::ContextswitchData;
+
+    fn task() -> Task {
+        Task {
+            id: TaskId(Uuid::parse_str("2c5a4f2b-6a3b-4d1e-9c3f-1f2e3d4c5b6a").unwrap()),
+            project: Some("contextswitch".to_string()),
+            contextswitch: Some(ContextswitchData {
+                bookmarks: vec![Bookmark {
+                    uri: "https://example.com/".parse::<Uri>().unwrap(),
+                    content: None,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..test_helpers::task("Write the release notes!")
+        }
+    }
+
+    #[test]
+    fn test_note_file_name() {
+        assert_eq!(
+            note_file_name(&task()),
+            "write-the-release-notes-2c5a4f2b.md"
+        );
+        assert_eq!(slugify("?!"), "task");
+        assert_eq!(slugify(&"word ".repeat(20)).len(), 49);
+    }
+
+    #[test]
+    fn test_render_note() {
+        let note = render_note(
+            "# {{description}}\n{{uuid}} {{project}} {{date}}\n{{bookmarks}}\n",
+            &task(),
+            Utc.ymd(2022, 1, 2).and_hms(1, 0, 0),
+        );
+
+        assert_eq!(
+            note,
+            "# Write the release notes!\n2c5a4f2b-6a3b-4d1e-9c3f-1f2e3d4c5b6a contextswitch 2022-01-02\n- <https://example.com/>\n"
+        );
+        assert_eq!(
+            note_title(Path::new("/notes/note.md"), &note),
+            "Write the release notes!"
+        );
+        assert_eq!(note_title(Path::new("/notes/note.md"), "no title"), "note");
+    }
+
+    #[test]
+    fn test_note_uri() {
+        let path = Path::new("/home/dax/My Notes/café.md");
+        let uri = note_uri(path).unwrap();
+
+        assert_eq!(
+            uri.to_string(),
+            "file://localhost/home/dax/My%20Notes/caf%C3%A9.md"
+        );
+        assert_eq!(note_path(&uri), Some(path.to_path_buf()));
+        assert_eq!(
+            note_path(&"https://localhost/note.md".parse::<Uri>().unwrap()),
+            None
+        );
+        for uri in [
+            "file://localhost/notes/../../etc/x.md",
+            "file://localhost/notes/%2E%2E/x.md",
+        ] {
+            assert_eq!(note_path(&uri.parse::<Uri>().unwrap()), None);
+        }
+    }
+
+    #[test]
+    fn test_find_task_ids() {
+        let content = "See 2c5a4f2b-6a3b-4d1e-9c3f-1f2e3d4c5b6a and 2C5A4F2B-6A3B-4D1E-9C3F-1F2E3D4C5B6A, not 2c5a4f2b";
+
+        assert_eq!(
+            find_task_ids(content),
+            vec![Uuid::parse_str("2c5a4f2b-6a3b-4d1e-9c3f-1f2e3d4c5b6a").unwrap()]
+        );
+    }
+}
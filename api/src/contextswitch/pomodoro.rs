@@ -0,0 +1,314 @@
+use super::{events, list_tasks, modify_task, taskwarrior, ContextswitchError};
+use crate::configuration::PomodoroSettings;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use contextswitch::{
+    DateCount, NewPomodoro, Pomodoro, PomodoroCount, PomodoroStats, Status, Task, TaskEvent, TaskId,
+};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+const DEFAULT_STATS_DAYS: i64 = 7;
+
+type ScheduledPomodoro = (TaskId, Pomodoro);
+
+pub struct PomodoroScheduler {
+    work_minutes: u32,
+    break_minutes: u32,
+    scheduled: mpsc::UnboundedSender<ScheduledPomodoro>,
+}
+
+pub fn running_pomodoro(task: &Task) -> Option<&Pomodoro> {
+    task.contextswitch
+        .as_ref()
+        .and_then(|contextswitch| contextswitch.pomodoros.last())
+        .filter(|pomodoro| !pomodoro.completed)
+}
+
+fn new_pomodoro(
+    scheduler: &PomodoroScheduler,
+    new_pomodoro: NewPomodoro,
+    now: DateTime<Utc>,
+) -> Result<Pomodoro, ContextswitchError> {
+    let work_minutes = new_pomodoro.work_minutes.unwrap_or(scheduler.work_minutes);
+    let break_minutes = new_pomodoro
+        .break_minutes
+        .unwrap_or(scheduler.break_minutes);
+    if work_minutes == 0 {
+        return Err(ContextswitchError::InvalidInputError(
+            "Pomodoro work length cannot be empty".to_string(),
+        ));
+    }
+
+    Ok(Pomodoro {
+        start: now,
+        work_seconds: work_minutes * 60,
+        break_seconds: break_minutes * 60,
+        completed: false,
+    })
+}
+
+// Starting a pomodoro starts the task, completing it stops the task
+#[tracing::instrument(level = "debug", skip(scheduler))]
+pub async fn start_pomodoro(
+    scheduler: &PomodoroScheduler,
+    task_id: &TaskId,
+    new_pomodoro: NewPomodoro,
+) -> Result<Task, ContextswitchError> {
+    let now = Utc::now();
+    let pomodoro = self::new_pomodoro(scheduler, new_pomodoro, now)?;
+    let task = modify_task(task_id, |task| {
+        if task.status != Status::Pending {
+            return Err(ContextswitchError::InvalidInputError(format!(
+                "Task {} is not pending",
+                task_id
+            )));
+        }
+        if running_pomodoro(&task).is_some() {
+            return Err(ContextswitchError::InvalidInputError(format!(
+                "A pomodoro is already running for task {}",
+                task_id
+            )));
+        }
+        let mut contextswitch = task.contextswitch.clone().unwrap_or_default();
+        contextswitch.pomodoros.push(pomodoro.clone());
+
+        Ok(Some(Task {
+            start: task.start.or(Some(now)),
+            contextswitch: Some(contextswitch),
+            ..task
+        }))
+    })
+    .await?;
+    if scheduler
+        .scheduled
+        .send((task.id.clone(), pomodoro))
+        .is_err()
+    {
+        warn!("Pomodoro scheduler is not running");
+    }
+
+    Ok(task)
+}
+
+// None when the pomodoro is not running anymore
+pub fn complete_pomodoro(task: Task, start: DateTime<Utc>) -> Option<Task> {
+    let mut contextswitch = task.contextswitch.clone()?;
+    let pomodoro = contextswitch
+        .pomodoros
+        .iter_mut()
+        .find(|pomodoro| pomodoro.start == start && !pomodoro.completed)?;
+    pomodoro.completed = true;
+
+    Some(Task {
+        start: None,
+        contextswitch: Some(contextswitch),
+        ..task
+    })
+}
+
+async fn finish_pomodoro(
+    task_id: &TaskId,
+    pomodoro: &Pomodoro,
+) -> Result<Option<Task>, ContextswitchError> {
+    let remaining = pomodoro.work_end() - Utc::now();
+    if let Ok(remaining) = remaining.to_std() {
+        tokio::time::sleep(remaining).await;
+    }
+    let mut completed = false;
+    let task = modify_task(task_id, |task| {
+        let completed_task = complete_pomodoro(task, pomodoro.start);
+        completed = completed_task.is_some();
+        Ok(completed_task)
+    })
+    .await?;
+    if !completed {
+        return Ok(None);
+    }
+    events::publish(TaskEvent::PomodoroCompleted(task.clone()));
+
+    Ok(Some(task))
+}
+
+fn schedule(task_id: TaskId, pomodoro: Pomodoro) {
+    tokio::spawn(async move {
+        if let Err(e) = finish_pomodoro(&task_id, &pomodoro).await {
+            warn!("Cannot complete pomodoro of task {}: {:?}", task_id, e);
+        }
+    });
+}
+
+// Pomodoros running before a restart are completed on time, or right away
+// when they ended in the meantime
+fn resume_pomodoros() -> Result<(), taskwarrior::TaskwarriorError> {
+    for task in taskwarrior::list_tasks(vec!["status:pending"])?
+        .iter()
+        .map(Task::from)
+    {
+        if let Some(pomodoro) = running_pomodoro(&task) {
+            schedule(task.id.clone(), pomodoro.clone());
+        }
+    }
+
+    Ok(())
+}
+
+pub fn spawn_scheduler(settings: &PomodoroSettings) -> Result<PomodoroScheduler, std::io::Error> {
+    let (scheduled, mut to_schedule) = mpsc::unbounded_channel::<ScheduledPomodoro>();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    std::thread::Builder::new()
+        .name("pomodoro-scheduler".to_string())
+        .spawn(move || {
+            runtime.block_on(async move {
+                if let Err(e) = resume_pomodoros() {
+                    warn!("Cannot resume pomodoros: {}", e);
+                }
+                while let Some((task_id, pomodoro)) = to_schedule.recv().await {
+                    schedule(task_id, pomodoro);
+                }
+            })
+        })?;
+
+    Ok(PomodoroScheduler {
+        work_minutes: settings.work_minutes,
+        break_minutes: settings.break_minutes,
+        scheduled,
+    })
+}
+
+pub fn compute_pomodoro_stats(tasks: &[Task], from: NaiveDate, to: NaiveDate) -> PomodoroStats {
+    let mut by_day: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut by_task: Vec<PomodoroCount> = vec![];
+    for task in tasks.iter().filter(|task| task.status != Status::Deleted) {
+        let completed_days: Vec<NaiveDate> = task
+            .contextswitch
+            .iter()
+            .flat_map(|contextswitch| contextswitch.pomodoros.iter())
+            .filter(|pomodoro| pomodoro.completed)
+            .map(|pomodoro| pomodoro.work_end().naive_utc().date())
+            .filter(|date| *date >= from && *date <= to)
+            .collect();
+        if completed_days.is_empty() {
+            continue;
+        }
+        for date in completed_days.iter() {
+            *by_day.entry(*date).or_default() += 1;
+        }
+        by_task.push(PomodoroCount {
+            task_id: task.id.clone(),
+            description: task.description.clone(),
+            count: completed_days.len(),
+        });
+    }
+    by_task.sort_by_key(|count| Reverse(count.count));
+    let mut day_counts = vec![];
+    let mut date = from;
+    while date <= to {
+        day_counts.push(DateCount {
+            date,
+            count: by_day.get(&date).cloned().unwrap_or(0),
+        });
+        date += Duration::days(1);
+    }
+
+    PomodoroStats {
+        from,
+        to,
+        by_day: day_counts,
+        by_task,
+    }
+}
+
+#[tracing::instrument(level = "debug")]
+pub fn get_pomodoro_stats(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<PomodoroStats, ContextswitchError> {
+    let to = to.unwrap_or_else(|| Utc::now().naive_utc().date());
+    let from = from.unwrap_or_else(|| to - Duration::days(DEFAULT_STATS_DAYS - 1));
+    if from > to {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Pomodoro stats start {} is after their end {}",
+            from, to
+        )));
+    }
+    let tasks = list_tasks(vec![])?;
+
+    Ok(compute_pomodoro_stats(&tasks, from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+    use chrono::TimeZone;
+    use contextswitch::ContextswitchData;
+
+    fn pomodoro(day: u32, hour: u32, completed: bool) -> Pomodoro {
+        Pomodoro {
+            start: Utc.ymd(2022, 1, day).and_hms(hour, 0, 0),
+            work_seconds: 25 * 60,
+            break_seconds: 5 * 60,
+            completed,
+        }
+    }
+
+    fn task(pomodoros: Vec<Pomodoro>) -> Task {
+        Task {
+            start: Some(Utc.ymd(2022, 1, 4).and_hms(9, 0, 0)),
+            contextswitch: Some(ContextswitchData {
+                pomodoros,
+                ..Default::default()
+            }),
+            ..test_helpers::task("pomodoro")
+        }
+    }
+
+    #[test]
+    fn test_complete_running_pomodoro() {
+        let task = task(vec![pomodoro(3, 9, true), pomodoro(4, 9, false)]);
+        assert_eq!(running_pomodoro(&task), Some(&pomodoro(4, 9, false)));
+
+        let completed_task = complete_pomodoro(task.clone(), pomodoro(4, 9, false).start).unwrap();
+
+        assert_eq!(completed_task.start, None);
+        assert_eq!(running_pomodoro(&completed_task), None);
+        assert!(complete_pomodoro(task, pomodoro(3, 9, true).start).is_none());
+    }
+
+    #[test]
+    fn test_compute_pomodoro_stats() {
+        let review = task(vec![
+            pomodoro(3, 9, true),
+            pomodoro(3, 10, true),
+            pomodoro(4, 9, false),
+        ]);
+        let deploy = task(vec![pomodoro(4, 14, true), pomodoro(6, 9, true)]);
+
+        let stats = compute_pomodoro_stats(
+            &[review.clone(), deploy.clone()],
+            NaiveDate::from_ymd(2022, 1, 3),
+            NaiveDate::from_ymd(2022, 1, 5),
+        );
+
+        assert_eq!(
+            stats
+                .by_day
+                .iter()
+                .map(|date_count| date_count.count)
+                .collect::<Vec<usize>>(),
+            vec![2, 1, 0]
+        );
+        assert_eq!(
+            stats
+                .by_task
+                .iter()
+                .map(|count| (count.task_id.clone(), count.count))
+                .collect::<Vec<(TaskId, usize)>>(),
+            vec![(review.id, 2), (deploy.id, 1)]
+        );
+    }
+}
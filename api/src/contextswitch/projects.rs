@@ -0,0 +1,177 @@
+use super::store::{store_path, JsonStore};
+use super::{list_context_tasks, ContextswitchError};
+use contextswitch::{ContextswitchData, Project, Status, Task};
+use std::collections::{BTreeMap, BTreeSet};
+
+const PROJECTS_FILE: &str = "projects.json";
+
+pub type ProjectStore = JsonStore<BTreeMap<String, ContextswitchData>>;
+
+pub fn new_project_store(data_location: &str) -> ProjectStore {
+    JsonStore::new(store_path(data_location, PROJECTS_FILE))
+}
+
+fn parent_project(name: &str) -> Option<&str> {
+    name.rsplit_once('.').map(|(parent, _)| parent)
+}
+
+fn build_project(
+    name: &str,
+    names: &BTreeSet<String>,
+    counts: &BTreeMap<String, (usize, usize)>,
+    metadata: &BTreeMap<String, ContextswitchData>,
+) -> Project {
+    let children: Vec<Project> = names
+        .iter()
+        .filter(|child| parent_project(child) == Some(name))
+        .map(|child| build_project(child, names, counts, metadata))
+        .collect();
+    let (own_pending, own_completed) = counts.get(name).cloned().unwrap_or((0, 0));
+    let pending = own_pending + children.iter().map(|child| child.pending).sum::<usize>();
+    let completed = own_completed + children.iter().map(|child| child.completed).sum::<usize>();
+    let progress = if pending + completed == 0 {
+        0.0
+    } else {
+        completed as f64 / (pending + completed) as f64
+    };
+
+    Project {
+        name: name.to_string(),
+        pending,
+        completed,
+        progress,
+        contextswitch: metadata.get(name).cloned(),
+        children,
+    }
+}
+
+pub fn build_project_tree(
+    tasks: &[Task],
+    metadata: &BTreeMap<String, ContextswitchData>,
+) -> Vec<Project> {
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for task in tasks {
+        if let Some(project) = &task.project {
+            let (pending, completed) = counts.entry(project.clone()).or_insert((0, 0));
+            match task.status {
+                Status::Pending => *pending += 1,
+                Status::Completed => *completed += 1,
+                _ => (),
+            }
+        }
+    }
+
+    let mut names: BTreeSet<String> = BTreeSet::new();
+    for project in counts.keys().chain(metadata.keys()) {
+        let mut name = Some(project.as_str());
+        while let Some(current) = name {
+            names.insert(current.to_string());
+            name = parent_project(current);
+        }
+    }
+
+    names
+        .iter()
+        .filter(|name| parent_project(name).is_none())
+        .map(|name| build_project(name, &names, &counts, metadata))
+        .collect()
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub fn list_projects(store: &ProjectStore) -> Result<Vec<Project>, ContextswitchError> {
+    let tasks = list_context_tasks(vec!["project.any:"])?;
+    let metadata = store.load()?;
+
+    Ok(build_project_tree(&tasks, &metadata))
+}
+
+#[tracing::instrument(level = "debug", skip(store, contextswitch_data))]
+pub async fn update_project_contextswitch(
+    store: &ProjectStore,
+    name: &str,
+    contextswitch_data: ContextswitchData,
+) -> Result<ContextswitchData, ContextswitchError> {
+    if name.is_empty() || name.split('.').any(|part| part.is_empty()) {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Invalid project name '{}'",
+            name
+        )));
+    }
+
+    store
+        .update(|metadata| {
+            metadata.insert(name.to_string(), contextswitch_data.clone());
+            Ok(contextswitch_data)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+    use contextswitch::Bookmark;
+    use http::Uri;
+
+    fn task(project: &str, status: Status) -> Task {
+        Task {
+            status,
+            project: Some(project.to_string()),
+            ..test_helpers::task("task")
+        }
+    }
+
+    #[test]
+    fn test_build_project_tree() {
+        let tasks = vec![
+            task("Home", Status::Pending),
+            task("Home.Garden", Status::Completed),
+            task("Home.Garden", Status::Pending),
+            task("Home.Kitchen.Oven", Status::Completed),
+            task("Work", Status::Deleted),
+        ];
+        let projects = build_project_tree(&tasks, &BTreeMap::new());
+
+        assert_eq!(projects.len(), 2);
+        let home = &projects[0];
+        assert_eq!(home.name, "Home");
+        assert_eq!((home.pending, home.completed), (2, 2));
+        assert_eq!(home.progress, 0.5);
+        assert_eq!(
+            home.children
+                .iter()
+                .map(|child| child.name.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["Home.Garden", "Home.Kitchen"]
+        );
+        let kitchen = &home.children[1];
+        assert_eq!((kitchen.pending, kitchen.completed), (0, 1));
+        assert_eq!(kitchen.children[0].name, "Home.Kitchen.Oven");
+        let work = &projects[1];
+        assert_eq!((work.pending, work.completed), (0, 0));
+        assert_eq!(work.progress, 0.0);
+    }
+
+    #[test]
+    fn test_build_project_tree_with_metadata() {
+        let contextswitch_data = ContextswitchData {
+            bookmarks: vec![Bookmark {
+                uri: "https://www.example.com/path".parse::<Uri>().unwrap(),
+                content: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let metadata = BTreeMap::from([("Work.Meetings".to_string(), contextswitch_data.clone())]);
+
+        let projects = build_project_tree(&[], &metadata);
+
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "Work");
+        assert_eq!(projects[0].contextswitch, None);
+        assert_eq!(
+            projects[0].children[0].contextswitch,
+            Some(contextswitch_data)
+        );
+    }
+}
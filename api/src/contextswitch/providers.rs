@@ -0,0 +1,220 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use contextswitch::{
+    Bookmark, BookmarkBadge, BookmarkContent, BookmarkEvent, BookmarkKind, ProviderInfo,
+};
+use http::uri::Uri;
+use std::sync::Arc;
+use tracing::warn;
+
+#[async_trait]
+pub trait BookmarkProvider: Send + Sync {
+    fn info(&self) -> ProviderInfo;
+
+    fn handles(&self, uri: &Uri) -> bool;
+
+    fn kind(&self, uri: &Uri) -> String;
+
+    fn bookmark_kind(&self) -> BookmarkKind {
+        BookmarkKind::Link
+    }
+
+    // The previous content of the bookmark is given to keep track of changes
+    async fn enrich(
+        &self,
+        bookmark: &Bookmark,
+        now: DateTime<Utc>,
+    ) -> Result<BookmarkContent, anyhow::Error>;
+
+    fn bookmark_event(&self, _previous: &Bookmark, _enriched: &Bookmark) -> Option<BookmarkEvent> {
+        None
+    }
+}
+
+pub fn badge(provider: &dyn BookmarkProvider, uri: &Uri) -> BookmarkBadge {
+    let info = provider.info();
+
+    BookmarkBadge {
+        provider: info.name,
+        icon: info.icon,
+        kind: provider.kind(uri),
+    }
+}
+
+pub struct ProviderRegistry {
+    providers: Vec<Arc<dyn BookmarkProvider>>,
+}
+
+// Only the providers enabled in the configuration are registered, in that order
+pub fn new_provider_registry(
+    enabled: &[String],
+    available: Vec<Arc<dyn BookmarkProvider>>,
+) -> ProviderRegistry {
+    let mut providers = vec![];
+    for name in enabled {
+        match available
+            .iter()
+            .find(|provider| &provider.info().name == name)
+        {
+            Some(provider) => providers.push(provider.clone()),
+            None => warn!("Unknown bookmark provider {}", name),
+        }
+    }
+
+    ProviderRegistry { providers }
+}
+
+impl ProviderRegistry {
+    pub fn find(&self, uri: &Uri) -> Option<&dyn BookmarkProvider> {
+        self.providers
+            .iter()
+            .find(|provider| provider.handles(uri))
+            .map(|provider| provider.as_ref())
+    }
+
+    pub fn bookmark_kind(&self, uri: &Uri) -> BookmarkKind {
+        self.find(uri)
+            .map(|provider| provider.bookmark_kind())
+            .unwrap_or_else(|| BookmarkKind::from_uri(uri))
+    }
+
+    pub fn list_providers(&self) -> Vec<ProviderInfo> {
+        self.providers
+            .iter()
+            .map(|provider| provider.info())
+            .collect()
+    }
+
+    // None when no provider handles the bookmark
+    pub async fn enrich(
+        &self,
+        bookmark: &Bookmark,
+        now: DateTime<Utc>,
+    ) -> Option<Result<(Bookmark, Option<BookmarkEvent>), anyhow::Error>> {
+        let provider = self.find(&bookmark.uri)?;
+        let content = match provider.enrich(bookmark, now).await {
+            Ok(content) => content,
+            Err(e) => return Some(Err(e)),
+        };
+        let enriched = Bookmark {
+            content: Some(BookmarkContent {
+                badge: Some(badge(provider, &bookmark.uri)),
+                ..content
+            }),
+            // Bookmarks saved before kinds existed get one when refreshed
+            kind: bookmark.kind.or_else(|| Some(provider.bookmark_kind())),
+            ..bookmark.clone()
+        };
+        let event = provider.bookmark_event(bookmark, &enriched);
+
+        Some(Ok((enriched, event)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestProvider {
+        name: String,
+    }
+
+    #[async_trait]
+    impl BookmarkProvider for TestProvider {
+        fn info(&self) -> ProviderInfo {
+            ProviderInfo {
+                name: self.name.clone(),
+                icon: "link".to_string(),
+                uri_patterns: vec![format!("https://{}.com/{{path}}", self.name)],
+                kinds: vec!["page".to_string()],
+                events: vec![],
+            }
+        }
+
+        fn handles(&self, uri: &Uri) -> bool {
+            uri.host() == Some(&format!("{}.com", self.name))
+        }
+
+        fn kind(&self, _uri: &Uri) -> String {
+            "page".to_string()
+        }
+
+        async fn enrich(
+            &self,
+            bookmark: &Bookmark,
+            _now: DateTime<Utc>,
+        ) -> Result<BookmarkContent, anyhow::Error> {
+            Ok(BookmarkContent {
+                title: bookmark.uri.path().to_string(),
+                content_preview: None,
+                details: None,
+                badge: None,
+            })
+        }
+    }
+
+    fn registry(enabled: &[&str]) -> ProviderRegistry {
+        new_provider_registry(
+            &enabled
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                Arc::new(TestProvider {
+                    name: "first".to_string(),
+                }),
+                Arc::new(TestProvider {
+                    name: "second".to_string(),
+                }),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_registry_only_includes_enabled_providers() {
+        let registry = registry(&["second", "unknown"]);
+
+        assert_eq!(
+            registry
+                .list_providers()
+                .iter()
+                .map(|provider| provider.name.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["second"]
+        );
+        assert!(registry
+            .find(&"https://first.com/".parse::<Uri>().unwrap())
+            .is_none());
+        assert!(registry
+            .find(&"https://second.com/".parse::<Uri>().unwrap())
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_enrich_adds_provider_badge() {
+        let bookmark = Bookmark {
+            uri: "https://first.com/page".parse::<Uri>().unwrap(),
+            content: None,
+            ..Default::default()
+        };
+
+        let (enriched, event) = registry(&["first"])
+            .enrich(&bookmark, Utc::now())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let content = enriched.content.unwrap();
+        assert_eq!(content.title, "/page");
+        assert_eq!(
+            content.badge,
+            Some(BookmarkBadge {
+                provider: "first".to_string(),
+                icon: "link".to_string(),
+                kind: "page".to_string(),
+            })
+        );
+        assert_eq!(event, None);
+        assert!(registry(&[]).enrich(&bookmark, Utc::now()).await.is_none());
+    }
+}
@@ -0,0 +1,299 @@
+use super::{list_tasks, modify_task, run_task_commands, time, ContextswitchError};
+use chrono::{DateTime, Utc};
+use contextswitch::canonical::UrlCanonicalizer;
+use contextswitch::{BookmarkEvent, HistoryEntry, Rule, RuleAction, RuleMatch, Status, Task};
+
+#[derive(Debug, Default)]
+pub struct RuleEngine {
+    rules: Vec<Rule>,
+}
+
+pub fn new_rule_engine(rules: &[Rule]) -> RuleEngine {
+    RuleEngine {
+        rules: rules.to_vec(),
+    }
+}
+
+pub fn list_rules(engine: &RuleEngine) -> Vec<Rule> {
+    engine.rules.clone()
+}
+
+fn is_matching(rule: &Rule, event: &BookmarkEvent) -> bool {
+    rule.provider == event.provider
+        && rule.kind.iter().all(|kind| kind == &event.kind)
+        && rule.state == event.state
+}
+
+pub fn match_rules(engine: &RuleEngine, task: &Task, events: &[BookmarkEvent]) -> Vec<RuleMatch> {
+    events
+        .iter()
+        .flat_map(|event| {
+            engine
+                .rules
+                .iter()
+                .filter(move |rule| is_matching(rule, event))
+                .map(move |rule| RuleMatch {
+                    task_id: task.id.clone(),
+                    description: task.description.clone(),
+                    rule: rule.name.clone(),
+                    event: event.clone(),
+                    actions: rule.actions.clone(),
+                })
+        })
+        .collect()
+}
+
+pub fn history_entries(rule_matches: &[RuleMatch], now: DateTime<Utc>) -> Vec<HistoryEntry> {
+    rule_matches
+        .iter()
+        .map(|rule_match| HistoryEntry {
+            date: now,
+            rule: rule_match.rule.clone(),
+            event: rule_match.event.clone(),
+            actions: rule_match.actions.clone(),
+        })
+        .collect()
+}
+
+// Taskwarrior arguments of an action, None when the task is already in the expected state
+fn action_args(action: &RuleAction, task: &Task) -> Option<Vec<String>> {
+    match action {
+        RuleAction::Done if task.status == Status::Pending => Some(vec!["done".to_string()]),
+        RuleAction::Done => None,
+        RuleAction::Start if task.start.is_none() && task.status == Status::Pending => {
+            Some(vec!["start".to_string()])
+        }
+        RuleAction::Start => None,
+        RuleAction::Annotate { text } => Some(vec!["annotate".to_string(), text.clone()]),
+        RuleAction::Tag { tag } => Some(vec!["mod".to_string(), format!("+{}", tag)]),
+        RuleAction::Wait { until } => Some(vec!["mod".to_string(), format!("wait:{}", until)]),
+    }
+}
+
+// Task state once an action has been run, so that a following action
+// expecting the same state is not run twice
+fn apply_action(action: &RuleAction, task: Task, now: DateTime<Utc>) -> Task {
+    match action {
+        RuleAction::Done => Task {
+            status: Status::Completed,
+            end: task.end.or(Some(now)),
+            ..task
+        },
+        RuleAction::Start => Task {
+            start: task.start.or(Some(now)),
+            ..task
+        },
+        _ => task,
+    }
+}
+
+fn rule_commands(task: &Task, rule_matches: &[RuleMatch], now: DateTime<Utc>) -> Vec<Vec<String>> {
+    let mut expected_task = task.clone();
+    let mut commands: Vec<Vec<String>> = vec![];
+    for action in rule_matches
+        .iter()
+        .flat_map(|rule_match| rule_match.actions.iter())
+    {
+        if let Some(args) = action_args(action, &expected_task) {
+            if !commands.contains(&args) {
+                commands.push(args);
+            }
+        }
+        expected_task = apply_action(action, expected_task, now);
+    }
+
+    commands
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(task_id = %task.id))]
+pub async fn apply_rule_matches(
+    task: Task,
+    rule_matches: &[RuleMatch],
+) -> Result<Task, ContextswitchError> {
+    let now = Utc::now();
+    // Taskwarrior commands bypass update_task, the work session of a started
+    // task is recorded before the task gets done
+    modify_task(&task.id, |task| {
+        let commands = rule_commands(&task, rule_matches, now);
+        if !commands.contains(&vec!["done".to_string()]) || task.invalid_contextswitch.is_some() {
+            return Ok(None);
+        }
+        let closed_task = time::close_session(
+            &task,
+            apply_action(&RuleAction::Done, task.clone(), now),
+            now,
+        );
+
+        Ok(
+            (closed_task.contextswitch != task.contextswitch).then(|| Task {
+                contextswitch: closed_task.contextswitch,
+                ..task
+            }),
+        )
+    })
+    .await?;
+
+    run_task_commands(&task.id, |task| rule_commands(task, rule_matches, now)).await
+}
+
+#[tracing::instrument(level = "debug", skip(engine, canonicalizer))]
+pub fn preview_rules(
+    engine: &RuleEngine,
+    canonicalizer: &UrlCanonicalizer,
+    event: BookmarkEvent,
+) -> Result<Vec<RuleMatch>, ContextswitchError> {
+    let events = [event];
+    let tasks = list_tasks(vec!["status:pending"])?;
+
+    Ok(tasks
+        .iter()
+        .filter(|task| {
+            task.contextswitch.iter().any(|contextswitch| {
+                contextswitch
+                    .bookmarks
+                    .iter()
+                    .any(|bookmark| canonicalizer.same_url(&bookmark.uri, &events[0].uri))
+            })
+        })
+        .flat_map(|task| match_rules(engine, task, &events))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+    use chrono::TimeZone;
+    use http::uri::Uri;
+
+    fn task() -> Task {
+        test_helpers::task("rules")
+    }
+
+    fn event(kind: &str, state: &str) -> BookmarkEvent {
+        BookmarkEvent {
+            uri: "https://github.com/dax/contextswitch/pull/1"
+                .parse::<Uri>()
+                .unwrap(),
+            provider: "github".to_string(),
+            kind: kind.to_string(),
+            state: state.to_string(),
+        }
+    }
+
+    fn engine() -> RuleEngine {
+        new_rule_engine(&[
+            Rule {
+                name: "merged".to_string(),
+                provider: "github".to_string(),
+                kind: Some("pull_request".to_string()),
+                state: "merged".to_string(),
+                actions: vec![RuleAction::Done],
+            },
+            Rule {
+                name: "closed".to_string(),
+                provider: "github".to_string(),
+                kind: None,
+                state: "closed".to_string(),
+                actions: vec![RuleAction::Tag {
+                    tag: "closed".to_string(),
+                }],
+            },
+        ])
+    }
+
+    #[test]
+    fn test_match_rules() {
+        let task = task();
+
+        let rule_matches = match_rules(
+            &engine(),
+            &task,
+            &[
+                event("pull_request", "merged"),
+                event("issue", "merged"),
+                event("issue", "closed"),
+            ],
+        );
+
+        assert_eq!(
+            rule_matches
+                .iter()
+                .map(|rule_match| (rule_match.rule.as_str(), rule_match.event.kind.as_str()))
+                .collect::<Vec<(&str, &str)>>(),
+            vec![("merged", "pull_request"), ("closed", "issue")]
+        );
+    }
+
+    #[test]
+    fn test_history_entries() {
+        let task = task();
+        let now = Utc.ymd(2022, 1, 2).and_hms(1, 0, 0);
+        let rule_matches = match_rules(&engine(), &task, &[event("pull_request", "merged")]);
+
+        assert_eq!(
+            history_entries(&rule_matches, now),
+            vec![HistoryEntry {
+                date: now,
+                rule: "merged".to_string(),
+                event: event("pull_request", "merged"),
+                actions: vec![RuleAction::Done],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rule_commands_follow_task_state() {
+        let now = Utc.ymd(2022, 1, 2).and_hms(1, 0, 0);
+        let rule_match = |actions: Vec<RuleAction>| RuleMatch {
+            task_id: task().id,
+            description: "rules".to_string(),
+            rule: "rule".to_string(),
+            event: event("pull_request", "merged"),
+            actions,
+        };
+        let tag = RuleAction::Tag {
+            tag: "merged".to_string(),
+        };
+
+        assert_eq!(
+            rule_commands(
+                &task(),
+                &[
+                    rule_match(vec![RuleAction::Done, tag.clone()]),
+                    rule_match(vec![RuleAction::Done, tag, RuleAction::Start]),
+                ],
+                now
+            ),
+            vec![
+                vec!["done".to_string()],
+                vec!["mod".to_string(), "+merged".to_string()]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_action_args() {
+        let task = task();
+        let completed_task = Task {
+            status: Status::Completed,
+            ..task.clone()
+        };
+
+        assert_eq!(
+            action_args(&RuleAction::Done, &task),
+            Some(vec!["done".to_string()])
+        );
+        assert_eq!(action_args(&RuleAction::Done, &completed_task), None);
+        assert_eq!(action_args(&RuleAction::Start, &completed_task), None);
+        assert_eq!(
+            action_args(
+                &RuleAction::Wait {
+                    until: "tomorrow".to_string()
+                },
+                &task
+            ),
+            Some(vec!["mod".to_string(), "wait:tomorrow".to_string()])
+        );
+    }
+}
@@ -0,0 +1,686 @@
+use super::bookmarks::content_preview;
+use super::providers::{BookmarkProvider, ProviderRegistry};
+use super::{add_task_with_description, update_contextswitch, ContextswitchError};
+use crate::configuration::SlackSettings;
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use contextswitch::{
+    Bookmark, BookmarkContent, BookmarkDetails, BookmarkEvent, BookmarkKind, ProviderInfo,
+    SlackDetails, Task,
+};
+use hmac::{Hmac, Mac};
+use http::uri::Uri;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tracing::warn;
+
+const SIGNATURE_VERSION: &str = "v0";
+// Older requests are rejected to prevent replay attacks
+const MAX_REQUEST_AGE_SECONDS: i64 = 5 * 60;
+const MAX_DESCRIPTION_LENGTH: usize = 80;
+// Reactions marking a thread as resolved
+const RESOLVED_REACTIONS: [&str; 3] = [
+    "white_check_mark",
+    "heavy_check_mark",
+    "ballot_box_with_check",
+];
+
+pub const TIMESTAMP_HEADER: &str = "X-Slack-Request-Timestamp";
+pub const SIGNATURE_HEADER: &str = "X-Slack-Signature";
+
+#[derive(Debug)]
+pub struct SlackClient {
+    client: reqwest::Client,
+    api_base_url: String,
+    token: Option<String>,
+    signing_secret: Option<String>,
+}
+
+pub fn new_slack_client(settings: &SlackSettings) -> SlackClient {
+    SlackClient {
+        client: reqwest::Client::new(),
+        api_base_url: settings.api_base_url.trim_end_matches('/').to_string(),
+        token: settings.token.clone(),
+        signing_secret: settings.signing_secret.clone(),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SlackReference {
+    pub workspace: String,
+    pub channel_id: String,
+    pub ts: String,
+    pub thread_ts: Option<String>,
+}
+
+// Permalinks look like https://<workspace>.slack.com/archives/<channel>/p<ts without dot>
+pub fn parse_slack_uri(uri: &Uri) -> Option<SlackReference> {
+    let workspace = uri.host()?.strip_suffix(".slack.com")?;
+    if workspace.is_empty() || workspace.contains('.') {
+        return None;
+    }
+    let segments: Vec<&str> = uri.path().split('/').filter(|s| !s.is_empty()).collect();
+    let (channel_id, message_id) = match segments[..] {
+        ["archives", channel_id, message_id, ..] => (channel_id, message_id),
+        _ => return None,
+    };
+    let digits = message_id.strip_prefix('p')?;
+    if digits.len() <= 6 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (seconds, microseconds) = digits.split_at(digits.len() - 6);
+    let thread_ts = uri
+        .query()
+        .and_then(|query| serde_urlencoded::from_str::<Vec<(String, String)>>(query).ok())
+        .and_then(|params| {
+            params
+                .into_iter()
+                .find(|(name, _)| name == "thread_ts")
+                .map(|(_, value)| value)
+        });
+
+    Some(SlackReference {
+        workspace: workspace.to_string(),
+        channel_id: channel_id.to_string(),
+        ts: format!("{}.{}", seconds, microseconds),
+        thread_ts,
+    })
+}
+
+pub fn permalink(reference: &SlackReference) -> String {
+    let permalink = format!(
+        "https://{}.slack.com/archives/{}/p{}",
+        reference.workspace,
+        reference.channel_id,
+        reference.ts.replace('.', "")
+    );
+    match &reference.thread_ts {
+        Some(thread_ts) => format!(
+            "{}?thread_ts={}&cid={}",
+            permalink, thread_ts, reference.channel_id
+        ),
+        None => permalink,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SlackChannel {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConversationInfo {
+    channel: SlackChannel,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlackReaction {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlackMessage {
+    ts: String,
+    #[serde(default)]
+    text: String,
+    user: Option<String>,
+    username: Option<String>,
+    #[serde(default)]
+    reply_count: u64,
+    latest_reply: Option<String>,
+    #[serde(default)]
+    reactions: Vec<SlackReaction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConversationReplies {
+    messages: Vec<SlackMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SlackUser {
+    name: String,
+    real_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    user: SlackUser,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SlackItem {
+    pub channel: String,
+    pub author: String,
+    pub text: String,
+    pub reply_count: u64,
+    pub latest_reply: Option<String>,
+    pub resolved: bool,
+}
+
+impl SlackClient {
+    // Slack reports API errors with a 200 status and an ok field
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, anyhow::Error> {
+        let url = format!("{}/{}", self.api_base_url, method);
+        let request = self.client.get(&url).query(query);
+        let request = match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        let response: serde_json::Value = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Cannot fetch {}", url))?
+            .json()
+            .await
+            .with_context(|| format!("Cannot parse {}", url))?;
+        if response["ok"] != serde_json::Value::Bool(true) {
+            bail!("Slack method {} failed: {}", method, response["error"]);
+        }
+
+        serde_json::from_value(response).with_context(|| format!("Cannot parse {}", url))
+    }
+
+    async fn fetch_author(&self, message: &SlackMessage) -> Result<String, anyhow::Error> {
+        match (&message.user, &message.username) {
+            (Some(user_id), _) => {
+                let user_info: UserInfo = self.call("users.info", &[("user", user_id)]).await?;
+                Ok(user_info.user.real_name.unwrap_or(user_info.user.name))
+            }
+            (None, Some(username)) => Ok(username.clone()),
+            (None, None) => Ok("unknown".to_string()),
+        }
+    }
+
+    pub async fn fetch(&self, reference: &SlackReference) -> Result<SlackItem, anyhow::Error> {
+        let channel_info: ConversationInfo = self
+            .call("conversations.info", &[("channel", &reference.channel_id)])
+            .await?;
+        let thread_ts = reference.thread_ts.as_ref().unwrap_or(&reference.ts);
+        // The thread parent comes first and holds the reply count
+        let replies: ConversationReplies = self
+            .call(
+                "conversations.replies",
+                &[("channel", &reference.channel_id), ("ts", thread_ts)],
+            )
+            .await?;
+        let parent = replies
+            .messages
+            .first()
+            .with_context(|| format!("Slack message {} not found", reference.ts))?;
+        let message = replies
+            .messages
+            .iter()
+            .find(|message| message.ts == reference.ts)
+            .unwrap_or(parent);
+
+        Ok(SlackItem {
+            channel: channel_info.channel.name,
+            author: self.fetch_author(message).await?,
+            text: message.text.clone(),
+            reply_count: parent.reply_count,
+            latest_reply: parent.latest_reply.clone(),
+            resolved: parent
+                .reactions
+                .iter()
+                .any(|reaction| RESOLVED_REACTIONS.contains(&reaction.name.as_str())),
+        })
+    }
+
+    pub fn verify_signature(
+        &self,
+        timestamp: Option<&str>,
+        signature: Option<&str>,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> Result<(), ContextswitchError> {
+        let signing_secret = self.signing_secret.as_ref().ok_or_else(|| {
+            ContextswitchError::UnauthorizedError(
+                "Slack signing secret is not configured".to_string(),
+            )
+        })?;
+        let invalid_request =
+            || ContextswitchError::UnauthorizedError("Invalid Slack request signature".to_string());
+        let timestamp = timestamp.ok_or_else(invalid_request)?;
+        let request_time: i64 = timestamp.parse().map_err(|_| invalid_request())?;
+        if (now.timestamp() - request_time).abs() > MAX_REQUEST_AGE_SECONDS {
+            return Err(invalid_request());
+        }
+        let signature = signature
+            .and_then(|signature| signature.strip_prefix("v0="))
+            .and_then(|signature| hex::decode(signature).ok())
+            .ok_or_else(invalid_request)?;
+
+        signature_mac(signing_secret, timestamp, body)
+            .verify_slice(&signature)
+            .map_err(|_| invalid_request())
+    }
+}
+
+fn signature_mac(secret: &str, timestamp: &str, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(format!("{}:{}:", SIGNATURE_VERSION, timestamp).as_bytes());
+    mac.update(body);
+    mac
+}
+
+pub fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    format!(
+        "{}={}",
+        SIGNATURE_VERSION,
+        hex::encode(
+            signature_mac(secret, timestamp, body)
+                .finalize()
+                .into_bytes()
+        )
+    )
+}
+
+fn slack_details(bookmark: &Bookmark) -> Option<&SlackDetails> {
+    match bookmark
+        .content
+        .as_ref()
+        .and_then(|content| content.details.as_ref())
+    {
+        Some(BookmarkDetails::Slack(details)) => Some(details),
+        _ => None,
+    }
+}
+
+pub fn bookmark_content(
+    bookmark: &Bookmark,
+    reference: &SlackReference,
+    item: SlackItem,
+) -> BookmarkContent {
+    // Replies existing when the bookmark is first resolved are not new
+    let seen_reply_count = slack_details(bookmark)
+        .map(|details| details.seen_reply_count)
+        .unwrap_or(item.reply_count);
+
+    BookmarkContent {
+        title: format!("{} in #{}", item.author, item.channel),
+        content_preview: content_preview(Some(&item.text)),
+        details: Some(BookmarkDetails::Slack(SlackDetails {
+            workspace: reference.workspace.clone(),
+            channel_id: reference.channel_id.clone(),
+            channel: item.channel,
+            author: item.author,
+            ts: reference.ts.clone(),
+            thread_ts: reference.thread_ts.clone(),
+            reply_count: item.reply_count,
+            seen_reply_count,
+            latest_reply: item.latest_reply,
+            resolved: item.resolved,
+        })),
+        badge: None,
+    }
+}
+
+pub fn bookmark_event(previous: &Bookmark, enriched: &Bookmark) -> Option<BookmarkEvent> {
+    let previously_resolved = slack_details(previous)?.resolved;
+    let details = slack_details(enriched)?;
+    if previously_resolved || !details.resolved {
+        return None;
+    }
+
+    Some(BookmarkEvent {
+        uri: enriched.uri.clone(),
+        provider: "slack".to_string(),
+        kind: match details.thread_ts {
+            Some(_) => "reply".to_string(),
+            None => "message".to_string(),
+        },
+        state: "resolved".to_string(),
+    })
+}
+
+#[async_trait]
+impl BookmarkProvider for SlackClient {
+    fn info(&self) -> ProviderInfo {
+        ProviderInfo {
+            name: "slack".to_string(),
+            icon: "comments".to_string(),
+            uri_patterns: vec![
+                "https://{workspace}.slack.com/archives/{channel}/p{timestamp}".to_string(),
+            ],
+            kinds: vec!["message".to_string(), "reply".to_string()],
+            events: vec!["resolved".to_string()],
+        }
+    }
+
+    fn handles(&self, uri: &Uri) -> bool {
+        parse_slack_uri(uri).is_some()
+    }
+
+    fn kind(&self, uri: &Uri) -> String {
+        match parse_slack_uri(uri).and_then(|reference| reference.thread_ts) {
+            Some(_) => "reply".to_string(),
+            None => "message".to_string(),
+        }
+    }
+
+    fn bookmark_kind(&self) -> BookmarkKind {
+        BookmarkKind::Chat
+    }
+
+    async fn enrich(
+        &self,
+        bookmark: &Bookmark,
+        _now: DateTime<Utc>,
+    ) -> Result<BookmarkContent, anyhow::Error> {
+        let reference = parse_slack_uri(&bookmark.uri)
+            .with_context(|| format!("{} is not a Slack permalink", bookmark.uri))?;
+        let item = self.fetch(&reference).await?;
+
+        Ok(bookmark_content(bookmark, &reference, item))
+    }
+
+    fn bookmark_event(&self, previous: &Bookmark, enriched: &Bookmark) -> Option<BookmarkEvent> {
+        bookmark_event(previous, enriched)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlashCommand {
+    pub command: String,
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlackTeam {
+    pub domain: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlackChannelReference {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlackInteractionMessage {
+    pub ts: String,
+    #[serde(default)]
+    pub text: String,
+    pub thread_ts: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlackInteraction {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub team: SlackTeam,
+    pub channel: SlackChannelReference,
+    pub message: Option<SlackInteractionMessage>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct SlackReply {
+    pub response_type: String,
+    pub text: String,
+}
+
+fn ephemeral_reply(text: String) -> SlackReply {
+    SlackReply {
+        response_type: "ephemeral".to_string(),
+        text,
+    }
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn run_command(command: SlashCommand) -> Result<SlackReply, ContextswitchError> {
+    if command.text.trim().is_empty() {
+        return Ok(ephemeral_reply(format!(
+            "Usage: {} <task description>",
+            command.command
+        )));
+    }
+    let task = add_task_with_description(vec![], &command.text).await?;
+
+    Ok(ephemeral_reply(format!(
+        "Task created: {}",
+        task.description
+    )))
+}
+
+fn message_description(text: &str) -> String {
+    let first_line = text.lines().map(str::trim).find(|line| !line.is_empty());
+    match first_line {
+        Some(line) => line.chars().take(MAX_DESCRIPTION_LENGTH).collect(),
+        None => "Slack message".to_string(),
+    }
+}
+
+#[tracing::instrument(level = "debug", skip(registry))]
+pub async fn handle_interaction(
+    registry: &ProviderRegistry,
+    interaction: SlackInteraction,
+) -> Result<Task, ContextswitchError> {
+    let message = match (interaction.kind.as_str(), interaction.message) {
+        ("message_action", Some(message)) => message,
+        (kind, _) => {
+            return Err(ContextswitchError::InvalidInputError(format!(
+                "Unsupported Slack interaction {}",
+                kind
+            )))
+        }
+    };
+    let reference = SlackReference {
+        workspace: interaction.team.domain,
+        channel_id: interaction.channel.id,
+        thread_ts: message
+            .thread_ts
+            .clone()
+            .filter(|thread_ts| thread_ts != &message.ts),
+        ts: message.ts,
+    };
+    let bookmark = Bookmark {
+        uri: permalink(&reference)
+            .parse::<Uri>()
+            .context("Cannot build Slack permalink")?,
+        content: None,
+        kind: Some(BookmarkKind::Chat),
+        added_at: Some(Utc::now()),
+        ..Default::default()
+    };
+    let bookmark = match registry.enrich(&bookmark, Utc::now()).await {
+        Some(Ok((enriched, _))) => enriched,
+        Some(Err(e)) => {
+            warn!("Cannot resolve Slack message {}: {:?}", bookmark.uri, e);
+            bookmark
+        }
+        None => bookmark,
+    };
+
+    let task = add_task_with_description(vec![], &message_description(&message.text)).await?;
+    update_contextswitch(&task.id, |_, contextswitch| {
+        contextswitch.bookmarks.push(bookmark);
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn client() -> SlackClient {
+        SlackClient {
+            client: reqwest::Client::new(),
+            api_base_url: "http://localhost".to_string(),
+            token: None,
+            signing_secret: Some("secret".to_string()),
+        }
+    }
+
+    fn reference(thread_ts: Option<&str>) -> SlackReference {
+        SlackReference {
+            workspace: "dax".to_string(),
+            channel_id: "C123".to_string(),
+            ts: "1650000000.000100".to_string(),
+            thread_ts: thread_ts.map(String::from),
+        }
+    }
+
+    fn item(reply_count: u64) -> SlackItem {
+        SlackItem {
+            channel: "general".to_string(),
+            author: "Dax".to_string(),
+            text: "Can someone look at this?".to_string(),
+            reply_count,
+            latest_reply: None,
+            resolved: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_slack_uri() {
+        let parse = |uri: &str| parse_slack_uri(&uri.parse::<Uri>().unwrap());
+
+        assert_eq!(
+            parse("https://dax.slack.com/archives/C123/p1650000000000100"),
+            Some(reference(None))
+        );
+        assert_eq!(
+            parse("https://dax.slack.com/archives/C123/p1650000000000100?thread_ts=1649999999.000200&cid=C123"),
+            Some(reference(Some("1649999999.000200")))
+        );
+        assert_eq!(parse("https://dax.slack.com/archives/C123"), None);
+        assert_eq!(parse("https://dax.slack.com/archives/C123/p12"), None);
+        assert_eq!(
+            parse("https://slack.com/archives/C123/p1650000000000100"),
+            None
+        );
+        assert_eq!(
+            parse("https://github.com/archives/C123/p1650000000000100"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_permalink() {
+        for reference in [reference(None), reference(Some("1649999999.000200"))] {
+            assert_eq!(
+                parse_slack_uri(&permalink(&reference).parse::<Uri>().unwrap()),
+                Some(reference)
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let now = Utc.timestamp(1650000000, 0);
+        let body = b"command=%2Ftask&text=Buy+milk";
+        let signature = sign("secret", "1650000000", body);
+
+        assert!(client()
+            .verify_signature(Some("1650000000"), Some(&signature), body, now)
+            .is_ok());
+        assert!(client()
+            .verify_signature(Some("1650000000"), Some(&signature), b"text=Sell+milk", now)
+            .is_err());
+        assert!(client()
+            .verify_signature(
+                Some("1650000000"),
+                Some(&signature),
+                body,
+                now + chrono::Duration::minutes(10)
+            )
+            .is_err());
+        assert!(client()
+            .verify_signature(None, Some(&signature), body, now)
+            .is_err());
+        assert!(SlackClient {
+            signing_secret: None,
+            ..client()
+        }
+        .verify_signature(Some("1650000000"), Some(&signature), body, now)
+        .is_err());
+    }
+
+    #[test]
+    fn test_enrich_bookmark_tracks_new_replies() {
+        let bookmark = Bookmark {
+            uri: permalink(&reference(None)).parse::<Uri>().unwrap(),
+            content: None,
+            ..Default::default()
+        };
+
+        let enrich = |bookmark: &Bookmark, reply_count: u64| Bookmark {
+            content: Some(bookmark_content(
+                bookmark,
+                &reference(None),
+                item(reply_count),
+            )),
+            ..bookmark.clone()
+        };
+
+        let resolved = enrich(&bookmark, 2);
+        let replied = enrich(&resolved, 5);
+
+        let resolved_details = slack_details(&resolved).unwrap();
+        assert_eq!(resolved_details.reply_count, 2);
+        assert_eq!(resolved_details.new_replies(), 0);
+        assert_eq!(slack_details(&replied).unwrap().new_replies(), 3);
+        let content = replied.content.unwrap();
+        assert_eq!(content.title, "Dax in #general");
+        assert_eq!(
+            content.content_preview,
+            Some("Can someone look at this?".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolved_thread_event() {
+        let bookmark = Bookmark {
+            uri: permalink(&reference(None)).parse::<Uri>().unwrap(),
+            content: None,
+            ..Default::default()
+        };
+        let enrich = |bookmark: &Bookmark, resolved: bool| Bookmark {
+            content: Some(bookmark_content(
+                bookmark,
+                &reference(None),
+                SlackItem {
+                    resolved,
+                    ..item(1)
+                },
+            )),
+            ..bookmark.clone()
+        };
+
+        let opened = enrich(&bookmark, false);
+        let resolved = enrich(&opened, true);
+
+        assert_eq!(bookmark_event(&bookmark, &opened), None);
+        assert_eq!(bookmark_event(&resolved, &enrich(&resolved, true)), None);
+        assert_eq!(
+            bookmark_event(&opened, &resolved),
+            Some(BookmarkEvent {
+                uri: bookmark.uri.clone(),
+                provider: "slack".to_string(),
+                kind: "message".to_string(),
+                state: "resolved".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_message_description() {
+        assert_eq!(
+            message_description("\n  Deploy failed  \nSee logs"),
+            "Deploy failed"
+        );
+        assert_eq!(message_description(""), "Slack message");
+        assert_eq!(message_description(&"a".repeat(100)).len(), 80);
+    }
+}
@@ -0,0 +1,271 @@
+use super::{list_context_tasks, ContextswitchError};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use contextswitch::{BurndownPoint, DateCount, Stats, StatsPeriod, Status, Task};
+use std::collections::BTreeMap;
+
+fn period_start(date: NaiveDate, period: StatsPeriod) -> NaiveDate {
+    match period {
+        StatsPeriod::Day => date,
+        StatsPeriod::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+    }
+}
+
+fn next_period_start(date: NaiveDate, period: StatsPeriod) -> NaiveDate {
+    match period {
+        StatsPeriod::Day => date + Duration::days(1),
+        StatsPeriod::Week => date + Duration::weeks(1),
+    }
+}
+
+fn periods(from: NaiveDate, to: NaiveDate, period: StatsPeriod) -> Vec<NaiveDate> {
+    let mut periods = vec![];
+    let mut current = period_start(from, period);
+    while current <= to {
+        periods.push(current);
+        current = next_period_start(current, period);
+    }
+    periods
+}
+
+fn count_by_period(
+    dates: impl Iterator<Item = DateTime<Utc>>,
+    periods: &[NaiveDate],
+    period: StatsPeriod,
+) -> Vec<DateCount> {
+    let mut counts: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    for date in dates {
+        *counts
+            .entry(period_start(date.naive_utc().date(), period))
+            .or_default() += 1;
+    }
+
+    periods
+        .iter()
+        .map(|date| DateCount {
+            date: *date,
+            count: counts.get(date).cloned().unwrap_or(0),
+        })
+        .collect()
+}
+
+fn burndown(tasks: &[&Task], periods: &[NaiveDate], period: StatsPeriod) -> Vec<BurndownPoint> {
+    periods
+        .iter()
+        .map(|date| {
+            let period_end = next_period_start(*date, period).and_hms(0, 0, 0);
+            let entered = tasks
+                .iter()
+                .filter(|task| task.entry.naive_utc() < period_end);
+            let completed = entered
+                .clone()
+                .filter(|task| {
+                    task.status == Status::Completed
+                        && task
+                            .end
+                            .map(|end| end.naive_utc() < period_end)
+                            .unwrap_or(false)
+                })
+                .count();
+
+            BurndownPoint {
+                date: *date,
+                pending: entered.count() - completed,
+                completed,
+            }
+        })
+        .collect()
+}
+
+pub fn compute_stats(tasks: &[Task], period: StatsPeriod, now: DateTime<Utc>) -> Stats {
+    let mut by_status: BTreeMap<String, usize> = BTreeMap::new();
+    let mut by_project: BTreeMap<String, usize> = BTreeMap::new();
+    let mut by_tag: BTreeMap<String, usize> = BTreeMap::new();
+    for task in tasks {
+        *by_status.entry(task.status.to_string()).or_default() += 1;
+        if task.status == Status::Deleted {
+            continue;
+        }
+        if let Some(project) = &task.project {
+            *by_project.entry(project.clone()).or_default() += 1;
+        }
+        for tag in task.tags.iter().flatten() {
+            *by_tag.entry(tag.clone()).or_default() += 1;
+        }
+    }
+
+    let active_tasks: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| task.status == Status::Pending || task.status == Status::Completed)
+        .collect();
+    let completed_tasks: Vec<&Task> = active_tasks
+        .iter()
+        .filter(|task| task.status == Status::Completed)
+        .cloned()
+        .collect();
+    let completion_times: Vec<i64> = completed_tasks
+        .iter()
+        .filter_map(|task| task.end.map(|end| (end - task.entry).num_seconds()))
+        .collect();
+    let average_completion_seconds = if completion_times.is_empty() {
+        None
+    } else {
+        Some(completion_times.iter().sum::<i64>() / completion_times.len() as i64)
+    };
+    let overdue = active_tasks
+        .iter()
+        .filter(|task| {
+            task.status == Status::Pending && task.due.map(|due| due < now).unwrap_or(false)
+        })
+        .count();
+
+    let periods = active_tasks
+        .iter()
+        .map(|task| task.entry.naive_utc().date())
+        .min()
+        .map(|first_date| periods(first_date, now.naive_utc().date(), period))
+        .unwrap_or_default();
+
+    Stats {
+        period,
+        by_status,
+        by_project,
+        by_tag,
+        created: count_by_period(active_tasks.iter().map(|task| task.entry), &periods, period),
+        completed: count_by_period(
+            completed_tasks.iter().filter_map(|task| task.end),
+            &periods,
+            period,
+        ),
+        average_completion_seconds,
+        overdue,
+        burndown: burndown(&active_tasks, &periods, period),
+    }
+}
+
+#[tracing::instrument(level = "debug")]
+pub fn get_stats(filters: Vec<&str>, period: StatsPeriod) -> Result<Stats, ContextswitchError> {
+    let tasks = list_context_tasks(filters)?;
+
+    Ok(compute_stats(&tasks, period, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+    use chrono::TimeZone;
+
+    fn task(status: Status, entry_day: u32, end_day: Option<u32>) -> Task {
+        Task {
+            entry: Utc.ymd(2022, 1, entry_day).and_hms(10, 0, 0),
+            modified: Utc.ymd(2022, 1, entry_day).and_hms(10, 0, 0),
+            status,
+            end: end_day.map(|day| Utc.ymd(2022, 1, day).and_hms(12, 0, 0)),
+            project: Some("project".to_string()),
+            tags: Some(vec!["tag".to_string()]),
+            ..test_helpers::task("task")
+        }
+    }
+
+    #[test]
+    fn test_compute_daily_stats() {
+        let now = Utc.ymd(2022, 1, 5).and_hms(12, 0, 0);
+        let mut overdue_task = task(Status::Pending, 3, None);
+        overdue_task.due = Some(Utc.ymd(2022, 1, 4).and_hms(0, 0, 0));
+        let tasks = vec![
+            task(Status::Completed, 3, Some(4)),
+            task(Status::Pending, 4, None),
+            task(Status::Deleted, 4, Some(5)),
+            overdue_task,
+        ];
+
+        let stats = compute_stats(&tasks, StatsPeriod::Day, now);
+
+        assert_eq!(
+            stats.by_status,
+            BTreeMap::from([
+                ("completed".to_string(), 1),
+                ("deleted".to_string(), 1),
+                ("pending".to_string(), 2)
+            ])
+        );
+        assert_eq!(
+            stats.by_project,
+            BTreeMap::from([("project".to_string(), 3)])
+        );
+        assert_eq!(stats.by_tag, BTreeMap::from([("tag".to_string(), 3)]));
+        assert_eq!(stats.overdue, 1);
+        assert_eq!(stats.average_completion_seconds, Some(26 * 3600));
+        assert_eq!(
+            stats
+                .created
+                .iter()
+                .map(|date_count| date_count.count)
+                .collect::<Vec<usize>>(),
+            vec![2, 1, 0]
+        );
+        assert_eq!(
+            stats
+                .completed
+                .iter()
+                .map(|date_count| date_count.count)
+                .collect::<Vec<usize>>(),
+            vec![0, 1, 0]
+        );
+        assert_eq!(
+            stats.burndown,
+            vec![
+                BurndownPoint {
+                    date: NaiveDate::from_ymd(2022, 1, 3),
+                    pending: 2,
+                    completed: 0
+                },
+                BurndownPoint {
+                    date: NaiveDate::from_ymd(2022, 1, 4),
+                    pending: 2,
+                    completed: 1
+                },
+                BurndownPoint {
+                    date: NaiveDate::from_ymd(2022, 1, 5),
+                    pending: 2,
+                    completed: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compute_weekly_stats() {
+        // 2022-01-03 is a Monday
+        let now = Utc.ymd(2022, 1, 12).and_hms(12, 0, 0);
+        let tasks = vec![
+            task(Status::Completed, 4, Some(11)),
+            task(Status::Pending, 9, None),
+        ];
+
+        let stats = compute_stats(&tasks, StatsPeriod::Week, now);
+
+        assert_eq!(
+            stats.created,
+            vec![
+                DateCount {
+                    date: NaiveDate::from_ymd(2022, 1, 3),
+                    count: 2
+                },
+                DateCount {
+                    date: NaiveDate::from_ymd(2022, 1, 10),
+                    count: 0
+                },
+            ]
+        );
+        assert_eq!(stats.completed[1].count, 1);
+    }
+
+    #[test]
+    fn test_compute_stats_without_tasks() {
+        let stats = compute_stats(&[], StatsPeriod::Day, Utc::now());
+
+        assert!(stats.burndown.is_empty());
+        assert_eq!(stats.average_completion_seconds, None);
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::Context;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+use super::ContextswitchError;
+
+const STORE_DIRECTORY: &str = "contextswitch";
+
+pub fn store_path(data_location: &str, file_name: &str) -> PathBuf {
+    Path::new(data_location)
+        .join(STORE_DIRECTORY)
+        .join(file_name)
+}
+
+#[derive(Debug)]
+pub struct JsonStore<T> {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+    data_type: PhantomData<T>,
+}
+
+impl<T> JsonStore<T>
+where
+    T: Default + Serialize + DeserializeOwned,
+{
+    pub fn new(path: PathBuf) -> Self {
+        JsonStore {
+            path,
+            write_lock: Mutex::new(()),
+            data_type: PhantomData,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn load(&self) -> Result<T, ContextswitchError> {
+        if !self.path.exists() {
+            return Ok(T::default());
+        }
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Cannot read {}", self.path.display()))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub async fn update<F, R>(&self, update: F) -> Result<R, ContextswitchError>
+    where
+        F: FnOnce(&mut T) -> Result<R, ContextswitchError>,
+    {
+        let _lock = self.write_lock.lock().await;
+        let mut data = self.load()?;
+        let result = update(&mut data)?;
+        self.save(&data)?;
+
+        Ok(result)
+    }
+
+    fn save(&self, data: &T) -> Result<(), ContextswitchError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Cannot create directory {}", parent.display()))?;
+        }
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(data)?)
+            .with_context(|| format!("Cannot write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Cannot write {}", self.path.display()))?;
+
+        Ok(())
+    }
+}
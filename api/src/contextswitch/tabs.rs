@@ -0,0 +1,217 @@
+use super::bookmarks::update_bookmarks;
+use super::providers::ProviderRegistry;
+use super::{get_task, ContextswitchError};
+use chrono::{DateTime, Utc};
+use contextswitch::canonical::UrlCanonicalizer;
+use contextswitch::{Bookmark, BookmarkContent, ContextswitchData, Tab, TabPanel, Task, TaskId};
+
+fn tab_bookmark(
+    registry: &ProviderRegistry,
+    tab: Tab,
+    panel: &str,
+    previous_tabs: &ContextswitchData,
+    canonicalizer: &UrlCanonicalizer,
+    now: DateTime<Utc>,
+) -> Bookmark {
+    // Content of tabs already saved is kept, it may have been enriched since
+    let previous_bookmark = previous_tabs.find_bookmark(&tab.url, canonicalizer);
+    let previous_content = previous_bookmark.and_then(|bookmark| bookmark.content.clone());
+    let content = match (previous_content, tab.title) {
+        (Some(content), Some(title)) if content.details.is_none() => {
+            Some(BookmarkContent { title, ..content })
+        }
+        (Some(content), _) => Some(content),
+        (None, Some(title)) => Some(BookmarkContent {
+            title,
+            content_preview: None,
+            details: None,
+            badge: None,
+        }),
+        (None, None) => None,
+    };
+
+    // So are the label, pin and position given by the user
+    match previous_bookmark {
+        Some(previous_bookmark) => Bookmark {
+            uri: tab.url,
+            content,
+            panel: Some(panel.to_string()),
+            ..previous_bookmark.clone()
+        },
+        None => Bookmark {
+            kind: Some(registry.bookmark_kind(&tab.url)),
+            uri: tab.url,
+            content,
+            panel: Some(panel.to_string()),
+            label: None,
+            added_at: Some(now),
+            pinned: false,
+            position: None,
+        },
+    }
+}
+
+// The same page opened in several tabs of the panel is only saved once
+fn replace_panel(
+    registry: &ProviderRegistry,
+    bookmarks: Vec<Bookmark>,
+    tab_panel: TabPanel,
+    canonicalizer: &UrlCanonicalizer,
+    now: DateTime<Utc>,
+) -> Vec<Bookmark> {
+    let (previous_bookmarks, bookmarks): (Vec<Bookmark>, Vec<Bookmark>) = bookmarks
+        .into_iter()
+        .partition(|bookmark| bookmark.panel.as_ref() == Some(&tab_panel.panel));
+    let previous_tabs = ContextswitchData {
+        bookmarks: previous_bookmarks,
+        ..Default::default()
+    };
+    let panel = tab_panel.panel;
+    let mut tabs = ContextswitchData::default();
+    for tab in tab_panel.tabs {
+        tabs.add_bookmark(
+            tab_bookmark(registry, tab, &panel, &previous_tabs, canonicalizer, now),
+            canonicalizer,
+        );
+    }
+
+    [bookmarks, tabs.bookmarks].concat()
+}
+
+fn group_tabs(bookmarks: &[Bookmark]) -> Vec<TabPanel> {
+    let mut tab_panels: Vec<TabPanel> = vec![];
+    for bookmark in bookmarks {
+        let panel = match &bookmark.panel {
+            Some(panel) => panel,
+            None => continue,
+        };
+        let tab = Tab {
+            url: bookmark.uri.clone(),
+            title: bookmark
+                .content
+                .as_ref()
+                .map(|content| content.title.clone()),
+        };
+        match tab_panels
+            .iter_mut()
+            .find(|tab_panel| &tab_panel.panel == panel)
+        {
+            Some(tab_panel) => tab_panel.tabs.push(tab),
+            None => tab_panels.push(TabPanel {
+                panel: panel.clone(),
+                tabs: vec![tab],
+            }),
+        }
+    }
+
+    tab_panels
+}
+
+// Tabs saved previously for the same panel are replaced
+#[tracing::instrument(level = "debug", skip(registry, canonicalizer, tab_panel), fields(panel = %tab_panel.panel))]
+pub async fn save_tabs(
+    registry: &ProviderRegistry,
+    canonicalizer: &UrlCanonicalizer,
+    task_id: &TaskId,
+    tab_panel: TabPanel,
+) -> Result<Task, ContextswitchError> {
+    if tab_panel.panel.trim().is_empty() {
+        return Err(ContextswitchError::InvalidInputError(
+            "Tabs panel name cannot be empty".to_string(),
+        ));
+    }
+    update_bookmarks(task_id, |bookmarks| {
+        Ok(replace_panel(
+            registry,
+            bookmarks,
+            tab_panel,
+            canonicalizer,
+            Utc::now(),
+        ))
+    })
+    .await
+}
+
+#[tracing::instrument(level = "debug")]
+pub fn list_tabs(
+    task_id: &TaskId,
+    panel: Option<&str>,
+) -> Result<Vec<TabPanel>, ContextswitchError> {
+    let task = get_task(task_id)?;
+    let bookmarks = task
+        .contextswitch
+        .map(|contextswitch| contextswitch.bookmarks)
+        .unwrap_or_default();
+
+    Ok(group_tabs(&bookmarks)
+        .into_iter()
+        .filter(|tab_panel| panel.iter().all(|panel| &tab_panel.panel == panel))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::providers::new_provider_registry;
+    use http::uri::Uri;
+
+    fn tab(url: &str, title: Option<&str>) -> Tab {
+        Tab {
+            url: url.parse::<Uri>().unwrap(),
+            title: title.map(String::from),
+        }
+    }
+
+    fn bookmark(url: &str, panel: Option<&str>) -> Bookmark {
+        Bookmark {
+            uri: url.parse::<Uri>().unwrap(),
+            panel: panel.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_replace_panel() {
+        let bookmarks = vec![
+            bookmark("https://example.com/", None),
+            bookmark("https://docs.rs/", Some("rust")),
+            bookmark("https://crates.io/", Some("rust")),
+            bookmark("https://news.ycombinator.com/", Some("news")),
+        ];
+
+        let bookmarks = replace_panel(
+            &new_provider_registry(&[], vec![]),
+            bookmarks,
+            TabPanel {
+                panel: "rust".to_string(),
+                tabs: vec![
+                    tab("https://crates.io/", None),
+                    tab("https://doc.rust-lang.org/", Some("Rust documentation")),
+                    tab("https://CRATES.io/?utm_source=rust", None),
+                    tab("https://example.com/", Some("Example")),
+                ],
+            },
+            &UrlCanonicalizer::default(),
+            Utc::now(),
+        );
+
+        assert_eq!(
+            group_tabs(&bookmarks),
+            vec![
+                TabPanel {
+                    panel: "news".to_string(),
+                    tabs: vec![tab("https://news.ycombinator.com/", None)],
+                },
+                TabPanel {
+                    panel: "rust".to_string(),
+                    tabs: vec![
+                        tab("https://crates.io/", None),
+                        tab("https://doc.rust-lang.org/", Some("Rust documentation")),
+                        tab("https://example.com/", Some("Example")),
+                    ],
+                },
+            ]
+        );
+        assert_eq!(bookmarks[0], bookmark("https://example.com/", None));
+    }
+}
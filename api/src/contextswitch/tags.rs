@@ -0,0 +1,291 @@
+use super::taskwarrior::{self, TaskwarriorError};
+use super::{events, list_context_tasks, ContextswitchError};
+use anyhow::anyhow;
+use chrono::{DateTime, Duration, Utc};
+use contextswitch::{Status, Tag, TagChange, Task, TaskId};
+use std::collections::BTreeMap;
+
+// Taskwarrior virtual tags, they cannot be given to a task
+const VIRTUAL_TAGS: [&str; 32] = [
+    "ACTIVE",
+    "ANNOTATED",
+    "BLOCKED",
+    "BLOCKING",
+    "CHILD",
+    "COMPLETED",
+    "DELETED",
+    "DUE",
+    "DUETODAY",
+    "INSTANCE",
+    "LATEST",
+    "MONTH",
+    "ORPHAN",
+    "OVERDUE",
+    "PARENT",
+    "PENDING",
+    "PRIORITY",
+    "PROJECT",
+    "QUARTER",
+    "READY",
+    "SCHEDULED",
+    "TAGGED",
+    "TEMPLATE",
+    "TODAY",
+    "TOMORROW",
+    "UDA",
+    "UNBLOCKED",
+    "UNTIL",
+    "WAITING",
+    "WEEK",
+    "YEAR",
+    "YESTERDAY",
+];
+
+// Only the virtual tags which can be computed from exported task fields
+pub fn virtual_tags(task: &Task, now: DateTime<Utc>) -> Vec<&'static str> {
+    let is_pending = task.status == Status::Pending;
+    let is_due_today = task
+        .due
+        .map(|due| due.date() == now.date())
+        .unwrap_or(false);
+    let tags_by_condition = [
+        ("ACTIVE", is_pending && task.start.is_some()),
+        ("CHILD", task.parent.is_some()),
+        ("COMPLETED", task.status == Status::Completed),
+        ("DELETED", task.status == Status::Deleted),
+        (
+            "DUE",
+            is_pending
+                && task
+                    .due
+                    .map(|due| due >= now && due <= now + Duration::days(7))
+                    .unwrap_or(false),
+        ),
+        ("DUETODAY", is_pending && is_due_today),
+        (
+            "OVERDUE",
+            is_pending && task.due.map(|due| due < now).unwrap_or(false),
+        ),
+        ("PARENT", task.status == Status::Recurring),
+        ("PENDING", is_pending),
+        ("PRIORITY", task.priority.is_some()),
+        ("PROJECT", task.project.is_some()),
+        (
+            "TAGGED",
+            task.tags
+                .as_ref()
+                .map(|tags| !tags.is_empty())
+                .unwrap_or(false),
+        ),
+        ("TODAY", is_pending && is_due_today),
+        (
+            "WAITING",
+            is_pending && task.wait.map(|wait| wait > now).unwrap_or(false),
+        ),
+    ];
+
+    tags_by_condition
+        .iter()
+        .filter(|(_, condition)| *condition)
+        .map(|(tag, _)| *tag)
+        .collect()
+}
+
+pub fn count_tags(tasks: &[Task], now: DateTime<Utc>) -> Vec<Tag> {
+    let mut tag_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut virtual_tag_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for task in tasks {
+        for tag in task.tags.iter().flatten() {
+            *tag_counts.entry(tag).or_default() += 1;
+        }
+        for tag in virtual_tags(task, now) {
+            *virtual_tag_counts.entry(tag).or_default() += 1;
+        }
+    }
+
+    let tags = tag_counts.into_iter().map(|(name, count)| Tag {
+        name: name.to_string(),
+        count,
+        is_virtual: false,
+    });
+    let virtual_tags = virtual_tag_counts.into_iter().map(|(name, count)| Tag {
+        name: name.to_string(),
+        count,
+        is_virtual: true,
+    });
+
+    tags.chain(virtual_tags).collect()
+}
+
+fn validate_tag(tag: &str) -> Result<(), ContextswitchError> {
+    if tag.is_empty()
+        || tag.contains(char::is_whitespace)
+        || tag.starts_with(['+', '-'])
+        || VIRTUAL_TAGS.contains(&tag)
+    {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Invalid tag '{}'",
+            tag
+        )));
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug")]
+pub fn list_tags() -> Result<Vec<Tag>, ContextswitchError> {
+    let tasks = list_context_tasks(vec![])?;
+
+    Ok(count_tags(&tasks, Utc::now()))
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn rename_tag(tag: &str, to: &str) -> Result<TagChange, ContextswitchError> {
+    merge_tags(vec![tag.to_string()], to).await
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn merge_tags(
+    sources: Vec<String>,
+    target: &str,
+) -> Result<TagChange, ContextswitchError> {
+    if sources.is_empty() {
+        return Err(ContextswitchError::InvalidInputError(
+            "No tag to merge".to_string(),
+        ));
+    }
+    for tag in sources.iter().map(String::as_str).chain([target]) {
+        validate_tag(tag)?;
+    }
+
+    let tag_filters: Vec<String> = sources.iter().map(|tag| format!("+{}", tag)).collect();
+    let mut filter = vec!["("];
+    for (index, tag_filter) in tag_filters.iter().enumerate() {
+        if index > 0 {
+            filter.push("or");
+        }
+        filter.push(tag_filter);
+    }
+    filter.push(")");
+    let result = taskwarrior::update_filtered_tasks(filter, |task| {
+        let task_tags = task.tags.clone().unwrap_or_default();
+        let removed_tags: Vec<String> = sources
+            .iter()
+            .filter(|tag| *tag != target && task_tags.contains(tag))
+            .map(|tag| format!("-{}", tag))
+            .collect();
+        if removed_tags.is_empty() && task_tags.iter().any(|tag| tag == target) {
+            return None;
+        }
+        Some(
+            removed_tags
+                .into_iter()
+                .chain([format!("+{}", target)])
+                .collect(),
+        )
+    })
+    .await;
+    let updated_tasks = match result {
+        Ok(updated_tasks) => updated_tasks,
+        Err(TaskwarriorError::PartialUpdateError { updated, source }) => {
+            for task in &updated {
+                events::publish_updated(&task.into());
+            }
+            let updated_ids: Vec<String> =
+                updated.iter().map(|task| task.uuid.to_string()).collect();
+            return Err(ContextswitchError::UnexpectedError(anyhow!(
+                "Tag merge stopped after rewriting tasks [{}]: {}",
+                updated_ids.join(", "),
+                source
+            )));
+        }
+        Err(e) => return Err(ContextswitchError::UnexpectedError(e.into())),
+    };
+    for task in &updated_tasks {
+        events::publish_updated(&task.into());
+    }
+
+    Ok(TagChange {
+        sources,
+        target: target.to_string(),
+        tasks: updated_tasks
+            .into_iter()
+            .map(|task| TaskId::from(task.uuid))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+    use chrono::TimeZone;
+
+    fn task(tags: &[&str]) -> Task {
+        Task {
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            ..test_helpers::task("task")
+        }
+    }
+
+    #[test]
+    fn test_virtual_tags() {
+        let now = Utc.ymd(2022, 1, 10).and_hms(12, 0, 0);
+        let mut overdue_task = task(&[]);
+        overdue_task.due = Some(Utc.ymd(2022, 1, 9).and_hms(12, 0, 0));
+        let mut active_task = task(&["tag"]);
+        active_task.start = Some(Utc.ymd(2022, 1, 10).and_hms(8, 0, 0));
+        active_task.due = Some(Utc.ymd(2022, 1, 10).and_hms(18, 0, 0));
+
+        assert_eq!(vec!["OVERDUE", "PENDING"], virtual_tags(&overdue_task, now));
+        assert_eq!(
+            vec!["ACTIVE", "DUE", "DUETODAY", "PENDING", "TAGGED", "TODAY"],
+            virtual_tags(&active_task, now)
+        );
+    }
+
+    #[test]
+    fn test_count_tags() {
+        let now = Utc.ymd(2022, 1, 10).and_hms(12, 0, 0);
+        let tasks = vec![task(&["home", "garden"]), task(&["home"]), task(&[])];
+
+        let tags = count_tags(&tasks, now);
+
+        assert_eq!(
+            vec![
+                Tag {
+                    name: "garden".to_string(),
+                    count: 1,
+                    is_virtual: false
+                },
+                Tag {
+                    name: "home".to_string(),
+                    count: 2,
+                    is_virtual: false
+                },
+                Tag {
+                    name: "PENDING".to_string(),
+                    count: 3,
+                    is_virtual: true
+                },
+                Tag {
+                    name: "TAGGED".to_string(),
+                    count: 2,
+                    is_virtual: true
+                },
+            ],
+            tags
+        );
+    }
+
+    #[test]
+    fn test_validate_tag() {
+        assert!(validate_tag("home").is_ok());
+        assert!(validate_tag("").is_err());
+        assert!(validate_tag("two words").is_err());
+        assert!(validate_tag("+home").is_err());
+        assert!(validate_tag("OVERDUE").is_err());
+        assert!(validate_tag("BLOCKED").is_err());
+        assert!(validate_tag("READY").is_err());
+    }
+}
@@ -0,0 +1,317 @@
+use super::store::{store_path, JsonStore};
+use super::{get_task, list_tasks, update_contextswitch, ContextswitchError};
+use anyhow::anyhow;
+use chrono::Utc;
+use contextswitch::{
+    NoteRevision, NoteRevisionInfo, NoteSearchResult, NotesData, NotesUpdate, Status, Task, TaskId,
+    TaskNotes,
+};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+const NOTES_DIRECTORY: &str = "notes";
+// Above this length, the content is only kept in the notes store
+const INLINE_NOTES_MAX_LENGTH: usize = 2048;
+const MAX_REVISIONS: usize = 50;
+const MAX_SNIPPET_LENGTH: usize = 120;
+
+type NoteHistory = Vec<NoteRevision>;
+
+#[derive(Debug)]
+pub struct NoteStore {
+    directory: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+pub fn new_note_store(data_location: &str) -> NoteStore {
+    NoteStore {
+        directory: store_path(data_location, NOTES_DIRECTORY),
+        write_lock: Mutex::new(()),
+    }
+}
+
+impl NoteStore {
+    fn history(&self, task_id: &TaskId) -> JsonStore<NoteHistory> {
+        JsonStore::new(self.directory.join(format!("{}.json", task_id)))
+    }
+}
+
+fn notes_data(task: &Task) -> Option<&NotesData> {
+    task.contextswitch
+        .as_ref()
+        .and_then(|contextswitch| contextswitch.notes.as_ref())
+}
+
+fn revision_content(history: &[NoteRevision], revision: u32) -> Option<String> {
+    history
+        .iter()
+        .find(|note_revision| note_revision.revision == revision)
+        .map(|note_revision| note_revision.content.clone())
+}
+
+fn current_content(
+    notes_data: &NotesData,
+    history: &[NoteRevision],
+) -> Result<String, ContextswitchError> {
+    match &notes_data.content {
+        Some(content) => Ok(content.clone()),
+        None => revision_content(history, notes_data.revision).ok_or_else(|| {
+            ContextswitchError::UnexpectedError(anyhow!(
+                "Notes revision {} is missing from the notes store",
+                notes_data.revision
+            ))
+        }),
+    }
+}
+
+// Oldest revisions are dropped first
+fn push_revision(history: &mut NoteHistory, note_revision: NoteRevision, max_revisions: usize) {
+    history.push(note_revision);
+    if history.len() > max_revisions {
+        history.drain(..history.len() - max_revisions);
+    }
+}
+
+fn new_notes_data(note_revision: &NoteRevision) -> NotesData {
+    NotesData {
+        revision: note_revision.revision,
+        modified: note_revision.date,
+        content: Some(note_revision.content.clone())
+            .filter(|content| content.len() <= INLINE_NOTES_MAX_LENGTH),
+    }
+}
+
+fn task_notes(
+    task_id: &TaskId,
+    notes_data: Option<&NotesData>,
+    content: String,
+    history: &[NoteRevision],
+) -> TaskNotes {
+    TaskNotes {
+        task_id: task_id.clone(),
+        revision: notes_data
+            .map(|notes_data| notes_data.revision)
+            .unwrap_or(0),
+        modified: notes_data.map(|notes_data| notes_data.modified),
+        content,
+        revisions: history
+            .iter()
+            .rev()
+            .map(|note_revision| NoteRevisionInfo {
+                revision: note_revision.revision,
+                date: note_revision.date,
+                length: note_revision.content.len(),
+            })
+            .collect(),
+    }
+}
+
+#[tracing::instrument(level = "debug", skip(note_store))]
+pub fn get_task_notes(
+    note_store: &NoteStore,
+    task_id: &TaskId,
+    revision: Option<u32>,
+) -> Result<TaskNotes, ContextswitchError> {
+    let task = get_task(task_id)?;
+    let history = note_store.history(task_id).load()?;
+    let notes_data = notes_data(&task);
+    let content = match (revision, notes_data) {
+        (Some(revision), _) => revision_content(&history, revision).ok_or_else(|| {
+            ContextswitchError::NotFoundError(format!("Notes revision {}", revision))
+        })?,
+        (None, Some(notes_data)) => current_content(notes_data, &history)?,
+        (None, None) => String::new(),
+    };
+
+    Ok(task_notes(task_id, notes_data, content, &history))
+}
+
+#[tracing::instrument(level = "debug", skip(note_store, notes_update))]
+pub async fn save_task_notes(
+    note_store: &NoteStore,
+    task_id: &TaskId,
+    notes_update: NotesUpdate,
+) -> Result<TaskNotes, ContextswitchError> {
+    let _lock = note_store.write_lock.lock().await;
+    let task = get_task(task_id)?;
+    let history_store = note_store.history(task_id);
+    let history = history_store.load()?;
+    let revision = notes_data(&task)
+        .map(|notes_data| notes_data.revision)
+        .unwrap_or(0);
+    if let Some(base_revision) = notes_update.revision.filter(|base| *base != revision) {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Notes were modified since revision {}, current revision is {}",
+            base_revision, revision
+        )));
+    }
+    let content = match notes_data(&task) {
+        Some(notes_data) => current_content(notes_data, &history)?,
+        None => String::new(),
+    };
+    if content == notes_update.content {
+        return Ok(task_notes(task_id, notes_data(&task), content, &history));
+    }
+
+    let note_revision = NoteRevision {
+        revision: revision + 1,
+        date: Utc::now(),
+        content: notes_update.content,
+    };
+    let history = history_store
+        .update(|history| {
+            push_revision(history, note_revision.clone(), MAX_REVISIONS);
+            Ok(history.clone())
+        })
+        .await?;
+    let task = update_contextswitch(task_id, |_, contextswitch| {
+        contextswitch.notes = Some(new_notes_data(&note_revision));
+        Ok(())
+    })
+    .await?;
+
+    Ok(task_notes(
+        task_id,
+        notes_data(&task),
+        note_revision.content,
+        &history,
+    ))
+}
+
+// The first matching line, shortened around the match when too long
+fn snippet(content: &str, query: &str) -> Option<String> {
+    let query = query.to_lowercase();
+    let line = content
+        .lines()
+        .find(|line| line.to_lowercase().contains(&query))?
+        .trim();
+    if line.chars().count() <= MAX_SNIPPET_LENGTH {
+        return Some(line.to_string());
+    }
+    let lowercase_line = line.to_lowercase();
+    let match_start = lowercase_line
+        .find(&query)
+        .map(|index| lowercase_line[..index].chars().count())
+        .unwrap_or(0);
+    let start = match_start.saturating_sub(MAX_SNIPPET_LENGTH / 4);
+    let snippet: String = line.chars().skip(start).take(MAX_SNIPPET_LENGTH).collect();
+
+    Some(format!(
+        "{}{}{}",
+        if start > 0 { "…" } else { "" },
+        snippet.trim(),
+        if start + MAX_SNIPPET_LENGTH < line.chars().count() {
+            "…"
+        } else {
+            ""
+        }
+    ))
+}
+
+fn search_content(
+    task: &Task,
+    notes_data: &NotesData,
+    content: &str,
+    query: &str,
+) -> Option<NoteSearchResult> {
+    snippet(content, query).map(|snippet| NoteSearchResult {
+        task_id: task.id.clone(),
+        description: task.description.clone(),
+        revision: notes_data.revision,
+        snippet,
+    })
+}
+
+#[tracing::instrument(level = "debug", skip(note_store))]
+pub fn search_task_notes(
+    note_store: &NoteStore,
+    query: &str,
+) -> Result<Vec<NoteSearchResult>, ContextswitchError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(ContextswitchError::InvalidInputError(
+            "Notes search query cannot be empty".to_string(),
+        ));
+    }
+
+    let mut results = vec![];
+    for task in list_tasks(vec![])?
+        .iter()
+        .filter(|task| task.status != Status::Deleted)
+    {
+        let notes_data = match notes_data(task) {
+            Some(notes_data) => notes_data,
+            None => continue,
+        };
+        let content = match &notes_data.content {
+            Some(content) => content.clone(),
+            None => current_content(notes_data, &note_store.history(&task.id).load()?)?,
+        };
+        if let Some(result) = search_content(task, notes_data, &content, query) {
+            results.push((notes_data.modified, result));
+        }
+    }
+    // Most recently edited notes first
+    results.sort_by(|(modified1, _), (modified2, _)| modified2.cmp(modified1));
+
+    Ok(results.into_iter().map(|(_, result)| result).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn note_revision(revision: u32, content: &str) -> NoteRevision {
+        NoteRevision {
+            revision,
+            date: Utc.ymd(2022, 1, 1).and_hms(10, revision, 0),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_push_revision_drops_oldest() {
+        let mut history = vec![];
+        for revision in 1..=4 {
+            push_revision(&mut history, note_revision(revision, "notes"), 3);
+        }
+
+        assert_eq!(
+            history
+                .iter()
+                .map(|note_revision| note_revision.revision)
+                .collect::<Vec<u32>>(),
+            vec![2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn test_large_notes_are_not_inlined() {
+        let small = note_revision(1, "# Notes");
+        let large = note_revision(2, &"a".repeat(INLINE_NOTES_MAX_LENGTH + 1));
+
+        assert_eq!(new_notes_data(&small).content, Some("# Notes".to_string()));
+        assert_eq!(new_notes_data(&large).content, None);
+        assert_eq!(
+            current_content(&new_notes_data(&large), &[small, large.clone()]).unwrap(),
+            large.content
+        );
+    }
+
+    #[test]
+    fn test_snippet() {
+        let content = "# Release\n\n- Update the CHANGELOG\n- Tag the release";
+
+        assert_eq!(
+            snippet(content, "changelog"),
+            Some("- Update the CHANGELOG".to_string())
+        );
+        assert_eq!(snippet(content, "deploy"), None);
+
+        let long_line = format!("{} needle {}", "a".repeat(200), "b".repeat(200));
+        let long_snippet = snippet(&long_line, "needle").unwrap();
+        assert!(long_snippet.starts_with('…') && long_snippet.ends_with('…'));
+        assert!(long_snippet.contains("needle"));
+    }
+}
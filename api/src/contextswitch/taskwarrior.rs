@@ -2,10 +2,11 @@ use crate::configuration::TaskwarriorSettings;
 use anyhow::{anyhow, Context};
 use chrono::{DateTime, Utc};
 use configparser::ini::Ini;
-use contextswitch::{ContextswitchData, Task, TaskId};
+use contextswitch::{migrations, Task, TaskId};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json;
+use std::collections::BTreeMap;
 use std::env;
 use std::fmt;
 use std::path::Path;
@@ -108,11 +109,86 @@ pub async fn add_task(add_args: Vec<&str>) -> Result<TaskwarriorTask, Taskwarrio
 
 #[tracing::instrument(level = "debug")]
 pub async fn update_task(action: TaskwarriorAction) -> Result<TaskwarriorTask, TaskwarriorError> {
+    let _lock = TW_WRITE_LOCK.lock().await;
+
+    run_update(action)
+}
+
+// Tasks are listed and modified under the same lock, a task changed in
+// between cannot be missed. Tasks without modification arguments are skipped.
+#[tracing::instrument(level = "debug", skip(build_args))]
+pub async fn update_filtered_tasks<F>(
+    filters: Vec<&str>,
+    build_args: F,
+) -> Result<Vec<TaskwarriorTask>, TaskwarriorError>
+where
+    F: Fn(&TaskwarriorTask) -> Option<Vec<String>>,
+{
+    let _lock = TW_WRITE_LOCK.lock().await;
+
+    let mut updated_tasks = vec![];
+    for task in list_tasks(filters)? {
+        let args = match build_args(&task) {
+            Some(args) => args,
+            None => continue,
+        };
+        let uuid = task.uuid.to_string();
+        let result = execute_command(
+            [
+                vec![uuid.as_str(), "mod"],
+                args.iter().map(String::as_str).collect(),
+            ]
+            .concat(),
+        )
+        .and_then(|_| {
+            get_task_by_id(&task.uuid)?.ok_or_else(|| {
+                TaskwarriorError::UnexpectedError(anyhow!(
+                    "Updated task with UUID {} was not found",
+                    task.uuid
+                ))
+            })
+        });
+        match result {
+            Ok(updated_task) => updated_tasks.push(updated_task),
+            Err(e) => {
+                return Err(TaskwarriorError::PartialUpdateError {
+                    updated: updated_tasks,
+                    source: Box::new(e),
+                })
+            }
+        }
+    }
+
+    Ok(updated_tasks)
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn run_task_commands(
+    uuid: &TaskwarriorTaskId,
+    commands: Vec<Vec<String>>,
+) -> Result<TaskwarriorTask, TaskwarriorError> {
+    let _lock = TW_WRITE_LOCK.lock().await;
+
+    for command in commands {
+        let uuid = uuid.to_string();
+        let args = [
+            vec![uuid.as_str()],
+            command.iter().map(String::as_str).collect(),
+        ]
+        .concat();
+        execute_command(args)?;
+    }
+
+    get_task_by_id(uuid)?.ok_or_else(|| {
+        TaskwarriorError::UnexpectedError(anyhow!("Task with UUID {} was not found", uuid))
+    })
+}
+
+fn run_update(action: TaskwarriorAction) -> Result<TaskwarriorTask, TaskwarriorError> {
     lazy_static! {
         static ref RE: Regex = Regex::new(r"Modified 1 task.").unwrap();
     }
 
-    let _lock = TW_WRITE_LOCK.lock().await;
     let args = [
         vec![action.uuid.to_string(), "mod".to_string()],
         action.args,
@@ -132,6 +208,127 @@ pub async fn update_task(action: TaskwarriorAction) -> Result<TaskwarriorTask, T
     })
 }
 
+fn execute_command(args: Vec<&str>) -> Result<String, TaskwarriorError> {
+    let command_output = Command::new("task")
+        .args(args)
+        .output()
+        .map_err(TaskwarriorError::ExecutionError)?;
+    if !command_output.status.success() {
+        let error_output = String::from_utf8_lossy(&command_output.stderr);
+        return Err(TaskwarriorError::UnexpectedError(anyhow!(
+            "Taskwarrior command failed: {}",
+            error_output.trim()
+        )));
+    }
+
+    let output =
+        String::from_utf8(command_output.stdout).context("Failed to read Taskwarrior output")?;
+    Ok(output)
+}
+
+#[tracing::instrument(level = "debug")]
+pub fn list_contexts() -> Result<Vec<TaskwarriorContext>, TaskwarriorError> {
+    let output = execute_command(vec!["_show"])?;
+
+    Ok(parse_contexts(&output))
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn define_context(context: TaskwarriorContext) -> Result<(), TaskwarriorError> {
+    let _lock = TW_WRITE_LOCK.lock().await;
+
+    let read_key = format!("context.{}.read", context.name);
+    execute_command(vec![
+        "rc.confirmation=off",
+        "config",
+        &read_key,
+        &context.read_filter,
+    ])?;
+    let write_key = format!("context.{}.write", context.name);
+    if let Some(write_filter) = &context.write_filter {
+        execute_command(vec![
+            "rc.confirmation=off",
+            "config",
+            &write_key,
+            write_filter,
+        ])?;
+    } else if list_contexts()?
+        .iter()
+        .any(|c| c.name == context.name && c.write_filter.is_some())
+    {
+        execute_command(vec!["rc.confirmation=off", "config", &write_key])?;
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn use_context(name: Option<&str>) -> Result<(), TaskwarriorError> {
+    let _lock = TW_WRITE_LOCK.lock().await;
+
+    execute_command(vec![
+        "rc.confirmation=off",
+        "context",
+        name.unwrap_or("none"),
+    ])?;
+
+    Ok(())
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn delete_context(name: &str) -> Result<(), TaskwarriorError> {
+    let _lock = TW_WRITE_LOCK.lock().await;
+
+    execute_command(vec!["rc.confirmation=off", "context", "delete", name])?;
+
+    Ok(())
+}
+
+fn parse_contexts(show_output: &str) -> Vec<TaskwarriorContext> {
+    let mut active_context = None;
+    let mut contexts: BTreeMap<String, TaskwarriorContext> = BTreeMap::new();
+
+    for (key, value) in show_output.lines().filter_map(|line| line.split_once('=')) {
+        if key == "context" {
+            active_context = Some(value.to_string()).filter(|name| !name.is_empty());
+            continue;
+        }
+        let definition = match key.strip_prefix("context.") {
+            Some(definition) => definition,
+            None => continue,
+        };
+        let (name, kind) = match definition.rsplit_once('.') {
+            Some((name, kind)) if kind == "read" || kind == "write" => (name, Some(kind)),
+            _ => (definition, None),
+        };
+        let context = contexts
+            .entry(name.to_string())
+            .or_insert_with(|| TaskwarriorContext {
+                name: name.to_string(),
+                read_filter: "".to_string(),
+                write_filter: None,
+                active: false,
+            });
+        match kind {
+            Some("read") => context.read_filter = value.to_string(),
+            Some(_) => context.write_filter = Some(value.to_string()).filter(|f| !f.is_empty()),
+            // Taskwarrior < 2.6 uses the same filter for reading and writing
+            None => {
+                context.read_filter = value.to_string();
+                context.write_filter = Some(value.to_string()).filter(|f| !f.is_empty());
+            }
+        }
+    }
+
+    contexts
+        .into_values()
+        .map(|context| TaskwarriorContext {
+            active: active_context.as_ref() == Some(&context.name),
+            ..context
+        })
+        .collect()
+}
+
 // Types
 // TaskwarriorTask
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
@@ -164,6 +361,42 @@ impl From<TaskId> for TaskwarriorTaskId {
     }
 }
 
+// Taskwarrior 2.6 exports dependencies as an array, older versions as a comma
+// separated list
+mod tw_depends_format {
+    use super::TaskwarriorTaskId;
+    use serde::{self, Deserialize, Deserializer};
+    use uuid::Uuid;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Depends {
+        List(Vec<TaskwarriorTaskId>),
+        Joined(String),
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<TaskwarriorTaskId>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<Depends>::deserialize(deserializer)? {
+            None => Ok(None),
+            Some(Depends::List(ids)) => Ok(Some(ids)),
+            Some(Depends::Joined(ids)) => ids
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(|id| {
+                    id.parse::<Uuid>()
+                        .map(TaskwarriorTaskId)
+                        .map_err(serde::de::Error::custom)
+                })
+                .collect::<Result<Vec<TaskwarriorTaskId>, D::Error>>()
+                .map(Some),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct TaskwarriorTask {
     pub uuid: TaskwarriorTaskId,
@@ -201,6 +434,12 @@ pub struct TaskwarriorTask {
     pub wait: Option<DateTime<Utc>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub parent: Option<TaskwarriorTaskId>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "tw_depends_format::deserialize"
+    )]
+    pub depends: Option<Vec<TaskwarriorTaskId>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub project: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -221,19 +460,25 @@ impl From<TaskwarriorTask> for Task {
 
 impl From<&TaskwarriorTask> for Task {
     fn from(task: &TaskwarriorTask) -> Self {
-        let cs_data =
-            task.contextswitch
-                .as_ref()
-                .and_then(|cs_string| -> Option<ContextswitchData> {
-                    let contextswitch_data_result = serde_json::from_str(cs_string);
-                    if contextswitch_data_result.is_err() {
-                        warn!(
-                            "Invalid Contextswitch data found in {}: {}",
-                            &task.uuid, cs_string
-                        );
-                    }
-                    contextswitch_data_result.ok()
-                });
+        let cs_string = task
+            .contextswitch
+            .as_ref()
+            .filter(|cs_string| !cs_string.trim().is_empty());
+        // Unreadable data is kept as is, so that it is not overwritten with nothing
+        let (cs_data, invalid_cs_data) =
+            match cs_string.map(|cs_string| migrations::from_json(cs_string)) {
+                Some(Ok(migrated)) => (Some(migrated.data), None),
+                Some(Err(e)) => {
+                    warn!(
+                        "Invalid Contextswitch data found in {}: {}: {}",
+                        &task.uuid,
+                        e,
+                        task.contextswitch.as_deref().unwrap_or_default()
+                    );
+                    (None, cs_string.cloned())
+                }
+                None => (None, None),
+            };
 
         Task {
             id: task.uuid.clone().into(),
@@ -252,6 +497,38 @@ impl From<&TaskwarriorTask> for Task {
             recur: task.recur,
             tags: task.tags.clone(),
             contextswitch: cs_data,
+            invalid_contextswitch: invalid_cs_data,
+        }
+    }
+}
+
+// TaskwarriorContext
+#[derive(Debug, PartialEq, Clone, Eq)]
+pub struct TaskwarriorContext {
+    pub name: String,
+    pub read_filter: String,
+    pub write_filter: Option<String>,
+    pub active: bool,
+}
+
+impl From<TaskwarriorContext> for contextswitch::Context {
+    fn from(context: TaskwarriorContext) -> Self {
+        contextswitch::Context {
+            name: context.name,
+            read_filter: context.read_filter,
+            write_filter: context.write_filter,
+            active: context.active,
+        }
+    }
+}
+
+impl From<contextswitch::NewContext> for TaskwarriorContext {
+    fn from(context: contextswitch::NewContext) -> Self {
+        TaskwarriorContext {
+            name: context.name,
+            read_filter: context.read_filter,
+            write_filter: context.write_filter.filter(|f| !f.is_empty()),
+            active: false,
         }
     }
 }
@@ -303,6 +580,13 @@ impl TryFrom<&Task> for TaskwarriorAction {
                     .collect::<Vec<String>>()
             })
             .unwrap_or_else(Vec::new);
+        // Unreadable data is left untouched unless new data replaces it
+        let contextswitch_arg = match (&task.contextswitch, &task.invalid_contextswitch) {
+            (None, Some(_)) => None,
+            _ => format_json(&task.contextswitch)?
+                .or_else(|| Some("".to_string()))
+                .map(to_arg("contextswitch")),
+        };
         let opt_args = [
             task.due
                 .map(format_date)
@@ -337,9 +621,7 @@ impl TryFrom<&Task> for TaskwarriorAction {
                 .map(|recur| recur.to_string())
                 .or_else(|| Some("".to_string()))
                 .map(to_arg("recur")),
-            format_json(&task.contextswitch)?
-                .or_else(|| Some("".to_string()))
-                .map(to_arg("contextswitch")),
+            contextswitch_arg,
         ];
 
         Ok(TaskwarriorAction {
@@ -368,6 +650,12 @@ pub enum TaskwarriorError {
         source: serde_json::Error,
         output: String,
     },
+    #[error("Error after updating {} tasks", updated.len())]
+    PartialUpdateError {
+        updated: Vec<TaskwarriorTask>,
+        #[source]
+        source: Box<TaskwarriorError>,
+    },
     #[error(transparent)]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -431,11 +719,49 @@ mod tests {
 
     mod from_taskwarrior_task_to_contextswitch_task {
         use super::super::*;
+        use crate::contextswitch::test_helpers::tw_task;
         use chrono::TimeZone;
-        use contextswitch::Bookmark;
+        use contextswitch::migrations::{DataVersion, CURRENT_VERSION};
+        use contextswitch::{Bookmark, ContextswitchData};
         use http::uri::Uri;
         use proptest::prelude::*;
 
+        #[test]
+        fn test_conversion_migrates_unversioned_data() {
+            let cs_task: Task = (&tw_task("{}")).into();
+
+            assert_eq!(
+                Some(ContextswitchData {
+                    version: DataVersion(CURRENT_VERSION),
+                    ..Default::default()
+                }),
+                cs_task.contextswitch
+            );
+            assert_eq!(None, cs_task.invalid_contextswitch);
+        }
+
+        #[test]
+        fn test_conversion_keeps_unreadable_data() {
+            let unsupported_version = r#"{"version": 99, "bookmarks": []}"#;
+            let cs_task: Task = (&tw_task(unsupported_version)).into();
+            assert_eq!(None, cs_task.contextswitch);
+            assert_eq!(
+                Some(unsupported_version.to_string()),
+                cs_task.invalid_contextswitch
+            );
+
+            let cs_task: Task = (&tw_task(r#"{"bookmarks": 42}"#)).into();
+            assert_eq!(None, cs_task.contextswitch);
+            assert_eq!(
+                Some(r#"{"bookmarks": 42}"#.to_string()),
+                cs_task.invalid_contextswitch
+            );
+
+            let cs_task: Task = (&tw_task("{not json")).into();
+            assert_eq!(None, cs_task.contextswitch);
+            assert_eq!(Some("{not json".to_string()), cs_task.invalid_contextswitch);
+        }
+
         #[test]
         fn test_successful_full_convertion() {
             let tw_task = TaskwarriorTask {
@@ -451,6 +777,7 @@ mod tests {
                 end: Some(Utc.ymd(2022, 1, 1).and_hms(1, 0, 4)),
                 wait: Some(Utc.ymd(2022, 1, 1).and_hms(1, 0, 5)),
                 parent: Some(TaskwarriorTaskId(Uuid::new_v4())),
+                depends: Some(vec![TaskwarriorTaskId(Uuid::new_v4())]),
                 project: Some("simple project".to_string()),
                 priority: Some(contextswitch::Priority::H),
                 recur: Some(contextswitch::Recurrence::Daily),
@@ -483,8 +810,10 @@ mod tests {
                 Some(ContextswitchData {
                     bookmarks: vec![Bookmark {
                         uri: "https://www.example.com/path".parse::<Uri>().unwrap(),
-                        content: None
-                    }]
+                        content: None,
+                        ..Default::default()
+                    }],
+                    ..Default::default()
                 }),
                 cs_task.contextswitch
             );
@@ -506,6 +835,7 @@ mod tests {
                     end: None,
                     wait: None,
                     parent: None,
+                    depends: None,
                     project: None,
                     priority: None,
                     recur: None,
@@ -527,8 +857,9 @@ mod tests {
 
     mod from_contextswitch_task_to_taskwarrior_action {
         use super::super::*;
+        use crate::contextswitch::test_helpers;
         use chrono::TimeZone;
-        use contextswitch::{Bookmark, Priority, Recurrence};
+        use contextswitch::{Bookmark, ContextswitchData, Priority, Recurrence};
         use http::Uri;
 
         #[test]
@@ -550,6 +881,7 @@ mod tests {
                 recur: None,
                 tags: None,
                 contextswitch: None,
+                invalid_contextswitch: None,
             };
             let action: TaskwarriorAction = (&task)
                 .try_into()
@@ -573,6 +905,23 @@ mod tests {
             );
         }
 
+        #[test]
+        fn test_convertion_leaves_unreadable_data_untouched() {
+            let task = Task {
+                status: contextswitch::Status::Completed,
+                invalid_contextswitch: Some("{not json".to_string()),
+                ..test_helpers::task("simple task")
+            };
+            let action: TaskwarriorAction = (&task)
+                .try_into()
+                .expect("Failed to convert Task into TaskwarriorAction");
+
+            assert!(!action
+                .args
+                .iter()
+                .any(|arg| arg.starts_with("contextswitch:")));
+        }
+
         #[test]
         fn test_successful_full_convertion() {
             let task = Task {
@@ -591,17 +940,21 @@ mod tests {
                 priority: Some(Priority::H),
                 recur: Some(Recurrence::Monthly),
                 tags: Some(vec!["tag1".to_string(), "tag2".to_string()]),
+                invalid_contextswitch: None,
                 contextswitch: Some(ContextswitchData {
                     bookmarks: vec![
                         Bookmark {
                             uri: "https://www.example.com/path".parse::<Uri>().unwrap(),
                             content: None,
+                            ..Default::default()
                         },
                         Bookmark {
                             uri: "https://www.example.com/path2".parse::<Uri>().unwrap(),
                             content: None,
+                            ..Default::default()
                         },
                     ],
+                    ..Default::default()
                 }),
             };
             let action: TaskwarriorAction = (&task)
@@ -623,11 +976,90 @@ mod tests {
                     "priority:H".to_string(),
                     "recur:monthly".to_string(),
                     String::from(
-                        r#"contextswitch:{"bookmarks":[{"uri":"https://www.example.com/path"},{"uri":"https://www.example.com/path2"}]}"#
+                        r#"contextswitch:{"version":1,"bookmarks":[{"uri":"https://www.example.com/path"},{"uri":"https://www.example.com/path2"}]}"#
                     )
                 ],
                 action.args
             );
         }
     }
+
+    mod parse_contexts {
+        use super::super::*;
+
+        #[test]
+        fn test_parse_contexts() {
+            let show_output = "color=on\n\
+                               context=work\n\
+                               context.home.read=project:Home\n\
+                               context.home.write=project:Home\n\
+                               context.work.read=+work or +meeting\n\
+                               data.location=/tmp\n";
+
+            assert_eq!(
+                vec![
+                    TaskwarriorContext {
+                        name: "home".to_string(),
+                        read_filter: "project:Home".to_string(),
+                        write_filter: Some("project:Home".to_string()),
+                        active: false,
+                    },
+                    TaskwarriorContext {
+                        name: "work".to_string(),
+                        read_filter: "+work or +meeting".to_string(),
+                        write_filter: None,
+                        active: true,
+                    }
+                ],
+                parse_contexts(show_output)
+            );
+        }
+
+        #[test]
+        fn test_parse_legacy_contexts() {
+            let show_output = "context=\ncontext.home=project:Home\n";
+
+            assert_eq!(
+                vec![TaskwarriorContext {
+                    name: "home".to_string(),
+                    read_filter: "project:Home".to_string(),
+                    write_filter: Some("project:Home".to_string()),
+                    active: false,
+                }],
+                parse_contexts(show_output)
+            );
+        }
+    }
+
+    mod parse_depends {
+        use super::super::*;
+
+        fn export(depends: &str) -> String {
+            format!(
+                r#"{{"id":1,"uuid":"{}","entry":"20220101T010000Z","modified":"20220101T010000Z","status":"pending","description":"simple task","urgency":0,"depends":{}}}"#,
+                Uuid::new_v4(),
+                depends
+            )
+        }
+
+        #[test]
+        fn test_parse_depends() {
+            let dependencies = [Uuid::new_v4(), Uuid::new_v4()];
+            let expected = Some(
+                dependencies
+                    .iter()
+                    .cloned()
+                    .map(TaskwarriorTaskId)
+                    .collect::<Vec<TaskwarriorTaskId>>(),
+            );
+
+            let list = format!(r#"["{}","{}"]"#, dependencies[0], dependencies[1]);
+            let tw_task: TaskwarriorTask = serde_json::from_str(&export(&list)).unwrap();
+            assert_eq!(expected, tw_task.depends);
+
+            let joined = format!(r#""{},{}""#, dependencies[0], dependencies[1]);
+            let tw_task: TaskwarriorTask = serde_json::from_str(&export(&joined)).unwrap();
+            assert_eq!(expected, tw_task.depends);
+        }
+    }
 }
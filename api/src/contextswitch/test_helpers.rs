@@ -0,0 +1,51 @@
+use super::taskwarrior::{TaskwarriorTask, TaskwarriorTaskId, TaskwarriorTaskLocalId};
+use chrono::{TimeZone, Utc};
+use contextswitch::{Status, Task, TaskId};
+use uuid::Uuid;
+
+// Pending task created on 2022-01-01, tests only set the fields they are about
+pub fn task(description: &str) -> Task {
+    Task {
+        id: TaskId(Uuid::new_v4()),
+        entry: Utc.ymd(2022, 1, 1).and_hms(1, 0, 0),
+        modified: Utc.ymd(2022, 1, 1).and_hms(1, 0, 1),
+        status: Status::Pending,
+        description: description.to_string(),
+        urgency: 0.0,
+        due: None,
+        start: None,
+        end: None,
+        wait: None,
+        parent: None,
+        project: None,
+        priority: None,
+        recur: None,
+        tags: None,
+        contextswitch: None,
+        invalid_contextswitch: None,
+    }
+}
+
+// Same task as exported by Taskwarrior, with raw Contextswitch data
+pub fn tw_task(contextswitch: &str) -> TaskwarriorTask {
+    TaskwarriorTask {
+        uuid: TaskwarriorTaskId(Uuid::new_v4()),
+        id: TaskwarriorTaskLocalId(42),
+        entry: Utc.ymd(2022, 1, 1).and_hms(1, 0, 0),
+        modified: Utc.ymd(2022, 1, 1).and_hms(1, 0, 1),
+        status: Status::Pending,
+        description: "simple task".to_string(),
+        urgency: 0.5,
+        due: None,
+        start: None,
+        end: None,
+        wait: None,
+        parent: None,
+        depends: None,
+        project: None,
+        priority: None,
+        recur: None,
+        tags: None,
+        contextswitch: Some(contextswitch.to_string()),
+    }
+}
@@ -0,0 +1,273 @@
+use super::{list_context_tasks, ContextswitchError};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use contextswitch::{Status, Task, TimeEntry, TimeGroupBy, TimeReport, WorkSession};
+use std::collections::BTreeMap;
+
+const DEFAULT_REPORT_DAYS: i64 = 7;
+
+// Sessions are identified by their start, so that a session already recorded
+// by the caller is not recorded twice
+pub fn record_session(
+    task: Task,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    note: Option<String>,
+) -> Task {
+    let mut contextswitch = task.contextswitch.clone().unwrap_or_default();
+    if contextswitch
+        .sessions
+        .iter()
+        .any(|session| session.start == start)
+    {
+        return task;
+    }
+    contextswitch
+        .sessions
+        .push(WorkSession { start, end, note });
+
+    Task {
+        contextswitch: Some(contextswitch),
+        ..task
+    }
+}
+
+// A session ends when a started task is stopped, restarted or closed
+pub fn close_session(previous_task: &Task, task: Task, now: DateTime<Utc>) -> Task {
+    match previous_task.start {
+        Some(start)
+            if previous_task.status == Status::Pending
+                && (task.start != Some(start) || task.status != Status::Pending) =>
+        {
+            record_session(task, start, now, None)
+        }
+        _ => task,
+    }
+}
+
+fn task_sessions(task: &Task, now: DateTime<Utc>) -> Vec<WorkSession> {
+    let mut sessions = task
+        .contextswitch
+        .as_ref()
+        .map(|contextswitch| contextswitch.sessions.clone())
+        .unwrap_or_default();
+    // The running session counts up to now
+    if let (Status::Pending, Some(start)) = (&task.status, task.start) {
+        if !sessions.iter().any(|session| session.start == start) {
+            sessions.push(WorkSession {
+                start,
+                end: now,
+                note: None,
+            });
+        }
+    }
+
+    sessions
+}
+
+fn group_keys(task: &Task, group_by: TimeGroupBy) -> Vec<String> {
+    match group_by {
+        TimeGroupBy::Task => vec![task.id.to_string()],
+        TimeGroupBy::Project => vec![task.project.clone().unwrap_or_else(|| "-".to_string())],
+        TimeGroupBy::Tag => match &task.tags {
+            Some(tags) if !tags.is_empty() => tags.clone(),
+            _ => vec!["-".to_string()],
+        },
+    }
+}
+
+pub fn compute_time_report(
+    tasks: &[Task],
+    from: NaiveDate,
+    to: NaiveDate,
+    group_by: TimeGroupBy,
+    now: DateTime<Utc>,
+) -> TimeReport {
+    let window_start = DateTime::<Utc>::from_utc(from.and_hms(0, 0, 0), Utc);
+    let window_end = DateTime::<Utc>::from_utc((to + Duration::days(1)).and_hms(0, 0, 0), Utc);
+    let mut entries: BTreeMap<String, TimeEntry> = BTreeMap::new();
+    let mut total_seconds = 0;
+    for task in tasks.iter().filter(|task| task.status != Status::Deleted) {
+        for session in task_sessions(task, now) {
+            let seconds =
+                (session.end.min(window_end) - session.start.max(window_start)).num_seconds();
+            if seconds <= 0 {
+                continue;
+            }
+            total_seconds += seconds;
+            for key in group_keys(task, group_by) {
+                let entry = entries
+                    .entry(key.clone())
+                    .or_insert_with(|| match group_by {
+                        TimeGroupBy::Task => TimeEntry {
+                            group: task.description.clone(),
+                            task_id: Some(task.id.clone()),
+                            seconds: 0,
+                            sessions: 0,
+                        },
+                        _ => TimeEntry {
+                            group: key,
+                            task_id: None,
+                            seconds: 0,
+                            sessions: 0,
+                        },
+                    });
+                entry.seconds += seconds;
+                entry.sessions += 1;
+            }
+        }
+    }
+
+    let mut entries: Vec<TimeEntry> = entries.into_values().collect();
+    entries.sort_by(|entry1, entry2| {
+        entry2
+            .seconds
+            .cmp(&entry1.seconds)
+            .then_with(|| entry1.group.cmp(&entry2.group))
+    });
+
+    TimeReport {
+        from,
+        to,
+        group_by,
+        entries,
+        total_seconds,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn time_report_csv(time_report: &TimeReport) -> String {
+    let mut csv = format!("{},task_id,seconds,hours,sessions\n", time_report.group_by);
+    for entry in time_report.entries.iter() {
+        csv.push_str(&format!(
+            "{},{},{},{:.2},{}\n",
+            csv_field(&entry.group),
+            entry
+                .task_id
+                .as_ref()
+                .map(|task_id| task_id.to_string())
+                .unwrap_or_default(),
+            entry.seconds,
+            entry.seconds as f64 / 3600.0,
+            entry.sessions
+        ));
+    }
+
+    csv
+}
+
+#[tracing::instrument(level = "debug")]
+pub fn get_time_report(
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    group_by: TimeGroupBy,
+) -> Result<TimeReport, ContextswitchError> {
+    let now = Utc::now();
+    let to = to.unwrap_or_else(|| now.naive_utc().date());
+    let from = from.unwrap_or_else(|| to - Duration::days(DEFAULT_REPORT_DAYS - 1));
+    if from > to {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Report start {} is after its end {}",
+            from, to
+        )));
+    }
+    let tasks = list_context_tasks(vec![])?;
+
+    Ok(compute_time_report(&tasks, from, to, group_by, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+    use chrono::TimeZone;
+    use contextswitch::ContextswitchData;
+
+    fn task(description: &str, tags: Vec<&str>, sessions: Vec<(u32, u32, u32)>) -> Task {
+        Task {
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            contextswitch: Some(ContextswitchData {
+                sessions: sessions
+                    .into_iter()
+                    .map(|(day, start_hour, end_hour)| WorkSession {
+                        start: Utc.ymd(2022, 1, day).and_hms(start_hour, 0, 0),
+                        end: Utc.ymd(2022, 1, day).and_hms(end_hour, 0, 0),
+                        note: None,
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+            ..test_helpers::task(description)
+        }
+    }
+
+    #[test]
+    fn test_close_session_when_task_is_stopped() {
+        let start = Utc.ymd(2022, 1, 3).and_hms(9, 0, 0);
+        let now = Utc.ymd(2022, 1, 3).and_hms(10, 30, 0);
+        let started_task = Task {
+            start: Some(start),
+            ..task("review", vec![], vec![])
+        };
+        let stopped_task = Task {
+            start: None,
+            ..started_task.clone()
+        };
+
+        let stopped_task = close_session(&started_task, stopped_task, now);
+        let sessions = stopped_task.contextswitch.unwrap().sessions;
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].duration(), Duration::minutes(90));
+
+        let unchanged_task = close_session(&started_task, started_task.clone(), now);
+        assert!(unchanged_task.contextswitch.unwrap().sessions.is_empty());
+    }
+
+    #[test]
+    fn test_compute_time_report_by_tag() {
+        let now = Utc.ymd(2022, 1, 10).and_hms(12, 0, 0);
+        let tasks = vec![
+            task("review", vec!["work"], vec![(3, 9, 11), (4, 9, 10)]),
+            task("garden", vec!["home"], vec![(3, 18, 19)]),
+            // Outside of the report window
+            task("taxes", vec!["home"], vec![(1, 9, 12)]),
+        ];
+
+        let time_report = compute_time_report(
+            &tasks,
+            NaiveDate::from_ymd(2022, 1, 3),
+            NaiveDate::from_ymd(2022, 1, 4),
+            TimeGroupBy::Tag,
+            now,
+        );
+
+        assert_eq!(time_report.total_seconds, 4 * 3600);
+        assert_eq!(
+            time_report.entries,
+            vec![
+                TimeEntry {
+                    group: "work".to_string(),
+                    task_id: None,
+                    seconds: 3 * 3600,
+                    sessions: 2,
+                },
+                TimeEntry {
+                    group: "home".to_string(),
+                    task_id: None,
+                    seconds: 3600,
+                    sessions: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            time_report_csv(&time_report),
+            "tag,task_id,seconds,hours,sessions\nwork,,10800,3.00,2\nhome,,3600,1.00,1\n"
+        );
+    }
+}
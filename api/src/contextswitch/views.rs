@@ -0,0 +1,331 @@
+use super::store::{store_path, JsonStore};
+use super::{list_context_tasks, ContextswitchError};
+use contextswitch::{Priority, Task, View};
+use std::cmp::Ordering;
+
+const VIEWS_FILE: &str = "views.json";
+
+pub type ViewStore = JsonStore<Vec<View>>;
+
+pub fn new_view_store(data_location: &str) -> ViewStore {
+    JsonStore::new(store_path(data_location, VIEWS_FILE))
+}
+
+fn builtin_view(name: &str, filter: &str, sort: &[&str], columns: &[&str]) -> View {
+    View {
+        name: name.to_string(),
+        filter: filter.to_string(),
+        sort: sort.iter().map(|s| s.to_string()).collect(),
+        columns: columns.iter().map(|c| c.to_string()).collect(),
+        group_by: None,
+        builtin: true,
+    }
+}
+
+// Mirror Taskwarrior default reports
+pub fn builtin_views() -> Vec<View> {
+    vec![
+        builtin_view(
+            "next",
+            "status:pending -WAITING",
+            &["urgency-"],
+            &["project", "tags", "due", "description", "urgency"],
+        ),
+        builtin_view(
+            "waiting",
+            "+WAITING",
+            &["due+", "wait+", "entry+"],
+            &["project", "tags", "wait", "due", "description"],
+        ),
+        builtin_view(
+            "overdue",
+            "status:pending +OVERDUE",
+            &["urgency-", "due+"],
+            &["project", "tags", "due", "description", "urgency"],
+        ),
+        builtin_view(
+            "recurring",
+            "( status:pending and +CHILD ) or status:recurring",
+            &["recur+", "due+", "urgency-"],
+            &["project", "tags", "recur", "due", "description"],
+        ),
+        builtin_view(
+            "completed",
+            "status:completed",
+            &["end+"],
+            &["project", "tags", "end", "description"],
+        ),
+    ]
+}
+
+// Task fields the web frontend knows how to display or group by
+const VIEW_COLUMNS: [&str; 13] = [
+    "description",
+    "status",
+    "urgency",
+    "project",
+    "priority",
+    "recur",
+    "tags",
+    "entry",
+    "modified",
+    "due",
+    "start",
+    "end",
+    "wait",
+];
+
+fn is_valid_view_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub fn list_views(store: &ViewStore) -> Result<Vec<View>, ContextswitchError> {
+    let saved_views = store.load()?;
+
+    Ok([builtin_views(), saved_views].concat())
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub fn get_view(store: &ViewStore, name: &str) -> Result<View, ContextswitchError> {
+    list_views(store)?
+        .into_iter()
+        .find(|view| view.name == name)
+        .ok_or_else(|| ContextswitchError::NotFoundError(format!("View {}", name)))
+}
+
+#[tracing::instrument(level = "debug", skip(store, view), fields(name = %view.name))]
+pub async fn save_view(store: &ViewStore, view: View) -> Result<View, ContextswitchError> {
+    if !is_valid_view_name(&view.name) {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Invalid view name '{}'",
+            view.name
+        )));
+    }
+    if builtin_views()
+        .iter()
+        .any(|builtin| builtin.name == view.name)
+    {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Built-in view '{}' cannot be modified",
+            view.name
+        )));
+    }
+    if let Some(invalid_sort) = view.sort.iter().find(|sort| parse_sort(sort).is_none()) {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Invalid sort '{}'",
+            invalid_sort
+        )));
+    }
+
+    if let Some(invalid_column) = view
+        .columns
+        .iter()
+        .chain(view.group_by.iter())
+        .find(|column| !VIEW_COLUMNS.contains(&column.as_str()))
+    {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Invalid column '{}'",
+            invalid_column
+        )));
+    }
+
+    let view = View {
+        builtin: false,
+        ..view
+    };
+    store
+        .update(|views| {
+            match views.iter_mut().find(|saved| saved.name == view.name) {
+                Some(saved) => *saved = view.clone(),
+                None => views.push(view.clone()),
+            }
+            Ok(view)
+        })
+        .await
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub async fn delete_view(store: &ViewStore, name: &str) -> Result<(), ContextswitchError> {
+    if builtin_views().iter().any(|builtin| builtin.name == name) {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Built-in view '{}' cannot be deleted",
+            name
+        )));
+    }
+
+    store
+        .update(|views| {
+            let views_count = views.len();
+            views.retain(|view| view.name != name);
+            if views.len() == views_count {
+                return Err(ContextswitchError::NotFoundError(format!("View {}", name)));
+            }
+            Ok(())
+        })
+        .await
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub fn list_view_tasks(store: &ViewStore, name: &str) -> Result<Vec<Task>, ContextswitchError> {
+    let view = get_view(store, name)?;
+    let mut tasks = list_context_tasks(view.filter.split_whitespace().collect())?;
+    sort_tasks(&mut tasks, &view.sort);
+
+    Ok(tasks)
+}
+
+fn parse_sort(sort: &str) -> Option<(&str, bool)> {
+    let (field, ascending) = match sort.strip_suffix('-') {
+        Some(field) => (field, false),
+        None => (sort.strip_suffix('+').unwrap_or(sort), true),
+    };
+    match field {
+        "urgency" | "due" | "entry" | "modified" | "start" | "end" | "wait" | "description"
+        | "project" | "priority" | "status" | "recur" => Some((field, ascending)),
+        _ => None,
+    }
+}
+
+fn priority_rank(priority: &Priority) -> u8 {
+    match priority {
+        Priority::H => 3,
+        Priority::M => 2,
+        Priority::L => 1,
+    }
+}
+
+// Tasks without a value for the sorted field always come last
+fn compare_options<T: PartialOrd>(a: Option<T>, b: Option<T>, ascending: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn compare_tasks(a: &Task, b: &Task, field: &str, ascending: bool) -> Ordering {
+    match field {
+        "urgency" => compare_options(Some(a.urgency), Some(b.urgency), ascending),
+        "due" => compare_options(a.due, b.due, ascending),
+        "entry" => compare_options(Some(a.entry), Some(b.entry), ascending),
+        "modified" => compare_options(Some(a.modified), Some(b.modified), ascending),
+        "start" => compare_options(a.start, b.start, ascending),
+        "end" => compare_options(a.end, b.end, ascending),
+        "wait" => compare_options(a.wait, b.wait, ascending),
+        "description" => compare_options(Some(&a.description), Some(&b.description), ascending),
+        "project" => compare_options(a.project.as_ref(), b.project.as_ref(), ascending),
+        "priority" => compare_options(
+            a.priority.as_ref().map(priority_rank),
+            b.priority.as_ref().map(priority_rank),
+            ascending,
+        ),
+        "status" => compare_options(
+            Some(a.status.to_string()),
+            Some(b.status.to_string()),
+            ascending,
+        ),
+        "recur" => compare_options(
+            a.recur.map(|recur| recur.to_string()),
+            b.recur.map(|recur| recur.to_string()),
+            ascending,
+        ),
+        _ => Ordering::Equal,
+    }
+}
+
+pub fn sort_tasks(tasks: &mut [Task], sort: &[String]) {
+    let sort_keys: Vec<(&str, bool)> = sort.iter().filter_map(|s| parse_sort(s)).collect();
+    tasks.sort_by(|a, b| {
+        sort_keys
+            .iter()
+            .map(|(field, ascending)| compare_tasks(a, b, field, *ascending))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+    use chrono::{TimeZone, Utc};
+
+    fn task(description: &str, urgency: f64, due_day: Option<u32>) -> Task {
+        Task {
+            urgency,
+            due: due_day.map(|day| Utc.ymd(2022, 2, day).and_hms(0, 0, 0)),
+            ..test_helpers::task(description)
+        }
+    }
+
+    fn descriptions(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|task| task.description.as_str()).collect()
+    }
+
+    #[test]
+    fn test_sort_by_urgency_descending() {
+        let mut tasks = vec![
+            task("a", 1.0, None),
+            task("b", 3.0, None),
+            task("c", 2.0, None),
+        ];
+
+        sort_tasks(&mut tasks, &["urgency-".to_string()]);
+
+        assert_eq!(vec!["b", "c", "a"], descriptions(&tasks));
+    }
+
+    #[test]
+    fn test_sort_puts_missing_values_last() {
+        let mut tasks = vec![
+            task("a", 1.0, None),
+            task("b", 1.0, Some(2)),
+            task("c", 1.0, Some(1)),
+        ];
+
+        sort_tasks(&mut tasks, &["due+".to_string()]);
+        assert_eq!(vec!["c", "b", "a"], descriptions(&tasks));
+
+        sort_tasks(&mut tasks, &["due-".to_string()]);
+        assert_eq!(vec!["b", "c", "a"], descriptions(&tasks));
+    }
+
+    #[test]
+    fn test_sort_with_multiple_keys() {
+        let mut tasks = vec![
+            task("b", 1.0, Some(1)),
+            task("c", 2.0, Some(1)),
+            task("a", 1.0, Some(1)),
+        ];
+
+        sort_tasks(
+            &mut tasks,
+            &[
+                "due+".to_string(),
+                "urgency-".to_string(),
+                "description".to_string(),
+            ],
+        );
+
+        assert_eq!(vec!["c", "a", "b"], descriptions(&tasks));
+    }
+
+    #[test]
+    fn test_parse_invalid_sort() {
+        assert_eq!(None, parse_sort("unknown+"));
+        assert_eq!(Some(("due", true)), parse_sort("due"));
+        assert_eq!(Some(("due", false)), parse_sort("due-"));
+    }
+}
@@ -0,0 +1,222 @@
+use super::{events, taskwarrior};
+use contextswitch::{Status, Task, TaskEvent};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+
+type TasksSnapshot = BTreeMap<Uuid, Task>;
+
+// Taskwarrior 2 stores tasks in *.data files, TaskChampion in a SQLite database
+fn is_task_data_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("data") | Some("sqlite3")
+    )
+}
+
+fn load_snapshot() -> Result<TasksSnapshot, taskwarrior::TaskwarriorError> {
+    Ok(taskwarrior::list_tasks(vec![])?
+        .iter()
+        .map(|task| {
+            let task = Task::from(task);
+            (task.id.0, task)
+        })
+        .collect())
+}
+
+pub fn diff_tasks(snapshot: &TasksSnapshot, current: &TasksSnapshot) -> Vec<TaskEvent> {
+    let mut task_events: Vec<TaskEvent> = current
+        .iter()
+        .filter_map(|(uuid, task)| match snapshot.get(uuid) {
+            None => Some(TaskEvent::Created(task.clone())),
+            Some(known_task) if known_task.modified != task.modified => {
+                Some(if task.status == Status::Deleted {
+                    TaskEvent::Deleted(task.clone())
+                } else {
+                    TaskEvent::Updated(task.clone())
+                })
+            }
+            Some(_) => None,
+        })
+        .collect();
+    let current_uuids: BTreeSet<&Uuid> = current.keys().collect();
+    // Purged tasks are not exported anymore
+    task_events.extend(
+        snapshot
+            .iter()
+            .filter(|(uuid, _)| !current_uuids.contains(uuid))
+            .map(|(_, task)| TaskEvent::Deleted(task.clone())),
+    );
+
+    task_events
+}
+
+// Events already published by Contextswitch write paths (or by the watcher
+// itself) must not be published again when the data files change
+pub fn record_task_event(snapshot: &mut TasksSnapshot, task_event: TaskEvent) {
+    match task_event {
+        TaskEvent::Created(task)
+        | TaskEvent::Updated(task)
+        | TaskEvent::PomodoroCompleted(task) => {
+            snapshot.insert(task.id.0, task);
+        }
+        TaskEvent::Deleted(task) => {
+            if snapshot.contains_key(&task.id.0) {
+                snapshot.insert(task.id.0, task);
+            }
+        }
+    }
+}
+
+fn record_published_events(
+    snapshot: &mut TasksSnapshot,
+    task_events: &mut broadcast::Receiver<TaskEvent>,
+) {
+    loop {
+        match task_events.try_recv() {
+            Ok(task_event) => record_task_event(snapshot, task_event),
+            Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                warn!("Taskwarrior watcher missed {} task events", skipped);
+            }
+            Err(_) => break,
+        }
+    }
+}
+
+#[tracing::instrument(level = "debug")]
+pub fn spawn_watcher(data_location: &str) -> Result<(), notify::Error> {
+    let (changes_sender, mut changes) = mpsc::unbounded_channel();
+    let mut watcher =
+        notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+            Ok(event) => {
+                if !matches!(event.kind, EventKind::Access(_))
+                    && event.paths.iter().any(|path| is_task_data_file(path))
+                {
+                    let _ = changes_sender.send(());
+                }
+            }
+            Err(e) => warn!("Error while watching Taskwarrior data: {}", e),
+        })?;
+    watcher.watch(Path::new(data_location), RecursiveMode::NonRecursive)?;
+    info!("Watching Taskwarrior data in {}", data_location);
+
+    let mut task_events = events::subscribe();
+    let mut snapshot = load_snapshot().unwrap_or_else(|e| {
+        warn!("Cannot load Taskwarrior tasks: {}", e);
+        TasksSnapshot::new()
+    });
+
+    tokio::spawn(async move {
+        // The watcher stops when dropped
+        let _watcher = watcher;
+        while changes.recv().await.is_some() {
+            tokio::time::sleep(DEBOUNCE_DELAY).await;
+            while changes.try_recv().is_ok() {}
+
+            record_published_events(&mut snapshot, &mut task_events);
+            // Exporting tasks blocks, it must not stall the async workers
+            match tokio::task::spawn_blocking(load_snapshot).await {
+                Ok(Ok(current)) => {
+                    let task_events = diff_tasks(&snapshot, &current);
+                    debug!(
+                        "Found {} external changes in Taskwarrior data",
+                        task_events.len()
+                    );
+                    task_events.into_iter().for_each(events::publish);
+                    snapshot = current;
+                }
+                Ok(Err(e)) => warn!("Cannot load Taskwarrior tasks: {}", e),
+                Err(e) => warn!("Taskwarrior tasks loading was interrupted: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contextswitch::test_helpers;
+    use chrono::{TimeZone, Utc};
+
+    fn task(description: &str, status: Status, modified_second: u32) -> Task {
+        Task {
+            modified: Utc.ymd(2022, 1, 1).and_hms(1, 0, modified_second),
+            status,
+            ..test_helpers::task(description)
+        }
+    }
+
+    fn snapshot(tasks: &[&Task]) -> TasksSnapshot {
+        tasks
+            .iter()
+            .map(|task| (task.id.0, (*task).clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_is_task_data_file() {
+        assert!(is_task_data_file(Path::new("/tmp/pending.data")));
+        assert!(is_task_data_file(Path::new("/tmp/taskchampion.sqlite3")));
+        assert!(!is_task_data_file(Path::new(
+            "/tmp/contextswitch/views.json"
+        )));
+    }
+
+    #[test]
+    fn test_diff_tasks() {
+        let unchanged = task("unchanged", Status::Pending, 0);
+        let updated = task("updated", Status::Pending, 0);
+        let deleted = task("deleted", Status::Pending, 0);
+        let purged = task("purged", Status::Completed, 0);
+        let created = task("created", Status::Pending, 0);
+        let updated_now = Task {
+            modified: Utc.ymd(2022, 1, 1).and_hms(1, 0, 1),
+            ..updated.clone()
+        };
+        let deleted_now = Task {
+            status: Status::Deleted,
+            modified: Utc.ymd(2022, 1, 1).and_hms(1, 0, 1),
+            ..deleted.clone()
+        };
+
+        let task_events = diff_tasks(
+            &snapshot(&[&unchanged, &updated, &deleted, &purged]),
+            &snapshot(&[&unchanged, &updated_now, &deleted_now, &created]),
+        );
+
+        assert_eq!(task_events.len(), 4);
+        assert!(task_events.contains(&TaskEvent::Updated(updated_now)));
+        assert!(task_events.contains(&TaskEvent::Deleted(deleted_now)));
+        assert!(task_events.contains(&TaskEvent::Created(created)));
+        assert!(task_events.contains(&TaskEvent::Deleted(purged)));
+    }
+
+    #[test]
+    fn test_recorded_task_events_are_not_diffed() {
+        let created = task("created", Status::Pending, 0);
+        let deleted = Task {
+            status: Status::Deleted,
+            modified: Utc.ymd(2022, 1, 1).and_hms(1, 0, 1),
+            ..task("deleted", Status::Pending, 0)
+        };
+        let purged = task("purged", Status::Completed, 0);
+        let mut known_tasks = snapshot(&[&Task {
+            status: Status::Pending,
+            ..deleted.clone()
+        }]);
+
+        record_task_event(&mut known_tasks, TaskEvent::Created(created.clone()));
+        record_task_event(&mut known_tasks, TaskEvent::Deleted(deleted.clone()));
+        record_task_event(&mut known_tasks, TaskEvent::Deleted(purged));
+
+        assert!(diff_tasks(&known_tasks, &snapshot(&[&created, &deleted])).is_empty());
+    }
+}
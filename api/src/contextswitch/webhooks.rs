@@ -0,0 +1,378 @@
+use super::events;
+use super::store::{store_path, JsonStore};
+use super::ContextswitchError;
+use crate::configuration::{WebhookSettings, WebhooksSettings};
+use anyhow::Context;
+use chrono::Utc;
+use contextswitch::{
+    NewWebhook, TaskEvent, Webhook, WebhookDelivery, WebhookEvent, WebhookPayload,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+const WEBHOOKS_FILE: &str = "webhooks.json";
+const DELIVERIES_FILE: &str = "webhook_deliveries.json";
+const MAX_DELIVERIES: usize = 500;
+
+pub const EVENT_HEADER: &str = "X-Contextswitch-Event";
+pub const SIGNATURE_HEADER: &str = "X-Contextswitch-Signature";
+
+#[derive(Debug)]
+pub struct WebhookStore {
+    configured: Vec<WebhookSettings>,
+    subscriptions: JsonStore<Vec<WebhookSettings>>,
+    deliveries: JsonStore<Vec<WebhookDelivery>>,
+}
+
+pub fn new_webhook_store(data_location: &str, settings: &WebhooksSettings) -> WebhookStore {
+    WebhookStore {
+        configured: settings.subscriptions.clone(),
+        subscriptions: JsonStore::new(store_path(data_location, WEBHOOKS_FILE)),
+        deliveries: JsonStore::new(store_path(data_location, DELIVERIES_FILE)),
+    }
+}
+
+fn to_webhook(subscription: &WebhookSettings, configured: bool) -> Webhook {
+    Webhook {
+        name: subscription.name.clone(),
+        url: subscription.url.clone(),
+        events: subscription.events.clone(),
+        configured,
+    }
+}
+
+fn is_valid_webhook_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+fn subscriptions(store: &WebhookStore) -> Result<Vec<WebhookSettings>, ContextswitchError> {
+    Ok([store.configured.clone(), store.subscriptions.load()?].concat())
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub fn list_webhooks(store: &WebhookStore) -> Result<Vec<Webhook>, ContextswitchError> {
+    let saved_webhooks = store.subscriptions.load()?;
+
+    Ok(store
+        .configured
+        .iter()
+        .map(|subscription| to_webhook(subscription, true))
+        .chain(
+            saved_webhooks
+                .iter()
+                .map(|subscription| to_webhook(subscription, false)),
+        )
+        .collect())
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(name = %new_webhook.name))]
+pub async fn save_webhook(
+    store: &WebhookStore,
+    new_webhook: NewWebhook,
+) -> Result<Webhook, ContextswitchError> {
+    if !is_valid_webhook_name(&new_webhook.name) {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Invalid webhook name {}",
+            new_webhook.name
+        )));
+    }
+    if reqwest::Url::parse(&new_webhook.url)
+        .map(|url| url.scheme() != "http" && url.scheme() != "https")
+        .unwrap_or(true)
+    {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Invalid webhook URL {}",
+            new_webhook.url
+        )));
+    }
+    if store
+        .configured
+        .iter()
+        .any(|subscription| subscription.name == new_webhook.name)
+    {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Cannot override configured webhook {}",
+            new_webhook.name
+        )));
+    }
+
+    let subscription = WebhookSettings {
+        name: new_webhook.name,
+        url: new_webhook.url,
+        secret: new_webhook.secret,
+        events: new_webhook.events,
+    };
+    let webhook = to_webhook(&subscription, false);
+    store
+        .subscriptions
+        .update(|subscriptions| {
+            subscriptions.retain(|saved| saved.name != subscription.name);
+            subscriptions.push(subscription);
+            Ok(())
+        })
+        .await?;
+
+    Ok(webhook)
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub async fn delete_webhook(store: &WebhookStore, name: &str) -> Result<(), ContextswitchError> {
+    if store
+        .configured
+        .iter()
+        .any(|subscription| subscription.name == name)
+    {
+        return Err(ContextswitchError::InvalidInputError(format!(
+            "Cannot delete configured webhook {}",
+            name
+        )));
+    }
+
+    store
+        .subscriptions
+        .update(|subscriptions| {
+            let webhooks_count = subscriptions.len();
+            subscriptions.retain(|subscription| subscription.name != name);
+            if subscriptions.len() == webhooks_count {
+                return Err(ContextswitchError::NotFoundError(format!(
+                    "Webhook {}",
+                    name
+                )));
+            }
+            Ok(())
+        })
+        .await
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub fn list_deliveries(
+    store: &WebhookStore,
+    name: &str,
+) -> Result<Vec<WebhookDelivery>, ContextswitchError> {
+    if !subscriptions(store)?
+        .iter()
+        .any(|subscription| subscription.name == name)
+    {
+        return Err(ContextswitchError::NotFoundError(format!(
+            "Webhook {}",
+            name
+        )));
+    }
+
+    Ok(store
+        .deliveries
+        .load()?
+        .into_iter()
+        .filter(|delivery| delivery.webhook == name)
+        .collect())
+}
+
+async fn record_delivery(
+    store: &WebhookStore,
+    delivery: WebhookDelivery,
+) -> Result<(), ContextswitchError> {
+    store
+        .deliveries
+        .update(|deliveries| {
+            deliveries.push(delivery);
+            if deliveries.len() > MAX_DELIVERIES {
+                deliveries.drain(..deliveries.len() - MAX_DELIVERIES);
+            }
+            Ok(())
+        })
+        .await
+}
+
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn is_subscribed(subscription: &WebhookSettings, event: WebhookEvent) -> bool {
+    subscription.events.is_empty() || subscription.events.contains(&event)
+}
+
+#[derive(Debug, Clone)]
+struct RetryPolicy {
+    max_attempts: u32,
+    retry_delay: Duration,
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(webhook = %subscription.name, event = %payload.event))]
+async fn deliver(
+    client: &reqwest::Client,
+    subscription: &WebhookSettings,
+    payload: &WebhookPayload,
+    retry_policy: &RetryPolicy,
+) -> Result<WebhookDelivery, ContextswitchError> {
+    let body = serde_json::to_vec(payload).context("Cannot serialize webhook payload")?;
+    let signature = sign(&subscription.secret, &body);
+    let mut delivery = WebhookDelivery {
+        webhook: subscription.name.clone(),
+        event: payload.event,
+        task_id: payload.task.id.clone(),
+        timestamp: payload.timestamp,
+        attempts: 0,
+        delivered: false,
+        status_code: None,
+        error: None,
+    };
+    let mut retry_delay = retry_policy.retry_delay;
+
+    while delivery.attempts < retry_policy.max_attempts.max(1) {
+        if delivery.attempts > 0 {
+            tokio::time::sleep(retry_delay).await;
+            retry_delay *= 2;
+        }
+        delivery.attempts += 1;
+
+        let response = client
+            .post(&subscription.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, payload.event.to_string())
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.clone())
+            .send()
+            .await;
+        match response {
+            Ok(response) => {
+                delivery.status_code = Some(response.status().as_u16());
+                if response.status().is_success() {
+                    delivery.delivered = true;
+                    delivery.error = None;
+                    break;
+                }
+                delivery.error = Some(format!("Unexpected status {}", response.status()));
+            }
+            Err(e) => {
+                delivery.status_code = None;
+                delivery.error = Some(e.to_string());
+            }
+        }
+        debug!(
+            "Webhook delivery attempt {} failed: {:?}",
+            delivery.attempts, delivery.error
+        );
+    }
+
+    Ok(delivery)
+}
+
+async fn dispatch(
+    store: Arc<WebhookStore>,
+    retry_policy: RetryPolicy,
+    mut task_events: broadcast::Receiver<TaskEvent>,
+) {
+    let client = reqwest::Client::new();
+    loop {
+        let task_event = match task_events.recv().await {
+            Ok(task_event) => task_event,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Webhooks dispatcher missed {} task events", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let event = WebhookEvent::from(&task_event);
+        let subscriptions = match subscriptions(&store) {
+            Ok(subscriptions) => subscriptions,
+            Err(e) => {
+                warn!("Cannot load webhooks: {}", e);
+                continue;
+            }
+        };
+        let payload = WebhookPayload {
+            event,
+            timestamp: Utc::now(),
+            task: task_event.task().clone(),
+        };
+
+        for subscription in subscriptions
+            .into_iter()
+            .filter(|subscription| is_subscribed(subscription, event))
+        {
+            let store = store.clone();
+            let client = client.clone();
+            let payload = payload.clone();
+            let retry_policy = retry_policy.clone();
+            // Retries must not hold back deliveries of the following events
+            tokio::spawn(async move {
+                let result = match deliver(&client, &subscription, &payload, &retry_policy).await {
+                    Ok(delivery) => record_delivery(&store, delivery).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    warn!("Cannot deliver webhook {}: {}", subscription.name, e);
+                }
+            });
+        }
+    }
+}
+
+// Deliveries run on their own thread so that they do not depend on the
+// lifetime of the runtime which started the server
+pub fn spawn_dispatcher(
+    store: Arc<WebhookStore>,
+    settings: &WebhooksSettings,
+) -> Result<(), std::io::Error> {
+    let retry_policy = RetryPolicy {
+        max_attempts: settings.max_attempts,
+        retry_delay: Duration::from_millis(settings.retry_delay_ms),
+    };
+    let task_events = events::subscribe();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    std::thread::Builder::new()
+        .name("webhooks".to_string())
+        .spawn(move || runtime.block_on(dispatch(store, retry_policy, task_events)))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(events: Vec<WebhookEvent>) -> WebhookSettings {
+        WebhookSettings {
+            name: "test".to_string(),
+            url: "http://localhost/hook".to_string(),
+            secret: "secret".to_string(),
+            events,
+        }
+    }
+
+    #[test]
+    fn test_sign() {
+        // echo -n '{"event":"created"}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(
+            sign("secret", br#"{"event":"created"}"#),
+            "sha256=74e1c80b3590da2bf380221609511ac0d4c3401a4aaa4c14dae3f899ee6f9bd0"
+        );
+    }
+
+    #[test]
+    fn test_is_subscribed() {
+        assert!(is_subscribed(&subscription(vec![]), WebhookEvent::Deleted));
+        assert!(is_subscribed(
+            &subscription(vec![WebhookEvent::Completed]),
+            WebhookEvent::Completed
+        ));
+        assert!(!is_subscribed(
+            &subscription(vec![WebhookEvent::Completed]),
+            WebhookEvent::Updated
+        ));
+    }
+}
@@ -0,0 +1,48 @@
+use actix_web::http::header::{HeaderMap, HeaderValue, ORIGIN};
+
+// Patterns ending with * match any origin starting with the rest of the pattern
+fn is_allowed_origin(origin: &str, allowed_origins: &[String]) -> bool {
+    allowed_origins
+        .iter()
+        .any(|allowed_origin| match allowed_origin.strip_suffix('*') {
+            Some(prefix) => origin.starts_with(prefix),
+            None => origin == allowed_origin,
+        })
+}
+
+// The origin to allow when it differs from the front base URL
+pub fn allowed_origin(headers: &HeaderMap, allowed_origins: &[String]) -> Option<HeaderValue> {
+    let origin = headers.get(ORIGIN)?;
+    if is_allowed_origin(origin.to_str().ok()?, allowed_origins) {
+        Some(origin.clone())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_allowed_origin() {
+        let allowed_origins = vec![
+            "moz-extension://*".to_string(),
+            "http://localhost:3000".to_string(),
+        ];
+
+        assert!(is_allowed_origin(
+            "moz-extension://0f6b4d2c-4a1b-4c3e-8f7a-2b9d1e5c6a7f",
+            &allowed_origins
+        ));
+        assert!(is_allowed_origin("http://localhost:3000", &allowed_origins));
+        assert!(!is_allowed_origin(
+            "http://localhost:3001",
+            &allowed_origins
+        ));
+        assert!(!is_allowed_origin(
+            "chrome-extension://abcdef",
+            &allowed_origins
+        ));
+    }
+}
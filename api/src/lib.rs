@@ -1,28 +1,76 @@
+use crate::contextswitch::{
+    attachments, bookmarks, github, index, notes, pomodoro, projects, providers, rules, slack,
+    task_notes, views, watcher, webhooks,
+};
 use actix_files as fs;
-use actix_web::{dev::Server, http, middleware, web, App, HttpServer};
+use actix_web::dev::{Server, Service};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::{http, middleware, web, App, HttpServer};
 use configuration::Settings;
 use core::time::Duration;
 use std::net::TcpListener;
-use tracing::info;
+use std::sync::Arc;
+use tracing::{info, warn};
 use tracing_actix_web::TracingLogger;
 
 #[macro_use]
 extern crate lazy_static;
 
+pub mod commands;
 pub mod configuration;
 pub mod contextswitch;
+mod cors;
 pub mod observability;
 pub mod routes;
 
 pub fn run(listener: TcpListener, settings: &Settings) -> Result<Server, std::io::Error> {
     let api_path = settings.application.api_path.clone();
     let front_base_url = settings.application.front_base_url.clone();
+    let allowed_origins = settings.application.allowed_origins.clone();
     let static_path = settings.application.static_path.clone();
     let static_dir = settings
         .application
         .static_dir
         .clone()
         .unwrap_or_else(|| ".".to_string());
+    let data_location = settings
+        .taskwarrior
+        .data_location
+        .clone()
+        .expect("Expecting taskwarrior.data_location setting to be set");
+    let view_store = web::Data::new(views::new_view_store(&data_location));
+    let project_store = web::Data::new(projects::new_project_store(&data_location));
+    let note_store = web::Data::new(task_notes::new_note_store(&data_location));
+    let attachment_store = web::Data::new(attachments::new_attachment_store(
+        &data_location,
+        &settings.attachments,
+    ));
+    let webhook_store = Arc::new(webhooks::new_webhook_store(
+        &data_location,
+        &settings.webhooks,
+    ));
+    webhooks::spawn_dispatcher(webhook_store.clone(), &settings.webhooks)?;
+    let webhook_store = web::Data::from(webhook_store);
+    let github_client = Arc::new(github::new_github_client(&settings.github));
+    let slack_client = Arc::new(slack::new_slack_client(&settings.slack));
+    let notebook = Arc::new(notes::new_notebook(&settings.notes)?);
+    let provider_registry = web::Data::new(providers::new_provider_registry(
+        &settings.providers.enabled,
+        vec![github_client, slack_client.clone(), notebook.clone()],
+    ));
+    let slack_client = web::Data::from(slack_client);
+    let notebook = web::Data::from(notebook);
+    let rule_engine = web::Data::new(rules::new_rule_engine(&settings.rules));
+    let pomodoro_scheduler = web::Data::new(pomodoro::spawn_scheduler(&settings.pomodoro)?);
+    let url_canonicalizer = web::Data::new(bookmarks::new_url_canonicalizer(&settings.bookmarks));
+    if let Err(e) = index::rebuild(&url_canonicalizer) {
+        warn!("Cannot build bookmark index: {}", e);
+    }
+    if settings.taskwarrior.watch {
+        if let Err(e) = watcher::spawn_watcher(&data_location) {
+            warn!("Cannot watch Taskwarrior data in {}: {}", data_location, e);
+        }
+    }
 
     let server = HttpServer::new(move || {
         info!(
@@ -35,21 +83,233 @@ pub fn run(listener: TcpListener, settings: &Settings) -> Result<Server, std::io
                     .add(("Access-Control-Allow-Origin", front_base_url.as_bytes()))
                     .add((
                         "Access-Control-Allow-Methods",
-                        "POST, GET, OPTIONS".as_bytes(),
+                        "POST, GET, PUT, DELETE, OPTIONS".as_bytes(),
                     ))
                     .add(("Access-Control-Allow-Headers", "content-type".as_bytes())),
             )
+            .wrap_fn({
+                let allowed_origins = allowed_origins.clone();
+                move |request, service| {
+                    let origin = cors::allowed_origin(request.headers(), &allowed_origins);
+                    let response = service.call(request);
+                    async move {
+                        let mut response = response.await?;
+                        if let Some(origin) = origin {
+                            let headers = response.headers_mut();
+                            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+                            headers.insert(header::VARY, HeaderValue::from_static("Origin"));
+                        }
+                        Ok(response)
+                    }
+                }
+            })
             .route("/tasks", web::get().to(routes::list_tasks))
             .route("/tasks", web::post().to(routes::add_task))
             .route("/tasks/{task_id}", web::put().to(routes::update_task))
+            .route(
+                "/tasks/{task_id}/attachments",
+                web::post().to(routes::add_attachment),
+            )
+            .route(
+                "/tasks/{task_id}/attachments/{attachment_id}",
+                web::get().to(routes::download_attachment),
+            )
+            .route(
+                "/tasks/{task_id}/attachments/{attachment_id}",
+                web::delete().to(routes::remove_attachment),
+            )
+            .route(
+                "/tasks/{task_id}/attachments/{attachment_id}",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route(
+                "/tasks/{task_id}/bookmarks",
+                web::post().to(routes::add_bookmark),
+            )
+            .route(
+                "/tasks/{task_id}/bookmarks",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route(
+                "/tasks/{task_id}/bookmarks/order",
+                web::put().to(routes::reorder_bookmarks),
+            )
+            .route(
+                "/tasks/{task_id}/bookmarks/order",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route(
+                "/tasks/{task_id}/bookmarks/pin",
+                web::put().to(routes::pin_bookmark),
+            )
+            .route(
+                "/tasks/{task_id}/bookmarks/pin",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route(
+                "/tasks/{task_id}/bookmarks/refresh",
+                web::post().to(routes::refresh_task_bookmarks),
+            )
+            .route(
+                "/tasks/{task_id}/bookmarks/seen",
+                web::post().to(routes::mark_bookmarks_seen),
+            )
+            .route(
+                "/tasks/{task_id}/checklist",
+                web::post().to(routes::add_checklist_item),
+            )
+            .route(
+                "/tasks/{task_id}/checklist/order",
+                web::put().to(routes::reorder_checklist),
+            )
+            .route(
+                "/tasks/{task_id}/checklist/order",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route(
+                "/tasks/{task_id}/checklist/{item_id}",
+                web::delete().to(routes::remove_checklist_item),
+            )
+            .route(
+                "/tasks/{task_id}/checklist/{item_id}",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route(
+                "/tasks/{task_id}/checklist/{item_id}/toggle",
+                web::post().to(routes::toggle_checklist_item),
+            )
+            .route(
+                "/tasks/{task_id}/checklist/{item_id}/promote",
+                web::post().to(routes::promote_checklist_item),
+            )
+            .route(
+                "/tasks/{task_id}/note",
+                web::post().to(routes::open_task_note),
+            )
+            .route(
+                "/tasks/{task_id}/notes",
+                web::get().to(routes::get_task_notes),
+            )
+            .route(
+                "/tasks/{task_id}/notes",
+                web::put().to(routes::save_task_notes),
+            )
+            .route(
+                "/tasks/{task_id}/notes",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route(
+                "/tasks/{task_id}/pomodoro",
+                web::post().to(routes::start_pomodoro),
+            )
+            .route(
+                "/tasks/{task_id}/pomodoro",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route("/tasks/{task_id}/tabs", web::get().to(routes::list_tabs))
+            .route("/tasks/{task_id}/tabs", web::post().to(routes::save_tabs))
+            .route(
+                "/tasks/{task_id}/tabs",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route("/admin/check", web::get().to(routes::check_tasks))
+            .route("/admin/repair", web::post().to(routes::repair_tasks))
+            .route(
+                "/admin/repair",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route(
+                "/attachments/cleanup",
+                web::post().to(routes::cleanup_attachments),
+            )
+            .route("/bookmarks/lookup", web::get().to(routes::lookup_bookmarks))
+            .route(
+                "/bookmarks/refresh",
+                web::post().to(routes::refresh_bookmarks),
+            )
             .route(
                 "/tasks",
                 web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route("/focus/{task_id}", web::post().to(routes::focus_task))
+            .route(
+                "/focus/{task_id}",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route("/contexts", web::get().to(routes::list_contexts))
+            .route("/contexts", web::post().to(routes::define_context))
+            .route(
+                "/contexts/active",
+                web::put().to(routes::set_active_context),
+            )
+            .route("/contexts/{name}", web::delete().to(routes::delete_context))
+            .route(
+                "/contexts/{name}",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route("/views", web::get().to(routes::list_views))
+            .route("/views", web::post().to(routes::save_view))
+            .route("/views/{name}", web::delete().to(routes::delete_view))
+            .route(
+                "/views/{name}",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route(
+                "/views/{name}/tasks",
+                web::get().to(routes::list_view_tasks),
+            )
+            .route("/projects", web::get().to(routes::list_projects))
+            .route(
+                "/projects/{name}/contextswitch",
+                web::put().to(routes::update_project_contextswitch),
+            )
+            .route(
+                "/projects/{name}/contextswitch",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route("/tags", web::get().to(routes::list_tags))
+            .route("/tags/merge", web::post().to(routes::merge_tags))
+            .route("/tags/{tag}/rename", web::post().to(routes::rename_tag))
+            .route("/stats", web::get().to(routes::get_stats))
+            .route("/time", web::get().to(routes::get_time_report))
+            .route("/pomodoros", web::get().to(routes::get_pomodoro_stats))
+            .route("/events", web::get().to(routes::stream_events))
+            .route("/providers", web::get().to(routes::list_providers))
+            .route("/notes/scan", web::post().to(routes::scan_notes))
+            .route("/notes/search", web::get().to(routes::search_task_notes))
+            .route("/rules", web::get().to(routes::list_rules))
+            .route("/rules/preview", web::post().to(routes::preview_rules))
+            .route("/slack/commands", web::post().to(routes::slack_command))
+            .route(
+                "/slack/interactions",
+                web::post().to(routes::slack_interaction),
+            )
+            .route("/webhooks", web::get().to(routes::list_webhooks))
+            .route("/webhooks", web::post().to(routes::save_webhook))
+            .route("/webhooks/{name}", web::delete().to(routes::delete_webhook))
+            .route(
+                "/webhooks/{name}",
+                web::method(http::Method::OPTIONS).to(routes::option_task),
+            )
+            .route(
+                "/webhooks/{name}/deliveries",
+                web::get().to(routes::list_webhook_deliveries),
             );
 
         let mut app = App::new()
             .wrap(TracingLogger::default())
             .wrap(middleware::Compress::default())
+            .app_data(view_store.clone())
+            .app_data(project_store.clone())
+            .app_data(note_store.clone())
+            .app_data(attachment_store.clone())
+            .app_data(webhook_store.clone())
+            .app_data(provider_registry.clone())
+            .app_data(slack_client.clone())
+            .app_data(notebook.clone())
+            .app_data(rule_engine.clone())
+            .app_data(pomodoro_scheduler.clone())
+            .app_data(url_canonicalizer.clone())
             .route("/ping", web::get().to(routes::ping))
             .service(api_scope);
         if let Some(path) = &static_path {
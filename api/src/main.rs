@@ -1,3 +1,4 @@
+use contextswitch_api::commands;
 use contextswitch_api::configuration::Settings;
 use contextswitch_api::observability::{get_subscriber, init_subscriber};
 use contextswitch_api::{contextswitch::taskwarrior, run};
@@ -5,11 +6,18 @@ use std::net::TcpListener;
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
-    let settings = Settings::new().expect("Cannot load Contextswitch configuration");
+    let mut settings = Settings::new().expect("Cannot load Contextswitch configuration");
     let subscriber = get_subscriber(&settings.application.log_directive);
     init_subscriber(subscriber);
 
-    taskwarrior::load_config(&settings.taskwarrior);
+    settings.taskwarrior.data_location = Some(taskwarrior::load_config(&settings.taskwarrior));
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some((command, command_args)) = args.split_first() {
+        return commands::run_command(command, command_args, &settings)
+            .await
+            .map_err(std::io::Error::other);
+    }
 
     let listener = TcpListener::bind(format!("0.0.0.0:{}", settings.application.port))
         .expect("Failed to bind port");
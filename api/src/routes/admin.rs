@@ -0,0 +1,30 @@
+use crate::contextswitch::{self as cs, admin};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::canonical::UrlCanonicalizer;
+use contextswitch::{IntegrityReport, RepairReport, RepairRequest};
+
+#[tracing::instrument(level = "debug", skip(canonicalizer))]
+pub async fn check_tasks(
+    canonicalizer: web::Data<UrlCanonicalizer>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let report: IntegrityReport = admin::check_tasks(&canonicalizer)?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&report)
+            .context("Cannot serialize Contextswitch integrity report")?,
+    ))
+}
+
+#[tracing::instrument(level = "debug", skip(canonicalizer, repair_request))]
+pub async fn repair_tasks(
+    canonicalizer: web::Data<UrlCanonicalizer>,
+    repair_request: web::Json<RepairRequest>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let report: RepairReport =
+        admin::repair_tasks(&canonicalizer, repair_request.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&report).context("Cannot serialize Contextswitch repair report")?,
+    ))
+}
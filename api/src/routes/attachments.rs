@@ -0,0 +1,126 @@
+use crate::contextswitch::{self as cs, attachments};
+use actix_multipart::{Field, Multipart};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::{AttachmentsCleanup, Task, TaskId};
+use serde::Deserialize;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+pub struct CleanupQuery {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+fn invalid_upload(e: actix_multipart::MultipartError) -> cs::ContextswitchError {
+    cs::ContextswitchError::InvalidInputError(format!("Invalid attachment upload: {}", e))
+}
+
+// Uploads are rejected as soon as they exceed the file size quota
+async fn read_field(
+    field: &mut Field,
+    max_size: Option<u64>,
+) -> Result<Vec<u8>, cs::ContextswitchError> {
+    let mut content = vec![];
+    while let Some(chunk) = field.next().await {
+        content.extend_from_slice(&chunk.map_err(invalid_upload)?);
+        if let Some(max_size) = max_size.filter(|max| content.len() as u64 > *max) {
+            return Err(cs::ContextswitchError::InvalidInputError(format!(
+                "Attachment exceeds the {} bytes limit",
+                max_size
+            )));
+        }
+    }
+
+    Ok(content)
+}
+
+// Every file of the upload is attached, other fields are ignored
+#[tracing::instrument(level = "debug", skip(attachment_store, payload))]
+pub async fn add_attachment(
+    attachment_store: web::Data<attachments::AttachmentStore>,
+    path: web::Path<TaskId>,
+    mut payload: Multipart,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task_id = path.into_inner();
+    let mut task: Option<Task> = None;
+    while let Some(field) = payload.next().await {
+        let mut field = field.map_err(invalid_upload)?;
+        let name = match field
+            .content_disposition()
+            .and_then(|content_disposition| content_disposition.get_filename())
+        {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        let mime_type = field.content_type().map(|mime_type| mime_type.to_string());
+        let content = read_field(&mut field, attachment_store.max_file_size()).await?;
+        task = Some(
+            attachments::add_attachment(
+                &attachment_store,
+                &task_id,
+                attachments::NewAttachment {
+                    name,
+                    mime_type,
+                    content,
+                },
+            )
+            .await?,
+        );
+    }
+    let task = task.ok_or_else(|| {
+        cs::ContextswitchError::InvalidInputError("No file to attach".to_string())
+    })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip(attachment_store))]
+pub async fn download_attachment(
+    attachment_store: web::Data<attachments::AttachmentStore>,
+    path: web::Path<(TaskId, Uuid)>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let (task_id, attachment_id) = path.into_inner();
+    let (attachment, content) =
+        attachments::get_attachment(&attachment_store, &task_id, &attachment_id)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(attachment.mime_type.as_str())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(attachment.name)],
+        })
+        .body(content))
+}
+
+#[tracing::instrument(level = "debug", skip(attachment_store))]
+pub async fn remove_attachment(
+    attachment_store: web::Data<attachments::AttachmentStore>,
+    path: web::Path<(TaskId, Uuid)>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let (task_id, attachment_id) = path.into_inner();
+    let task: Task =
+        attachments::remove_attachment(&attachment_store, &task_id, &attachment_id).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(dry_run = cleanup_query.dry_run))]
+pub async fn cleanup_attachments(
+    attachment_store: web::Data<attachments::AttachmentStore>,
+    cleanup_query: web::Query<CleanupQuery>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let cleanup: AttachmentsCleanup =
+        attachments::cleanup_attachments(&attachment_store, cleanup_query.dry_run)?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&cleanup)
+            .context("Cannot serialize Contextswitch attachments cleanup")?,
+    ))
+}
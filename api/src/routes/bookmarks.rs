@@ -0,0 +1,113 @@
+use crate::contextswitch::{self as cs, bookmarks, index, providers, rules};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::canonical::UrlCanonicalizer;
+use contextswitch::{BookmarkOrder, BookmarkPin, NewBookmark, Task, TaskId};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct LookupQuery {
+    url: String,
+    #[serde(default, rename = "match")]
+    lookup_match: index::LookupMatch,
+}
+
+#[tracing::instrument(level = "debug", skip(registry, canonicalizer, new_bookmark))]
+pub async fn add_bookmark(
+    registry: web::Data<providers::ProviderRegistry>,
+    canonicalizer: web::Data<UrlCanonicalizer>,
+    path: web::Path<TaskId>,
+    new_bookmark: web::Json<NewBookmark>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task: Task = bookmarks::add_bookmark(
+        &registry,
+        &canonicalizer,
+        &path.into_inner(),
+        new_bookmark.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip(canonicalizer, bookmark_pin))]
+pub async fn pin_bookmark(
+    canonicalizer: web::Data<UrlCanonicalizer>,
+    path: web::Path<TaskId>,
+    bookmark_pin: web::Json<BookmarkPin>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task: Task = bookmarks::pin_bookmark(
+        &canonicalizer,
+        &path.into_inner(),
+        bookmark_pin.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip(canonicalizer, order))]
+pub async fn reorder_bookmarks(
+    canonicalizer: web::Data<UrlCanonicalizer>,
+    path: web::Path<TaskId>,
+    order: web::Json<BookmarkOrder>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task: Task =
+        bookmarks::reorder_bookmarks(&canonicalizer, &path.into_inner(), &order.uris).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip(registry, engine))]
+pub async fn refresh_task_bookmarks(
+    registry: web::Data<providers::ProviderRegistry>,
+    engine: web::Data<rules::RuleEngine>,
+    path: web::Path<TaskId>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task: Task =
+        bookmarks::refresh_task_bookmarks(&registry, &engine, &path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip(registry, engine))]
+pub async fn refresh_bookmarks(
+    registry: web::Data<providers::ProviderRegistry>,
+    engine: web::Data<rules::RuleEngine>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let tasks: Vec<Task> = bookmarks::refresh_bookmarks(&registry, &engine).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&tasks).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn mark_bookmarks_seen(
+    path: web::Path<TaskId>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task: Task = bookmarks::mark_bookmarks_seen(&path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(url = %query.url, lookup_match = ?query.lookup_match))]
+pub async fn lookup_bookmarks(
+    query: web::Query<LookupQuery>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let tasks: Vec<Task> = bookmarks::lookup_bookmarks(&query.url, query.lookup_match)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&tasks).context("Cannot serialize Contextswitch task")?))
+}
@@ -0,0 +1,61 @@
+use crate::contextswitch::{self as cs, checklist};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::{ChecklistOrder, NewChecklistItem, Task, TaskId};
+use uuid::Uuid;
+
+fn task_response(task: &Task) -> Result<HttpResponse, cs::ContextswitchError> {
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip(new_item))]
+pub async fn add_checklist_item(
+    path: web::Path<TaskId>,
+    new_item: web::Json<NewChecklistItem>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task: Task = checklist::add_item(&path.into_inner(), &new_item.text).await?;
+
+    task_response(&task)
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn toggle_checklist_item(
+    path: web::Path<(TaskId, Uuid)>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let (task_id, item_id) = path.into_inner();
+    let task: Task = checklist::toggle_item_done(&task_id, &item_id).await?;
+
+    task_response(&task)
+}
+
+#[tracing::instrument(level = "debug", skip(order))]
+pub async fn reorder_checklist(
+    path: web::Path<TaskId>,
+    order: web::Json<ChecklistOrder>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task: Task = checklist::reorder_checklist(&path.into_inner(), &order.items).await?;
+
+    task_response(&task)
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn remove_checklist_item(
+    path: web::Path<(TaskId, Uuid)>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let (task_id, item_id) = path.into_inner();
+    let task: Task = checklist::remove_item(&task_id, &item_id).await?;
+
+    task_response(&task)
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn promote_checklist_item(
+    path: web::Path<(TaskId, Uuid)>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let (task_id, item_id) = path.into_inner();
+    let task: Task = checklist::promote_item(&task_id, &item_id).await?;
+
+    task_response(&task)
+}
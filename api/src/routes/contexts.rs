@@ -0,0 +1,44 @@
+use crate::contextswitch as cs;
+use actix_web::{web, HttpResponse};
+use anyhow::Context as _;
+use contextswitch::{ActiveContext, Context, NewContext};
+
+#[tracing::instrument(level = "debug")]
+pub async fn list_contexts() -> Result<HttpResponse, cs::ContextswitchError> {
+    let contexts: Vec<Context> = cs::list_contexts()?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&contexts).context("Cannot serialize Contextswitch contexts")?))
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(name = %new_context.name))]
+pub async fn define_context(
+    new_context: web::Json<NewContext>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let context: Context = cs::define_context(new_context.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&context).context("Cannot serialize Contextswitch context")?))
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn set_active_context(
+    active_context: web::Json<ActiveContext>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let active_context = cs::set_active_context(active_context.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&active_context).context("Cannot serialize Contextswitch context")?,
+    ))
+}
+
+#[tracing::instrument(level = "debug")]
+pub async fn delete_context(
+    path: web::Path<String>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    cs::delete_context(&path.into_inner()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
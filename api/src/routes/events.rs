@@ -0,0 +1,30 @@
+use crate::contextswitch::events;
+use actix_web::{http::header::ContentEncoding, web, HttpResponse};
+use std::convert::Infallible;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use tracing::warn;
+
+#[tracing::instrument(level = "debug")]
+pub async fn stream_events() -> HttpResponse {
+    let task_events = BroadcastStream::new(events::subscribe()).filter_map(|event| match event {
+        Ok(event) => serde_json::to_string(&event)
+            .map(|data| web::Bytes::from(format!("data: {}\n\n", data)))
+            .ok(),
+        Err(e) => {
+            warn!("Task events subscriber lagging behind: {}", e);
+            None
+        }
+    });
+    // Start the stream with a comment so that clients get the response headers right away
+    let stream = tokio_stream::once(web::Bytes::from_static(b": connected\n\n"))
+        .chain(task_events)
+        .map(Ok::<_, Infallible>);
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        // Compressed streams would be buffered by the compression middleware
+        .insert_header(ContentEncoding::Identity)
+        .streaming(stream)
+}
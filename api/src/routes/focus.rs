@@ -0,0 +1,16 @@
+use crate::contextswitch::{self as cs, focus};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::{Focus, FocusRequest, TaskId};
+
+#[tracing::instrument(level = "debug", skip(focus_request))]
+pub async fn focus_task(
+    path: web::Path<TaskId>,
+    focus_request: web::Json<FocusRequest>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let focus: Focus = focus::focus_task(&path.into_inner(), focus_request.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&focus).context("Cannot serialize Contextswitch focus")?))
+}
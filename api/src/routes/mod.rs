@@ -1,5 +1,45 @@
+mod admin;
+mod attachments;
+mod bookmarks;
+mod checklist;
+mod contexts;
+mod events;
+mod focus;
 mod health_check;
+mod notes;
+mod pomodoro;
+mod projects;
+mod providers;
+mod rules;
+mod slack;
+mod stats;
+mod tabs;
+mod tags;
+mod task_notes;
 mod tasks;
+mod time;
+mod views;
+mod webhooks;
 
+pub use admin::*;
+pub use attachments::*;
+pub use bookmarks::*;
+pub use checklist::*;
+pub use contexts::*;
+pub use events::*;
+pub use focus::*;
 pub use health_check::*;
+pub use notes::*;
+pub use pomodoro::*;
+pub use projects::*;
+pub use providers::*;
+pub use rules::*;
+pub use slack::*;
+pub use stats::*;
+pub use tabs::*;
+pub use tags::*;
+pub use task_notes::*;
 pub use tasks::*;
+pub use time::*;
+pub use views::*;
+pub use webhooks::*;
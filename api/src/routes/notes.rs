@@ -0,0 +1,27 @@
+use crate::contextswitch::{self as cs, notes};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::{Task, TaskId};
+
+#[tracing::instrument(level = "debug", skip(notebook))]
+pub async fn open_task_note(
+    notebook: web::Data<notes::Notebook>,
+    path: web::Path<TaskId>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task: Task = notes::open_task_note(&notebook, &path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip(notebook))]
+pub async fn scan_notes(
+    notebook: web::Data<notes::Notebook>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let tasks: Vec<Task> = notes::scan_notes(&notebook).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&tasks).context("Cannot serialize Contextswitch task")?))
+}
@@ -0,0 +1,37 @@
+use crate::contextswitch::{self as cs, pomodoro};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::NaiveDate;
+use contextswitch::{NewPomodoro, PomodoroStats, Task, TaskId};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct PomodoroStatsQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+#[tracing::instrument(level = "debug", skip(scheduler, new_pomodoro))]
+pub async fn start_pomodoro(
+    scheduler: web::Data<pomodoro::PomodoroScheduler>,
+    path: web::Path<TaskId>,
+    new_pomodoro: web::Json<NewPomodoro>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task: Task =
+        pomodoro::start_pomodoro(&scheduler, &path.into_inner(), new_pomodoro.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn get_pomodoro_stats(
+    query: web::Query<PomodoroStatsQuery>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let stats: PomodoroStats = pomodoro::get_pomodoro_stats(query.from, query.to)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&stats).context("Cannot serialize Contextswitch pomodoros")?))
+}
@@ -0,0 +1,34 @@
+use crate::contextswitch::{self as cs, projects};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::{ContextswitchData, Project};
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_projects(
+    store: web::Data<projects::ProjectStore>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let projects: Vec<Project> = projects::list_projects(&store)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&projects).context("Cannot serialize Contextswitch projects")?))
+}
+
+#[tracing::instrument(level = "debug", skip(store, contextswitch_data))]
+pub async fn update_project_contextswitch(
+    store: web::Data<projects::ProjectStore>,
+    path: web::Path<String>,
+    contextswitch_data: web::Json<ContextswitchData>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let contextswitch_data = projects::update_project_contextswitch(
+        &store,
+        &path.into_inner(),
+        contextswitch_data.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&contextswitch_data)
+            .context("Cannot serialize Contextswitch data")?,
+    ))
+}
@@ -0,0 +1,15 @@
+use crate::contextswitch::{self as cs, providers};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::ProviderInfo;
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_providers(
+    registry: web::Data<providers::ProviderRegistry>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let providers: Vec<ProviderInfo> = registry.list_providers();
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&providers).context("Cannot serialize Contextswitch providers")?,
+    ))
+}
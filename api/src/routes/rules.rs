@@ -0,0 +1,31 @@
+use crate::contextswitch::{self as cs, rules};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::canonical::UrlCanonicalizer;
+use contextswitch::{BookmarkEvent, Rule, RuleMatch};
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_rules(
+    engine: web::Data<rules::RuleEngine>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let rules: Vec<Rule> = rules::list_rules(&engine);
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&rules).context("Cannot serialize Contextswitch rules")?))
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(uri = %event.uri, state = %event.state))]
+pub async fn preview_rules(
+    engine: web::Data<rules::RuleEngine>,
+    canonicalizer: web::Data<UrlCanonicalizer>,
+    event: web::Json<BookmarkEvent>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let rule_matches: Vec<RuleMatch> =
+        rules::preview_rules(&engine, &canonicalizer, event.into_inner())?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&rule_matches)
+            .context("Cannot serialize Contextswitch rule matches")?,
+    ))
+}
@@ -0,0 +1,64 @@
+use crate::contextswitch::{self as cs, providers, slack};
+use actix_web::{web, HttpRequest, HttpResponse};
+use anyhow::Context;
+use chrono::Utc;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct InteractionForm {
+    payload: String,
+}
+
+fn header<'a>(request: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    request
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+}
+
+// Signatures cover the raw body, which must be checked before being parsed
+fn verify_request(
+    client: &slack::SlackClient,
+    request: &HttpRequest,
+    body: &[u8],
+) -> Result<(), cs::ContextswitchError> {
+    client.verify_signature(
+        header(request, slack::TIMESTAMP_HEADER),
+        header(request, slack::SIGNATURE_HEADER),
+        body,
+        Utc::now(),
+    )
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn slack_command(
+    client: web::Data<slack::SlackClient>,
+    request: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    verify_request(&client, &request, &body)?;
+    let command: slack::SlashCommand = serde_urlencoded::from_bytes(&body)
+        .map_err(|e| cs::ContextswitchError::InvalidInputError(e.to_string()))?;
+    let reply = slack::run_command(command).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&reply).context("Cannot serialize Slack reply")?))
+}
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn slack_interaction(
+    client: web::Data<slack::SlackClient>,
+    registry: web::Data<providers::ProviderRegistry>,
+    request: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    verify_request(&client, &request, &body)?;
+    let form: InteractionForm = serde_urlencoded::from_bytes(&body)
+        .map_err(|e| cs::ContextswitchError::InvalidInputError(e.to_string()))?;
+    let interaction: slack::SlackInteraction = serde_json::from_str(&form.payload)
+        .map_err(|e| cs::ContextswitchError::InvalidInputError(e.to_string()))?;
+    slack::handle_interaction(&registry, interaction).await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -0,0 +1,27 @@
+use crate::contextswitch::{self as cs, stats};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::{Stats, StatsPeriod};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    filter: Option<String>,
+    #[serde(default)]
+    period: StatsPeriod,
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(filter = %stats_query.filter.as_ref().unwrap_or(&"".to_string())))]
+pub async fn get_stats(
+    stats_query: web::Query<StatsQuery>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let filter = stats_query
+        .filter
+        .as_ref()
+        .map_or(vec![], |filter| filter.split(' ').collect());
+    let stats: Stats = stats::get_stats(filter, stats_query.period)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&stats).context("Cannot serialize Contextswitch stats")?))
+}
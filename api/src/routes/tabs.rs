@@ -0,0 +1,43 @@
+use crate::contextswitch::{self as cs, providers, tabs};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::canonical::UrlCanonicalizer;
+use contextswitch::{TabPanel, Task, TaskId};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct TabsQuery {
+    panel: Option<String>,
+}
+
+#[tracing::instrument(level = "debug", skip(registry, canonicalizer, tab_panel))]
+pub async fn save_tabs(
+    registry: web::Data<providers::ProviderRegistry>,
+    canonicalizer: web::Data<UrlCanonicalizer>,
+    path: web::Path<TaskId>,
+    tab_panel: web::Json<TabPanel>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let task: Task = tabs::save_tabs(
+        &registry,
+        &canonicalizer,
+        &path.into_inner(),
+        tab_panel.into_inner(),
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&task).context("Cannot serialize Contextswitch task")?))
+}
+
+#[tracing::instrument(level = "debug", skip(query))]
+pub async fn list_tabs(
+    path: web::Path<TaskId>,
+    query: web::Query<TabsQuery>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let tab_panels: Vec<TabPanel> = tabs::list_tabs(&path.into_inner(), query.panel.as_deref())?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&tab_panels).context("Cannot serialize Contextswitch tabs")?))
+}
@@ -0,0 +1,37 @@
+use crate::contextswitch::{self as cs, tags};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::{Tag, TagChange, TagMerge, TagRename};
+
+#[tracing::instrument(level = "debug")]
+pub async fn list_tags() -> Result<HttpResponse, cs::ContextswitchError> {
+    let tags: Vec<Tag> = tags::list_tags()?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&tags).context("Cannot serialize Contextswitch tags")?))
+}
+
+#[tracing::instrument(level = "debug", skip(tag_rename), fields(to = %tag_rename.to))]
+pub async fn rename_tag(
+    path: web::Path<String>,
+    tag_rename: web::Json<TagRename>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let tag_change: TagChange = tags::rename_tag(&path.into_inner(), &tag_rename.to).await?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&tag_change).context("Cannot serialize Contextswitch tag change")?,
+    ))
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(target = %tag_merge.target))]
+pub async fn merge_tags(
+    tag_merge: web::Json<TagMerge>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let tag_merge = tag_merge.into_inner();
+    let tag_change: TagChange = tags::merge_tags(tag_merge.sources, &tag_merge.target).await?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&tag_change).context("Cannot serialize Contextswitch tag change")?,
+    ))
+}
@@ -0,0 +1,57 @@
+use crate::contextswitch::{self as cs, task_notes};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::{NoteSearchResult, NotesUpdate, TaskId, TaskNotes};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct TaskNotesQuery {
+    revision: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct NotesSearchQuery {
+    q: String,
+}
+
+#[tracing::instrument(level = "debug", skip(note_store, notes_query))]
+pub async fn get_task_notes(
+    note_store: web::Data<task_notes::NoteStore>,
+    path: web::Path<TaskId>,
+    notes_query: web::Query<TaskNotesQuery>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let notes: TaskNotes =
+        task_notes::get_task_notes(&note_store, &path.into_inner(), notes_query.revision)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&notes).context("Cannot serialize Contextswitch task notes")?))
+}
+
+#[tracing::instrument(level = "debug", skip(note_store, notes_update))]
+pub async fn save_task_notes(
+    note_store: web::Data<task_notes::NoteStore>,
+    path: web::Path<TaskId>,
+    notes_update: web::Json<NotesUpdate>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let notes: TaskNotes =
+        task_notes::save_task_notes(&note_store, &path.into_inner(), notes_update.into_inner())
+            .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&notes).context("Cannot serialize Contextswitch task notes")?))
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(q = %search_query.q))]
+pub async fn search_task_notes(
+    note_store: web::Data<task_notes::NoteStore>,
+    search_query: web::Query<NotesSearchQuery>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let results: Vec<NoteSearchResult> =
+        task_notes::search_task_notes(&note_store, &search_query.q)?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&results).context("Cannot serialize Contextswitch notes search")?,
+    ))
+}
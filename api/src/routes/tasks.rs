@@ -13,6 +13,9 @@ impl ResponseError for cs::ContextswitchError {
     fn status_code(&self) -> StatusCode {
         match self {
             cs::ContextswitchError::InvalidDataError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            cs::ContextswitchError::InvalidInputError(_) => StatusCode::BAD_REQUEST,
+            cs::ContextswitchError::NotFoundError(_) => StatusCode::NOT_FOUND,
+            cs::ContextswitchError::UnauthorizedError(_) => StatusCode::UNAUTHORIZED,
             cs::ContextswitchError::UnexpectedError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -26,7 +29,7 @@ pub async fn list_tasks(
         .filter
         .as_ref()
         .map_or(vec![], |filter| filter.split(' ').collect());
-    let tasks: Vec<Task> = cs::list_tasks(filter)?;
+    let tasks: Vec<Task> = cs::list_context_tasks(filter)?;
 
     Ok(HttpResponse::Ok()
         .content_type("application/json")
@@ -0,0 +1,50 @@
+use crate::contextswitch::{self as cs, time};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use chrono::NaiveDate;
+use contextswitch::{TimeGroupBy, TimeReport};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeReportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Deserialize)]
+pub struct TimeQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    #[serde(default)]
+    group_by: TimeGroupBy,
+    #[serde(default)]
+    format: TimeReportFormat,
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(group_by = %time_query.group_by))]
+pub async fn get_time_report(
+    time_query: web::Query<TimeQuery>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let time_report: TimeReport =
+        time::get_time_report(time_query.from, time_query.to, time_query.group_by)?;
+
+    Ok(match time_query.format {
+        TimeReportFormat::Json => HttpResponse::Ok().content_type("application/json").body(
+            serde_json::to_string(&time_report)
+                .context("Cannot serialize Contextswitch time report")?,
+        ),
+        TimeReportFormat::Csv => HttpResponse::Ok()
+            .content_type("text/csv")
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(format!(
+                    "time-{}-{}.csv",
+                    time_report.from, time_report.to
+                ))],
+            })
+            .body(time::time_report_csv(&time_report)),
+    })
+}
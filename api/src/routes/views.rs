@@ -0,0 +1,49 @@
+use crate::contextswitch::{self as cs, views};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::{Task, View};
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_views(
+    store: web::Data<views::ViewStore>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let views: Vec<View> = views::list_views(&store)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&views).context("Cannot serialize Contextswitch views")?))
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(name = %view.name))]
+pub async fn save_view(
+    store: web::Data<views::ViewStore>,
+    view: web::Json<View>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let view: View = views::save_view(&store, view.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&view).context("Cannot serialize Contextswitch view")?))
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub async fn delete_view(
+    store: web::Data<views::ViewStore>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    views::delete_view(&store, &path.into_inner()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub async fn list_view_tasks(
+    store: web::Data<views::ViewStore>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let tasks: Vec<Task> = views::list_view_tasks(&store, &path.into_inner())?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&tasks).context("Cannot serialize Contextswitch task")?))
+}
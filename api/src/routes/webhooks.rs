@@ -0,0 +1,50 @@
+use crate::contextswitch::{self as cs, webhooks};
+use actix_web::{web, HttpResponse};
+use anyhow::Context;
+use contextswitch::{NewWebhook, Webhook, WebhookDelivery};
+
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn list_webhooks(
+    store: web::Data<webhooks::WebhookStore>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let webhooks: Vec<Webhook> = webhooks::list_webhooks(&store)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&webhooks).context("Cannot serialize Contextswitch webhooks")?))
+}
+
+#[tracing::instrument(level = "debug", skip_all, fields(name = %new_webhook.name))]
+pub async fn save_webhook(
+    store: web::Data<webhooks::WebhookStore>,
+    new_webhook: web::Json<NewWebhook>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let webhook: Webhook = webhooks::save_webhook(&store, new_webhook.into_inner()).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(serde_json::to_string(&webhook).context("Cannot serialize Contextswitch webhook")?))
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub async fn delete_webhook(
+    store: web::Data<webhooks::WebhookStore>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    webhooks::delete_webhook(&store, &path.into_inner()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[tracing::instrument(level = "debug", skip(store))]
+pub async fn list_webhook_deliveries(
+    store: web::Data<webhooks::WebhookStore>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, cs::ContextswitchError> {
+    let deliveries: Vec<WebhookDelivery> = webhooks::list_deliveries(&store, &path.into_inner())?;
+
+    Ok(HttpResponse::Ok().content_type("application/json").body(
+        serde_json::to_string(&deliveries)
+            .context("Cannot serialize Contextswitch webhook deliveries")?,
+    ))
+}
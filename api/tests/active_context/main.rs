@@ -0,0 +1,86 @@
+// The active context applies to every listing, tests changing it run in their
+// own binary and so against their own Taskwarrior data
+#[path = "../api/helpers.rs"]
+mod helpers;
+
+use contextswitch::{ActiveContext, NewContext, Tag, Task, View};
+use contextswitch_api::contextswitch as cs;
+use helpers::app_address;
+use rstest::*;
+
+async fn get<T: serde::de::DeserializeOwned>(app_address: &str, path: &str, filter: &str) -> T {
+    reqwest::Client::new()
+        .get(&format!("{}{}", &app_address, path))
+        .query(&[("filter", filter)])
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+#[rstest]
+#[tokio::test]
+async fn set_active_context_filters_tasks(app_address: &str) {
+    let hidden_task = cs::add_task(vec!["test", "hidden", "+cs_hidden", "+cs_a"])
+        .await
+        .unwrap();
+    let visible_task = cs::add_task(vec!["test", "visible", "+cs_b"])
+        .await
+        .unwrap();
+    cs::define_context(NewContext {
+        name: "set_active_context".to_string(),
+        read_filter: "-cs_hidden".to_string(),
+        write_filter: None,
+    })
+    .await
+    .unwrap();
+    let response = reqwest::Client::new()
+        .post(&format!("{}/views", &app_address))
+        .json(&View {
+            name: "cs_a_or_b".to_string(),
+            filter: "+cs_a or +cs_b".to_string(),
+            sort: vec![],
+            columns: vec![],
+            group_by: None,
+            builtin: false,
+        })
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert!(response.status().is_success());
+
+    let active_context: ActiveContext = reqwest::Client::new()
+        .put(&format!("{}/contexts/active", &app_address))
+        .json(&ActiveContext {
+            name: Some("set_active_context".to_string()),
+        })
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    assert_eq!(active_context.name, Some("set_active_context".to_string()));
+
+    let tasks: Vec<Task> = get(app_address, "/tasks", "+cs_a or +cs_b").await;
+    let view_tasks: Vec<Task> = get(app_address, "/views/cs_a_or_b/tasks", "").await;
+    let tags: Vec<Tag> = get(app_address, "/tags", "").await;
+    let all_tasks = cs::list_tasks(vec!["+cs_a", "or", "+cs_b"]).unwrap();
+    cs::set_active_context(ActiveContext { name: None })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        tasks.iter().map(|task| &task.id).collect::<Vec<_>>(),
+        vec![&visible_task.id]
+    );
+    assert_eq!(
+        view_tasks.iter().map(|task| &task.id).collect::<Vec<_>>(),
+        vec![&visible_task.id]
+    );
+    assert!(!tags.iter().any(|tag| tag.name == "cs_hidden"));
+    assert_eq!(all_tasks.len(), 2);
+    assert!(all_tasks.iter().any(|task| task.id == hidden_task.id));
+}
@@ -0,0 +1,93 @@
+use crate::helpers::app_address;
+use contextswitch::{
+    Bookmark, ContextswitchData, IntegrityIssueKind, IntegrityReport, RepairReport, RepairRequest,
+    Task,
+};
+use contextswitch_api::contextswitch as cs;
+use http::uri::Uri;
+use rstest::*;
+
+async fn repair_tasks(app_address: &str, dry_run: bool) -> RepairReport {
+    reqwest::Client::new()
+        .post(&format!("{}/admin/repair", &app_address))
+        .json(&RepairRequest {
+            fixes: vec![IntegrityIssueKind::DuplicateBookmark],
+            dry_run,
+        })
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+#[rstest]
+#[tokio::test]
+async fn check_and_repair_duplicate_bookmarks(app_address: &str) {
+    let task = cs::add_task(vec!["test", "check_and_repair_duplicate_bookmarks"])
+        .await
+        .unwrap();
+    let bookmark = Bookmark {
+        uri: "https://example.com/duplicate".parse::<Uri>().unwrap(),
+        content: None,
+        ..Default::default()
+    };
+    let task = cs::update_task(Task {
+        contextswitch: Some(ContextswitchData {
+            bookmarks: vec![bookmark.clone(), bookmark.clone()],
+            ..Default::default()
+        }),
+        ..task
+    })
+    .await
+    .unwrap();
+
+    let report: IntegrityReport = reqwest::Client::new()
+        .get(&format!("{}/admin/check", &app_address))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    assert!(report.issues.iter().any(
+        |issue| issue.task_id == task.id && issue.kind == IntegrityIssueKind::DuplicateBookmark
+    ));
+
+    let report = repair_tasks(app_address, true).await;
+    assert!(report.dry_run);
+    assert!(report.repaired.iter().any(|issue| issue.task_id == task.id));
+    assert_eq!(
+        cs::get_task(&task.id)
+            .unwrap()
+            .contextswitch
+            .unwrap()
+            .bookmarks,
+        vec![bookmark.clone(), bookmark.clone()]
+    );
+
+    let report = repair_tasks(app_address, false).await;
+    assert!(report.repaired.iter().any(|issue| issue.task_id == task.id));
+    assert_eq!(
+        cs::get_task(&task.id)
+            .unwrap()
+            .contextswitch
+            .unwrap()
+            .bookmarks,
+        vec![bookmark]
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn repair_requires_a_fix(app_address: &str) {
+    let response = reqwest::Client::new()
+        .post(&format!("{}/admin/repair", &app_address))
+        .json(&RepairRequest::default())
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(400, response.status().as_u16());
+}
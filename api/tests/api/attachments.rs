@@ -0,0 +1,93 @@
+use crate::helpers::app_address;
+use contextswitch::{AttachmentsCleanup, Task};
+use contextswitch_api::contextswitch as cs;
+use reqwest::multipart::{Form, Part};
+use rstest::*;
+
+async fn upload(app_address: &str, task: &Task, name: &str, content: Vec<u8>) -> reqwest::Response {
+    let form = Form::new().part(
+        "file",
+        Part::bytes(content)
+            .file_name(name.to_string())
+            .mime_str("text/plain")
+            .unwrap(),
+    );
+
+    reqwest::Client::new()
+        .post(&format!("{}/tasks/{}/attachments", &app_address, task.id))
+        .multipart(form)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+#[rstest]
+#[tokio::test]
+async fn upload_download_and_remove_attachment(app_address: &str) {
+    let task = cs::add_task(vec!["test", "upload_download_and_remove_attachment"])
+        .await
+        .unwrap();
+
+    let task: Task = upload(app_address, &task, "build.log", b"build failed".to_vec())
+        .await
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    let attachment = task.contextswitch.as_ref().unwrap().attachments[0].clone();
+    assert_eq!(attachment.name, "build.log");
+    assert_eq!(attachment.mime_type, "text/plain");
+    assert_eq!(attachment.size, 12);
+
+    let response = reqwest::Client::new()
+        .get(&format!(
+            "{}/tasks/{}/attachments/{}",
+            &app_address, task.id, attachment.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert!(response.status().is_success());
+    assert_eq!(response.headers()["content-type"], "text/plain");
+    assert_eq!(response.text().await.unwrap(), "build failed");
+
+    let task: Task = reqwest::Client::new()
+        .delete(&format!(
+            "{}/tasks/{}/attachments/{}",
+            &app_address, task.id, attachment.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    assert!(task.contextswitch.unwrap().attachments.is_empty());
+}
+
+#[rstest]
+#[tokio::test]
+async fn reject_attachment_over_quota(app_address: &str) {
+    let task = cs::add_task(vec!["test", "reject_attachment_over_quota"])
+        .await
+        .unwrap();
+
+    let response = upload(app_address, &task, "large.bin", vec![0; 2 * 1024 * 1024]).await;
+
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+#[tokio::test]
+async fn cleanup_attachments_dry_run(app_address: &str) {
+    let cleanup: AttachmentsCleanup = reqwest::Client::new()
+        .post(&format!("{}/attachments/cleanup", &app_address))
+        .query(&[("dry_run", "true")])
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    assert!(cleanup.dry_run);
+}
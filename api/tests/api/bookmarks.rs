@@ -0,0 +1,188 @@
+use crate::helpers::app_address;
+use contextswitch::{BookmarkKind, BookmarkOrder, BookmarkPin, NewBookmark, Task};
+use contextswitch_api::contextswitch as cs;
+use http::uri::Uri;
+use rstest::*;
+
+async fn add_bookmark(app_address: &str, task: &Task, url: &str) -> Task {
+    add_labelled_bookmark(app_address, task, url, None).await
+}
+
+async fn add_labelled_bookmark(
+    app_address: &str,
+    task: &Task,
+    url: &str,
+    label: Option<&str>,
+) -> Task {
+    reqwest::Client::new()
+        .post(&format!("{}/tasks/{}/bookmarks", &app_address, task.id))
+        .json(&NewBookmark {
+            uri: url.parse::<Uri>().unwrap(),
+            label: label.map(String::from),
+        })
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+#[rstest]
+#[tokio::test]
+async fn add_bookmark_once_per_page(app_address: &str) {
+    let task = cs::add_task(vec!["test", "add_bookmark_once_per_page"])
+        .await
+        .unwrap();
+
+    add_bookmark(
+        app_address,
+        &task,
+        "https://example.com/article?utm_source=news",
+    )
+    .await;
+    let task = add_bookmark(app_address, &task, "https://EXAMPLE.com:443/article/").await;
+
+    let bookmarks = task.contextswitch.unwrap().bookmarks;
+    assert_eq!(bookmarks.len(), 1);
+    assert_eq!(
+        bookmarks[0].uri.to_string(),
+        "https://example.com/article?utm_source=news"
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn pin_and_reorder_bookmarks(app_address: &str) {
+    let task = cs::add_task(vec!["test", "pin_and_reorder_bookmarks"])
+        .await
+        .unwrap();
+    add_labelled_bookmark(app_address, &task, "https://example.com/a", Some("Spec")).await;
+    let task = add_bookmark(app_address, &task, "https://example.com/b").await;
+    let bookmarks = task.contextswitch.unwrap().bookmarks;
+    assert_eq!(bookmarks[0].label, Some("Spec".to_string()));
+    assert_eq!(bookmarks[0].kind, Some(BookmarkKind::Link));
+    assert!(bookmarks[0].added_at.is_some());
+
+    let task: Task = reqwest::Client::new()
+        .put(&format!(
+            "{}/tasks/{}/bookmarks/order",
+            &app_address, task.id
+        ))
+        .json(&BookmarkOrder {
+            uris: vec![
+                "https://example.com/b".to_string(),
+                "https://example.com/a".to_string(),
+            ],
+        })
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    let task: Task = reqwest::Client::new()
+        .put(&format!("{}/tasks/{}/bookmarks/pin", &app_address, task.id))
+        .json(&BookmarkPin {
+            uri: "https://example.com/a".parse::<Uri>().unwrap(),
+            pinned: true,
+        })
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    let contextswitch = task.contextswitch.unwrap();
+    assert_eq!(
+        contextswitch
+            .bookmarks
+            .iter()
+            .map(|bookmark| (bookmark.uri.to_string(), bookmark.position))
+            .collect::<Vec<(String, Option<u32>)>>(),
+        vec![
+            ("https://example.com/b".to_string(), Some(0)),
+            ("https://example.com/a".to_string(), Some(1)),
+        ]
+    );
+    assert_eq!(
+        contextswitch
+            .sorted_bookmarks()
+            .iter()
+            .map(|bookmark| bookmark.uri.to_string())
+            .collect::<Vec<String>>(),
+        vec!["https://example.com/a", "https://example.com/b"]
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn reject_incomplete_bookmark_order(app_address: &str) {
+    let task = cs::add_task(vec!["test", "reject_incomplete_bookmark_order"])
+        .await
+        .unwrap();
+    let task = add_bookmark(app_address, &task, "https://example.com/a").await;
+
+    let response = reqwest::Client::new()
+        .put(&format!(
+            "{}/tasks/{}/bookmarks/order",
+            &app_address, task.id
+        ))
+        .json(&BookmarkOrder { uris: vec![] })
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(400, response.status().as_u16());
+}
+
+#[rstest]
+#[tokio::test]
+async fn pin_and_reorder_bookmarks_by_equivalent_url(app_address: &str) {
+    let task = cs::add_task(vec!["test", "pin_and_reorder_bookmarks_by_equivalent_url"])
+        .await
+        .unwrap();
+    add_bookmark(app_address, &task, "https://example.com/a").await;
+    let task = add_bookmark(app_address, &task, "https://example.com/b?id=1").await;
+
+    let response = reqwest::Client::new()
+        .put(&format!(
+            "{}/tasks/{}/bookmarks/order",
+            &app_address, task.id
+        ))
+        .json(&BookmarkOrder {
+            uris: vec![
+                "https://EXAMPLE.com/b?id=1&utm_source=news".to_string(),
+                "https://example.com:443/a/".to_string(),
+            ],
+        })
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(200, response.status().as_u16());
+    let task: Task = reqwest::Client::new()
+        .put(&format!("{}/tasks/{}/bookmarks/pin", &app_address, task.id))
+        .json(&BookmarkPin {
+            uri: "https://Example.com/a/#top".parse::<Uri>().unwrap(),
+            pinned: true,
+        })
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    let bookmarks = task.contextswitch.unwrap().bookmarks;
+    assert_eq!(
+        bookmarks
+            .iter()
+            .map(|bookmark| (bookmark.uri.to_string(), bookmark.position, bookmark.pinned))
+            .collect::<Vec<(String, Option<u32>, bool)>>(),
+        vec![
+            ("https://example.com/b?id=1".to_string(), Some(0), false),
+            ("https://example.com/a".to_string(), Some(1), true),
+        ]
+    );
+}
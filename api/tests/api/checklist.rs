@@ -0,0 +1,162 @@
+use crate::helpers::app_address;
+use contextswitch::{ChecklistItem, ChecklistOrder, NewChecklistItem, Task};
+use contextswitch_api::contextswitch as cs;
+use rstest::*;
+
+async fn add_item(app_address: &str, task: &Task, text: &str) -> Task {
+    reqwest::Client::new()
+        .post(&format!("{}/tasks/{}/checklist", &app_address, task.id))
+        .json(&NewChecklistItem {
+            text: text.to_string(),
+        })
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+async fn post_item(app_address: &str, task: &Task, item: &ChecklistItem, action: &str) -> Task {
+    reqwest::Client::new()
+        .post(&format!(
+            "{}/tasks/{}/checklist/{}/{}",
+            &app_address, task.id, item.id, action
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+fn checklist(task: &Task) -> Vec<ChecklistItem> {
+    task.contextswitch.as_ref().unwrap().checklist.clone()
+}
+
+#[rstest]
+#[tokio::test]
+async fn manage_checklist_items(app_address: &str) {
+    let task = cs::add_task(vec!["test", "manage_checklist_items"])
+        .await
+        .unwrap();
+    add_item(app_address, &task, "write tests").await;
+    add_item(app_address, &task, "update changelog").await;
+    let task = add_item(app_address, &task, "release").await;
+    let items = checklist(&task);
+
+    let task = post_item(app_address, &task, &items[0], "toggle").await;
+    assert!(checklist(&task)[0].done);
+    assert!(checklist(&task)[0].done_at.is_some());
+    assert_eq!(
+        task.contextswitch.as_ref().unwrap().checklist_progress(),
+        (1, 3)
+    );
+
+    let task: Task = reqwest::Client::new()
+        .put(&format!(
+            "{}/tasks/{}/checklist/order",
+            &app_address, task.id
+        ))
+        .json(&ChecklistOrder {
+            items: vec![items[2].id, items[0].id, items[1].id],
+        })
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    assert_eq!(
+        checklist(&task)
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["release", "write tests", "update changelog"]
+    );
+
+    let task: Task = reqwest::Client::new()
+        .delete(&format!(
+            "{}/tasks/{}/checklist/{}",
+            &app_address, task.id, items[1].id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    assert_eq!(checklist(&task).len(), 2);
+}
+
+#[rstest]
+#[tokio::test]
+async fn add_checklist_items_concurrently(app_address: &str) {
+    let task = cs::add_task(vec!["test", "add_checklist_items_concurrently"])
+        .await
+        .unwrap();
+    tokio::join!(
+        add_item(app_address, &task, "write tests"),
+        add_item(app_address, &task, "update changelog"),
+        add_item(app_address, &task, "release"),
+    );
+    let task = cs::get_task(&task.id).unwrap();
+
+    let mut texts: Vec<String> = checklist(&task).into_iter().map(|item| item.text).collect();
+    texts.sort();
+    assert_eq!(texts, vec!["release", "update changelog", "write tests"]);
+}
+
+#[rstest]
+#[tokio::test]
+async fn promote_checklist_item_to_task(app_address: &str) {
+    let task = cs::add_task(vec![
+        "test",
+        "promote_checklist_item_to_task",
+        "project:checklist",
+    ])
+    .await
+    .unwrap();
+    let task = add_item(app_address, &task, "promote_checklist_item_child").await;
+    let item = checklist(&task)[0].clone();
+
+    let task = post_item(app_address, &task, &item, "promote").await;
+
+    let child_id = checklist(&task)[0].task_id.clone().unwrap();
+    let child = cs::get_task(&child_id).unwrap();
+    assert_eq!(child.description, "promote_checklist_item_child");
+    assert_eq!(child.project, Some("checklist".to_string()));
+
+    let response = reqwest::Client::new()
+        .post(&format!(
+            "{}/tasks/{}/checklist/{}/promote",
+            &app_address, task.id, item.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+#[tokio::test]
+async fn promote_checklist_item_text_as_description(app_address: &str) {
+    let task = cs::add_task(vec![
+        "test",
+        "promote_checklist_item_text_as_description",
+        "project:checklist",
+    ])
+    .await
+    .unwrap();
+    let task = add_item(app_address, &task, "ship +urgent project:x").await;
+    let item = checklist(&task)[0].clone();
+
+    let task = post_item(app_address, &task, &item, "promote").await;
+
+    let child_id = checklist(&task)[0].task_id.clone().unwrap();
+    let child = cs::get_task(&child_id).unwrap();
+    assert_eq!(child.description, "ship +urgent project:x");
+    assert_eq!(child.project, Some("checklist".to_string()));
+    assert!(child.tags.unwrap_or_default().is_empty());
+}
@@ -0,0 +1,120 @@
+use crate::helpers::app_address;
+use contextswitch::{ActiveContext, Context, NewContext};
+use contextswitch_api::contextswitch as cs;
+use rstest::*;
+
+mod list_contexts {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn list_defined_contexts(app_address: &str) {
+        let context: Context = reqwest::Client::new()
+            .post(&format!("{}/contexts", &app_address))
+            .json(&NewContext {
+                name: "list_defined_contexts".to_string(),
+                read_filter: "project:Home".to_string(),
+                write_filter: Some("project:Home".to_string()),
+            })
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+
+        assert_eq!(
+            context,
+            Context {
+                name: "list_defined_contexts".to_string(),
+                read_filter: "project:Home".to_string(),
+                write_filter: Some("project:Home".to_string()),
+                active: false,
+            }
+        );
+
+        let contexts: Vec<Context> = reqwest::Client::new()
+            .get(&format!("{}/contexts", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+
+        assert!(contexts.contains(&context));
+    }
+}
+
+mod define_context {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn define_context_with_invalid_name(app_address: &str) {
+        let response = reqwest::Client::new()
+            .post(&format!("{}/contexts", &app_address))
+            .json(&NewContext {
+                name: "invalid name".to_string(),
+                read_filter: "project:Home".to_string(),
+                write_filter: None,
+            })
+            .send()
+            .await
+            .expect("Failed to execute request");
+
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+}
+
+mod set_active_context {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn set_unknown_active_context(app_address: &str) {
+        let response = reqwest::Client::new()
+            .put(&format!("{}/contexts/active", &app_address))
+            .json(&ActiveContext {
+                name: Some("unknown_context".to_string()),
+            })
+            .send()
+            .await
+            .expect("Failed to execute request");
+
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+}
+
+mod delete_context {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn delete_context(app_address: &str) {
+        cs::define_context(NewContext {
+            name: "delete_context".to_string(),
+            read_filter: "+delete_context".to_string(),
+            write_filter: None,
+        })
+        .await
+        .unwrap();
+
+        let response = reqwest::Client::new()
+            .delete(&format!("{}/contexts/delete_context", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request");
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+
+        let contexts = cs::list_contexts().unwrap();
+        assert!(!contexts.iter().any(|c| c.name == "delete_context"));
+
+        let response = reqwest::Client::new()
+            .delete(&format!("{}/contexts/delete_context", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request");
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+}
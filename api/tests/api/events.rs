@@ -0,0 +1,82 @@
+use crate::helpers::app_address;
+use contextswitch::TaskEvent;
+use contextswitch_api::contextswitch as cs;
+use rstest::*;
+use std::process::Command;
+
+async fn next_task_event(response: &mut reqwest::Response, buffer: &mut String) -> TaskEvent {
+    loop {
+        if let Some(index) = buffer.find("\n\n") {
+            let message: String = buffer.drain(..index + 2).collect();
+            if let Some(data) = message.strip_prefix("data: ") {
+                return serde_json::from_str(data.trim_end()).expect("Cannot parse task event");
+            }
+            continue;
+        }
+        let chunk = response
+            .chunk()
+            .await
+            .expect("Failed to read events")
+            .expect("Events stream closed");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn stream_task_events(app_address: &str) {
+    let mut response = reqwest::Client::new()
+        .get(&format!("{}/events", &app_address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/event-stream"
+    );
+
+    let task = cs::add_task(vec!["test", "stream_task_events"])
+        .await
+        .unwrap();
+    let mut buffer = String::new();
+    // Other tests publish events concurrently
+    let event = loop {
+        let event = next_task_event(&mut response, &mut buffer).await;
+        if event.task().id == task.id {
+            break event;
+        }
+    };
+
+    assert_eq!(event, TaskEvent::Created(task));
+}
+
+#[rstest]
+#[tokio::test]
+async fn stream_external_task_changes(app_address: &str) {
+    let data_location = Command::new("task")
+        .args(["_get", "rc.data.location"])
+        .output()
+        .expect("Failed to get Taskwarrior data location");
+    let data_location = String::from_utf8(data_location.stdout).unwrap();
+    cs::watcher::spawn_watcher(data_location.trim()).expect("Failed to watch Taskwarrior data");
+    let mut response = reqwest::Client::new()
+        .get(&format!("{}/events", &app_address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    // Tasks added outside of Contextswitch are only noticed by the watcher
+    Command::new("task")
+        .args(["add", "test", "stream_external_task_changes"])
+        .output()
+        .expect("Failed to add task with Taskwarrior");
+    let mut buffer = String::new();
+    let event = loop {
+        let event = next_task_event(&mut response, &mut buffer).await;
+        if event.task().description == "test stream_external_task_changes" {
+            break event;
+        }
+    };
+
+    assert!(matches!(event, TaskEvent::Created(_)));
+}
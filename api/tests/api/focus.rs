@@ -0,0 +1,63 @@
+use crate::helpers::app_address;
+use contextswitch::{Focus, FocusRequest, Tab, Task};
+use contextswitch_api::contextswitch as cs;
+use http::uri::Uri;
+use rstest::*;
+
+async fn focus_task(app_address: &str, task: &Task, focus_request: &FocusRequest) -> Focus {
+    reqwest::Client::new()
+        .post(&format!("{}/focus/{}", &app_address, task.id))
+        .json(focus_request)
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+#[rstest]
+#[tokio::test]
+async fn switch_focus_between_tasks(app_address: &str) {
+    let review = cs::add_task(vec!["test", "switch_focus_between_tasks", "review"])
+        .await
+        .unwrap();
+    let deploy = cs::add_task(vec!["test", "switch_focus_between_tasks", "deploy"])
+        .await
+        .unwrap();
+
+    let focus = focus_task(app_address, &review, &FocusRequest::default()).await;
+    assert_eq!(focus.task.id, review.id);
+    assert!(focus.task.start.is_some());
+    assert_eq!(focus.snapshot, None);
+
+    let tabs = vec![Tab {
+        url: "https://example.com/switch_focus_between_tasks"
+            .parse::<Uri>()
+            .unwrap(),
+        title: Some("Pull request".to_string()),
+    }];
+    let focus = focus_task(
+        app_address,
+        &deploy,
+        &FocusRequest {
+            tabs: tabs.clone(),
+            note: Some("Second file left to review".to_string()),
+        },
+    )
+    .await;
+    assert_eq!(focus.task.id, deploy.id);
+    assert!(focus.task.start.is_some());
+    let previous_task = focus.previous_task.unwrap();
+    assert_eq!(previous_task.id, review.id);
+    assert_eq!(previous_task.start, None);
+
+    let focus = focus_task(app_address, &review, &FocusRequest::default()).await;
+    let snapshot = focus.snapshot.unwrap();
+    assert_eq!(snapshot.tabs, tabs);
+    assert_eq!(
+        snapshot.note,
+        Some("Second file left to review".to_string())
+    );
+    assert_eq!(focus.previous_task.unwrap().id, deploy.id);
+}
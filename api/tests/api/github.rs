@@ -0,0 +1,124 @@
+use crate::helpers::app_address;
+use contextswitch::{
+    Bookmark, BookmarkBadge, BookmarkDetails, ContextswitchData, GithubDetails, GithubKind, Task,
+};
+use contextswitch_api::contextswitch as cs;
+use http::uri::Uri;
+use rstest::*;
+
+async fn add_task_with_bookmarks(description: &str, uris: &[&str]) -> Task {
+    let task = cs::add_task(vec!["test", description]).await.unwrap();
+    cs::update_task(Task {
+        contextswitch: Some(ContextswitchData {
+            bookmarks: uris
+                .iter()
+                .map(|uri| Bookmark {
+                    uri: uri.parse::<Uri>().unwrap(),
+                    content: None,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }),
+        ..task
+    })
+    .await
+    .unwrap()
+}
+
+async fn refresh_task_bookmarks(app_address: &str, task: &Task) -> Task {
+    reqwest::Client::new()
+        .post(&format!(
+            "{}/tasks/{}/bookmarks/refresh",
+            &app_address, task.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+fn github_details(task: &Task, index: usize) -> GithubDetails {
+    let bookmark = &task.contextswitch.as_ref().unwrap().bookmarks[index];
+    match bookmark.content.as_ref().unwrap().details.as_ref().unwrap() {
+        BookmarkDetails::Github(details) => details.clone(),
+        _ => panic!("Expecting GitHub details"),
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn refresh_github_bookmarks(app_address: &str) {
+    let task = add_task_with_bookmarks(
+        "refresh_github_bookmarks",
+        &[
+            "https://github.com/dax/contextswitch/issues/1",
+            "https://github.com/dax/contextswitch/pull/3",
+            "https://github.com/dax/contextswitch/discussions/4",
+            "https://example.com/",
+        ],
+    )
+    .await;
+
+    let task = refresh_task_bookmarks(app_address, &task).await;
+
+    let bookmarks = &task.contextswitch.as_ref().unwrap().bookmarks;
+    assert_eq!(bookmarks[0].content.as_ref().unwrap().title, "Issue 1");
+    assert_eq!(
+        bookmarks[0].content.as_ref().unwrap().badge,
+        Some(BookmarkBadge {
+            provider: "github".to_string(),
+            icon: "github".to_string(),
+            kind: "issue".to_string(),
+        })
+    );
+    let issue = github_details(&task, 0);
+    assert_eq!(issue.kind, GithubKind::Issue);
+    assert_eq!(issue.repository, "dax/contextswitch");
+    assert_eq!(issue.state, "open");
+    assert_eq!(issue.labels, vec!["bug".to_string()]);
+    assert_eq!(issue.assignees, vec!["dax".to_string()]);
+    let pull_request = github_details(&task, 1);
+    assert_eq!(pull_request.state, "merged");
+    assert_eq!(pull_request.ci_status, Some("success".to_string()));
+    let discussion = github_details(&task, 2);
+    assert_eq!(discussion.kind, GithubKind::Discussion);
+    assert_eq!(discussion.labels, vec!["question".to_string()]);
+    assert_eq!(bookmarks[3].content, None);
+}
+
+#[rstest]
+#[tokio::test]
+async fn track_github_state_changes(app_address: &str) {
+    let task = add_task_with_bookmarks(
+        "track_github_state_changes",
+        &["https://github.com/dax/contextswitch/issues/2"],
+    )
+    .await;
+
+    refresh_task_bookmarks(app_address, &task).await;
+    let task = refresh_task_bookmarks(app_address, &task).await;
+
+    let issue = github_details(&task, 0);
+    assert_eq!(issue.state, "closed");
+    assert_eq!(
+        issue
+            .state_history
+            .iter()
+            .map(|change| change.state.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["open", "closed"]
+    );
+    // Applied by the issue-closed rule of the test configuration
+    assert!(task
+        .tags
+        .as_ref()
+        .unwrap()
+        .contains(&"issue_closed".to_string()));
+    let history = &task.contextswitch.as_ref().unwrap().history;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].rule, "issue-closed");
+    assert_eq!(history[0].event.state, "closed");
+}
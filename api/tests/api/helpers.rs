@@ -1,9 +1,14 @@
+use actix_web::{web, App, HttpResponse, HttpServer};
 use contextswitch_api::configuration::Settings;
 use contextswitch_api::contextswitch::taskwarrior;
 use contextswitch_api::observability::{get_subscriber, init_subscriber};
 use mktemp::Temp;
 use rstest::*;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
 use std::net::TcpListener;
+use std::sync::Mutex;
 use tracing::info;
 
 fn setup_tracing(settings: &Settings) {
@@ -22,23 +27,227 @@ fn setup_server(settings: &Settings) -> String {
     format!("http://127.0.0.1:{}", port)
 }
 
-fn setup_taskwarrior(mut settings: Settings) -> String {
+fn setup_taskwarrior(settings: &mut Settings) {
     info!("Setting up Taskwarrior");
     let tmp_dir = Temp::new_dir().unwrap();
     settings.taskwarrior.data_location = tmp_dir.to_str().map(String::from);
     let task_data_location = taskwarrior::load_config(&settings.taskwarrior);
     tmp_dir.release();
 
-    task_data_location
+    settings.taskwarrior.data_location = Some(task_data_location);
+}
+
+fn setup_notes(settings: &mut Settings) {
+    info!("Setting up notes directory");
+    let tmp_dir = Temp::new_dir().unwrap();
+    settings.notes.directory = tmp_dir.to_str().map(String::from);
+    tmp_dir.release();
+}
+
+// Stand-in for the GitHub API: issue #2 gets closed once it has been fetched
+#[derive(Default)]
+struct GithubFetches {
+    issues: Mutex<HashMap<u64, usize>>,
+}
+
+async fn github_issue(
+    path: web::Path<(String, String, u64)>,
+    fetches: web::Data<GithubFetches>,
+) -> HttpResponse {
+    let (_, _, number) = path.into_inner();
+    let mut issues = fetches.issues.lock().unwrap();
+    let fetch_count = issues.entry(number).or_default();
+    *fetch_count += 1;
+    let state = if number == 2 && *fetch_count > 1 {
+        "closed"
+    } else {
+        "open"
+    };
+
+    HttpResponse::Ok().json(json!({
+        "number": number,
+        "title": format!("Issue {}", number),
+        "body": "Issue description",
+        "state": state,
+        "labels": [{ "name": "bug" }],
+        "assignees": [{ "login": "dax" }],
+    }))
+}
+
+async fn github_pull_request(path: web::Path<(String, String, u64)>) -> HttpResponse {
+    let (_, _, number) = path.into_inner();
+
+    HttpResponse::Ok().json(json!({
+        "number": number,
+        "title": format!("Pull request {}", number),
+        "body": null,
+        "state": "closed",
+        "merged": true,
+        "labels": [],
+        "assignees": [{ "login": "dax" }],
+        "head": { "sha": "abcdef" },
+    }))
+}
+
+async fn github_commit_status() -> HttpResponse {
+    HttpResponse::Ok().json(json!({ "state": "pending", "total_count": 0 }))
+}
+
+async fn github_check_runs() -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "total_count": 1,
+        "check_runs": [{ "status": "completed", "conclusion": "success" }],
+    }))
+}
+
+async fn github_graphql() -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "data": {
+            "repository": {
+                "discussion": {
+                    "title": "Discussion",
+                    "body": "Discussion description",
+                    "closed": false,
+                    "labels": { "nodes": [{ "name": "question" }] },
+                }
+            }
+        }
+    }))
+}
+
+fn setup_github(settings: &mut Settings) {
+    info!("Setting up GitHub stand-in");
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind random port");
+    settings.github.api_base_url =
+        format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port());
+    let fetches = web::Data::new(GithubFetches::default());
+
+    // Each test runs its own runtime, the stand-in must outlive them
+    std::thread::spawn(move || {
+        actix_web::rt::System::new().block_on(
+            HttpServer::new(move || {
+                App::new()
+                    .app_data(fetches.clone())
+                    .route(
+                        "/repos/{owner}/{repository}/issues/{number}",
+                        web::get().to(github_issue),
+                    )
+                    .route(
+                        "/repos/{owner}/{repository}/pulls/{number}",
+                        web::get().to(github_pull_request),
+                    )
+                    .route(
+                        "/repos/{owner}/{repository}/commits/{sha}/status",
+                        web::get().to(github_commit_status),
+                    )
+                    .route(
+                        "/repos/{owner}/{repository}/commits/{sha}/check-runs",
+                        web::get().to(github_check_runs),
+                    )
+                    .route("/graphql", web::post().to(github_graphql))
+            })
+            .workers(1)
+            .listen(listener)
+            .expect("Failed to bind address")
+            .run(),
+        )
+    });
+}
+
+// Stand-in for the Slack Web API: threads get a new reply each time they are
+// fetched, thread 1650000200.000100 gets resolved once it has been fetched
+#[derive(Default)]
+struct SlackFetches {
+    threads: Mutex<HashMap<String, u64>>,
+}
+
+#[derive(Deserialize)]
+struct SlackQuery {
+    channel: Option<String>,
+    ts: Option<String>,
+    user: Option<String>,
+}
+
+async fn slack_conversation_info(query: web::Query<SlackQuery>) -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "ok": true,
+        "channel": { "id": query.channel, "name": "general" },
+    }))
+}
+
+async fn slack_conversation_replies(
+    query: web::Query<SlackQuery>,
+    fetches: web::Data<SlackFetches>,
+) -> HttpResponse {
+    let ts = query.ts.clone().unwrap_or_default();
+    let mut threads = fetches.threads.lock().unwrap();
+    let reply_count = threads.entry(ts.clone()).or_insert(1);
+    *reply_count += 1;
+    let reactions = if ts == "1650000200.000100" && *reply_count > 2 {
+        json!([{ "name": "white_check_mark", "count": 1 }])
+    } else {
+        json!([])
+    };
+
+    HttpResponse::Ok().json(json!({
+        "ok": true,
+        "messages": [{
+            "ts": ts,
+            "user": "U123",
+            "text": "Can someone look at the deployment?",
+            "reply_count": *reply_count,
+            "latest_reply": "1650000100.000100",
+            "reactions": reactions,
+        }],
+    }))
+}
+
+async fn slack_user_info(query: web::Query<SlackQuery>) -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "ok": true,
+        "user": { "id": query.user, "name": "dax", "real_name": "Dax" },
+    }))
+}
+
+fn setup_slack(settings: &mut Settings) {
+    info!("Setting up Slack stand-in");
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind random port");
+    settings.slack.api_base_url =
+        format!("http://127.0.0.1:{}", listener.local_addr().unwrap().port());
+    let fetches = web::Data::new(SlackFetches::default());
+
+    std::thread::spawn(move || {
+        actix_web::rt::System::new().block_on(
+            HttpServer::new(move || {
+                App::new()
+                    .app_data(fetches.clone())
+                    .route(
+                        "/conversations.info",
+                        web::get().to(slack_conversation_info),
+                    )
+                    .route(
+                        "/conversations.replies",
+                        web::get().to(slack_conversation_replies),
+                    )
+                    .route("/users.info", web::get().to(slack_user_info))
+            })
+            .workers(1)
+            .listen(listener)
+            .expect("Failed to bind address")
+            .run(),
+        )
+    });
 }
 
 #[fixture]
 #[once]
 pub fn app_address() -> String {
-    let settings = Settings::new_from_file(Some("config/test".to_string()))
+    let mut settings = Settings::new_from_file(Some("config/test".to_string()))
         .expect("Cannot load test configuration");
     setup_tracing(&settings);
-    let address = setup_server(&settings);
-    setup_taskwarrior(settings);
-    address
+    setup_taskwarrior(&mut settings);
+    setup_github(&mut settings);
+    setup_slack(&mut settings);
+    setup_notes(&mut settings);
+    setup_server(&settings)
 }
@@ -1,3 +1,24 @@
+mod admin;
+mod attachments;
+mod bookmarks;
+mod checklist;
+mod contexts;
+mod events;
+mod focus;
+mod github;
 mod health_check;
 mod helpers;
+mod notes;
+mod pomodoro;
+mod projects;
+mod providers;
+mod rules;
+mod slack;
+mod stats;
+mod tabs;
+mod tags;
+mod task_notes;
 mod tasks;
+mod time;
+mod views;
+mod webhooks;
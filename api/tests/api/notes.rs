@@ -0,0 +1,99 @@
+use crate::helpers::app_address;
+use contextswitch::{BookmarkDetails, NoteDetails, Task};
+use contextswitch_api::contextswitch as cs;
+use rstest::*;
+use std::fs;
+use std::path::Path;
+
+async fn open_task_note(app_address: &str, task: &Task) -> Task {
+    reqwest::Client::new()
+        .post(&format!("{}/tasks/{}/note", &app_address, task.id))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+fn note_details(task: &Task) -> Vec<NoteDetails> {
+    task.contextswitch
+        .iter()
+        .flat_map(|contextswitch| contextswitch.bookmarks.iter())
+        .filter_map(
+            |bookmark| match bookmark.content.as_ref()?.details.as_ref()? {
+                BookmarkDetails::Note(details) => Some(details.clone()),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_task_note(app_address: &str) {
+    let task = cs::add_task(vec!["test", "create_task_note"])
+        .await
+        .unwrap();
+
+    let task = open_task_note(app_address, &task).await;
+
+    let notes = note_details(&task);
+    assert_eq!(notes.len(), 1);
+    assert!(notes[0].path.ends_with(&format!(
+        "test-create-task-note-{}.md",
+        &task.id.to_string()[..8]
+    )));
+    let content = fs::read_to_string(&notes[0].path).unwrap();
+    assert!(content.starts_with("# test create_task_note\n"));
+    assert!(content.contains(&task.id.to_string()));
+
+    // Opening the note again does not create another one
+    let task = open_task_note(app_address, &task).await;
+    assert_eq!(note_details(&task), notes);
+}
+
+#[rstest]
+#[tokio::test]
+async fn link_notes_mentioning_tasks(app_address: &str) {
+    let task = cs::add_task(vec!["test", "link_notes_mentioning_tasks"])
+        .await
+        .unwrap();
+    let other_task = cs::add_task(vec!["test", "link_notes_mentioning_tasks"])
+        .await
+        .unwrap();
+    let task = open_task_note(app_address, &task).await;
+    let notes_directory = Path::new(&note_details(&task)[0].path)
+        .parent()
+        .unwrap()
+        .join("meetings");
+    fs::create_dir_all(&notes_directory).unwrap();
+    let note_path = notes_directory.join("weekly.md");
+    fs::write(
+        &note_path,
+        format!("# Weekly meeting\n\n- [ ] {}\n", other_task.id),
+    )
+    .unwrap();
+
+    let tasks: Vec<Task> = reqwest::Client::new()
+        .post(&format!("{}/notes/scan", &app_address))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    let linked_task = tasks
+        .iter()
+        .find(|linked_task| linked_task.id == other_task.id)
+        .expect("Note was not linked to the task");
+    let bookmark = &linked_task.contextswitch.as_ref().unwrap().bookmarks[0];
+    assert_eq!(bookmark.content.as_ref().unwrap().title, "Weekly meeting");
+    assert_eq!(
+        note_details(linked_task)[0].path,
+        note_path.to_string_lossy()
+    );
+    // Notes already linked are left untouched
+    assert!(!tasks.iter().any(|linked_task| linked_task.id == task.id));
+}
@@ -0,0 +1,98 @@
+use crate::helpers::app_address;
+use chrono::{Duration, Utc};
+use contextswitch::{ContextswitchData, NewPomodoro, Pomodoro, PomodoroStats, Task};
+use contextswitch_api::contextswitch as cs;
+use rstest::*;
+
+async fn start_pomodoro(
+    app_address: &str,
+    task: &Task,
+    new_pomodoro: &NewPomodoro,
+) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(&format!("{}/tasks/{}/pomodoro", &app_address, task.id))
+        .json(new_pomodoro)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+#[rstest]
+#[tokio::test]
+async fn start_pomodoro_on_task(app_address: &str) {
+    let task = cs::add_task(vec!["test", "start_pomodoro_on_task"])
+        .await
+        .unwrap();
+
+    let started_task: Task = start_pomodoro(
+        app_address,
+        &task,
+        &NewPomodoro {
+            work_minutes: None,
+            break_minutes: Some(10),
+        },
+    )
+    .await
+    .json()
+    .await
+    .expect("Cannot parse JSON result");
+
+    assert!(started_task.start.is_some());
+    let pomodoros = started_task.contextswitch.unwrap().pomodoros;
+    assert_eq!(pomodoros.len(), 1);
+    assert_eq!(pomodoros[0].work_seconds, 25 * 60);
+    assert_eq!(pomodoros[0].break_seconds, 10 * 60);
+    assert!(!pomodoros[0].completed);
+
+    let response = start_pomodoro(app_address, &task, &NewPomodoro::default()).await;
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+#[tokio::test]
+async fn count_completed_pomodoros(app_address: &str) {
+    let task = cs::add_task(vec!["test", "count_completed_pomodoros"])
+        .await
+        .unwrap();
+    let start = Utc::now() - Duration::hours(1);
+    let task = cs::update_task(Task {
+        contextswitch: Some(ContextswitchData {
+            pomodoros: vec![
+                Pomodoro {
+                    start,
+                    work_seconds: 25 * 60,
+                    break_seconds: 5 * 60,
+                    completed: true,
+                },
+                Pomodoro {
+                    start: start + Duration::minutes(30),
+                    work_seconds: 25 * 60,
+                    break_seconds: 5 * 60,
+                    completed: true,
+                },
+            ],
+            ..Default::default()
+        }),
+        ..task
+    })
+    .await
+    .unwrap();
+
+    let stats: PomodoroStats = reqwest::Client::new()
+        .get(&format!("{}/pomodoros", &app_address))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    let task_count = stats
+        .by_task
+        .iter()
+        .find(|count| count.task_id == task.id)
+        .unwrap();
+    assert_eq!(task_count.count, 2);
+    assert_eq!(stats.by_day.len(), 7);
+    assert!(stats.by_day.iter().map(|day| day.count).sum::<usize>() >= 2);
+}
@@ -0,0 +1,89 @@
+use crate::helpers::app_address;
+use contextswitch::{Bookmark, ContextswitchData, Project};
+use contextswitch_api::contextswitch as cs;
+use http::uri::Uri;
+use rstest::*;
+
+fn find_project<'a>(projects: &'a [Project], name: &str) -> Option<&'a Project> {
+    projects.iter().find_map(|project| {
+        if project.name == name {
+            Some(project)
+        } else {
+            find_project(&project.children, name)
+        }
+    })
+}
+
+mod list_projects {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn list_projects_hierarchy(app_address: &str) {
+        cs::add_task(vec!["test", "list_projects", "project:ListProjects.Sub"])
+            .await
+            .unwrap();
+        cs::add_task(vec!["test", "list_projects", "project:ListProjects"])
+            .await
+            .unwrap();
+
+        let projects: Vec<Project> = reqwest::Client::new()
+            .get(&format!("{}/projects", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+
+        let project = find_project(&projects, "ListProjects").unwrap();
+        assert_eq!(project.pending, 2);
+        assert_eq!(project.completed, 0);
+        assert_eq!(project.children.len(), 1);
+        assert_eq!(project.children[0].name, "ListProjects.Sub");
+        assert_eq!(project.children[0].pending, 1);
+    }
+}
+
+mod update_project_contextswitch {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn update_project_contextswitch(app_address: &str) {
+        let contextswitch_data = ContextswitchData {
+            bookmarks: vec![Bookmark {
+                uri: "https://example.com/project".parse::<Uri>().unwrap(),
+                content: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let updated_data: ContextswitchData = reqwest::Client::new()
+            .put(&format!(
+                "{}/projects/UpdateProject.Sub/contextswitch",
+                &app_address
+            ))
+            .json(&contextswitch_data)
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+        assert_eq!(updated_data, contextswitch_data);
+
+        let projects: Vec<Project> = reqwest::Client::new()
+            .get(&format!("{}/projects", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+
+        let project = find_project(&projects, "UpdateProject.Sub").unwrap();
+        assert_eq!(project.contextswitch, Some(contextswitch_data));
+    }
+}
@@ -0,0 +1,35 @@
+use crate::helpers::app_address;
+use contextswitch::ProviderInfo;
+use rstest::*;
+
+#[rstest]
+#[tokio::test]
+async fn list_enabled_providers(app_address: &str) {
+    let providers: Vec<ProviderInfo> = reqwest::Client::new()
+        .get(&format!("{}/providers", &app_address))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    assert_eq!(
+        providers
+            .iter()
+            .map(|provider| provider.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["github", "slack", "notes"]
+    );
+    let github = &providers[0];
+    assert_eq!(github.icon, "github");
+    assert_eq!(
+        github.kinds,
+        vec![
+            "issue".to_string(),
+            "pull_request".to_string(),
+            "discussion".to_string()
+        ]
+    );
+    assert!(github.events.contains(&"merged".to_string()));
+}
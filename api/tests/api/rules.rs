@@ -0,0 +1,69 @@
+use crate::helpers::app_address;
+use contextswitch::{
+    Bookmark, BookmarkEvent, ContextswitchData, Rule, RuleAction, RuleMatch, Task,
+};
+use contextswitch_api::contextswitch as cs;
+use http::uri::Uri;
+use rstest::*;
+
+#[rstest]
+#[tokio::test]
+async fn list_configured_rules(app_address: &str) {
+    let rules: Vec<Rule> = reqwest::Client::new()
+        .get(&format!("{}/rules", &app_address))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    assert!(rules
+        .iter()
+        .any(|rule| rule.name == "pull-request-merged" && rule.actions == vec![RuleAction::Done]));
+}
+
+#[rstest]
+#[tokio::test]
+async fn preview_rules_without_applying_them(app_address: &str) {
+    let uri = "https://github.com/dax/contextswitch/pull/35"
+        .parse::<Uri>()
+        .unwrap();
+    let task = cs::add_task(vec!["test", "preview_rules"]).await.unwrap();
+    let task = cs::update_task(Task {
+        contextswitch: Some(ContextswitchData {
+            bookmarks: vec![Bookmark {
+                uri: uri.clone(),
+                content: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..task
+    })
+    .await
+    .unwrap();
+
+    let rule_matches: Vec<RuleMatch> = reqwest::Client::new()
+        .post(&format!("{}/rules/preview", &app_address))
+        .json(&BookmarkEvent {
+            // Bookmarks are matched whatever the way their URL is written
+            uri: "https://GitHub.com/dax/contextswitch/pull/35/?utm_source=mail"
+                .parse::<Uri>()
+                .unwrap(),
+            provider: "github".to_string(),
+            kind: "pull_request".to_string(),
+            state: "merged".to_string(),
+        })
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    assert_eq!(rule_matches.len(), 1);
+    assert_eq!(rule_matches[0].task_id, task.id);
+    assert_eq!(rule_matches[0].rule, "pull-request-merged");
+    assert_eq!(cs::get_task(&task.id).unwrap(), task);
+}
@@ -0,0 +1,245 @@
+use crate::helpers::app_address;
+use chrono::Utc;
+use contextswitch::{Bookmark, BookmarkDetails, ContextswitchData, SlackDetails, Task};
+use contextswitch_api::contextswitch::{self as cs, slack};
+use http::uri::Uri;
+use rstest::*;
+use serde_json::json;
+
+const SIGNING_SECRET: &str = "test_signing_secret";
+
+async fn post_signed(app_address: &str, path: &str, body: String) -> reqwest::Response {
+    let timestamp = Utc::now().timestamp().to_string();
+
+    reqwest::Client::new()
+        .post(&format!("{}{}", &app_address, path))
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )
+        .header(slack::TIMESTAMP_HEADER, &timestamp)
+        .header(
+            slack::SIGNATURE_HEADER,
+            slack::sign(SIGNING_SECRET, &timestamp, body.as_bytes()),
+        )
+        .body(body)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn post_task(app_address: &str, task: &Task, path: &str) -> Task {
+    reqwest::Client::new()
+        .post(&format!(
+            "{}/tasks/{}/bookmarks/{}",
+            &app_address, task.id, path
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+fn slack_details(task: &Task) -> SlackDetails {
+    let bookmark = &task.contextswitch.as_ref().unwrap().bookmarks[0];
+    match bookmark.content.as_ref().unwrap().details.as_ref().unwrap() {
+        BookmarkDetails::Slack(details) => details.clone(),
+        _ => panic!("Expecting Slack details"),
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn track_slack_thread_replies(app_address: &str) {
+    let task = cs::add_task(vec!["test", "track_slack_thread_replies"])
+        .await
+        .unwrap();
+    let task = cs::update_task(Task {
+        contextswitch: Some(ContextswitchData {
+            bookmarks: vec![Bookmark {
+                uri: "https://dax.slack.com/archives/C123/p1650000000000100"
+                    .parse::<Uri>()
+                    .unwrap(),
+                content: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..task
+    })
+    .await
+    .unwrap();
+
+    let task = post_task(app_address, &task, "refresh").await;
+    let content = task.contextswitch.as_ref().unwrap().bookmarks[0]
+        .content
+        .clone()
+        .unwrap();
+    assert_eq!(content.title, "Dax in #general");
+    assert_eq!(
+        content.content_preview,
+        Some("Can someone look at the deployment?".to_string())
+    );
+    let details = slack_details(&task);
+    assert_eq!(details.channel, "general");
+    assert_eq!(details.reply_count, 2);
+    assert_eq!(details.new_replies(), 0);
+
+    let task = post_task(app_address, &task, "refresh").await;
+    assert_eq!(slack_details(&task).new_replies(), 1);
+
+    let task = post_task(app_address, &task, "seen").await;
+    assert_eq!(slack_details(&task).new_replies(), 0);
+}
+
+#[rstest]
+#[tokio::test]
+async fn apply_rules_on_resolved_slack_thread(app_address: &str) {
+    let task = cs::add_task(vec!["test", "apply_rules_on_resolved_slack_thread"])
+        .await
+        .unwrap();
+    let task = cs::update_task(Task {
+        contextswitch: Some(ContextswitchData {
+            bookmarks: vec![Bookmark {
+                uri: "https://dax.slack.com/archives/C123/p1650000200000100"
+                    .parse::<Uri>()
+                    .unwrap(),
+                content: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..task
+    })
+    .await
+    .unwrap();
+
+    let task = post_task(app_address, &task, "refresh").await;
+    assert!(!slack_details(&task).resolved);
+    let task = post_task(app_address, &task, "refresh").await;
+
+    assert!(slack_details(&task).resolved);
+    assert!(task
+        .tags
+        .unwrap_or_default()
+        .contains(&"thread_resolved".to_string()));
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_task_from_slash_command(app_address: &str) {
+    let body = serde_urlencoded::to_string(&[
+        ("command", "/task"),
+        ("text", "test create_task_from_slash_command"),
+        ("user_id", "U123"),
+    ])
+    .unwrap();
+
+    let response = post_signed(app_address, "/slack/commands", body).await;
+
+    assert!(response.status().is_success());
+    let reply: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(reply["response_type"], "ephemeral");
+    assert_eq!(
+        reply["text"],
+        "Task created: test create_task_from_slash_command"
+    );
+    let tasks = cs::list_tasks(vec!["create_task_from_slash_command"]).unwrap();
+    assert_eq!(tasks.len(), 1);
+}
+
+#[rstest]
+#[tokio::test]
+async fn slash_command_text_is_only_a_description(app_address: &str) {
+    let body = serde_urlencoded::to_string(&[
+        ("command", "/task"),
+        (
+            "text",
+            "test slash_command_text_is_only_a_description project:injected +injected",
+        ),
+        ("user_id", "U123"),
+    ])
+    .unwrap();
+
+    let response = post_signed(app_address, "/slack/commands", body).await;
+
+    assert!(response.status().is_success());
+    let tasks = cs::list_tasks(vec!["slash_command_text_is_only_a_description"]).unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(
+        tasks[0].description,
+        "test slash_command_text_is_only_a_description project:injected +injected"
+    );
+    assert_eq!(tasks[0].project, None);
+    assert_eq!(tasks[0].tags, None);
+
+    let body = serde_urlencoded::to_string(&[
+        ("command", "/task"),
+        (
+            "text",
+            "test slash_command_configuration_override rc.data.location=/tmp",
+        ),
+        ("user_id", "U123"),
+    ])
+    .unwrap();
+
+    let response = post_signed(app_address, "/slack/commands", body).await;
+
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    assert!(cs::list_tasks(vec!["slash_command_configuration_override"])
+        .unwrap()
+        .is_empty());
+}
+
+#[rstest]
+#[tokio::test]
+async fn reject_unsigned_slash_command(app_address: &str) {
+    let response = reqwest::Client::new()
+        .post(&format!("{}/slack/commands", &app_address))
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            "application/x-www-form-urlencoded",
+        )
+        .header(slack::TIMESTAMP_HEADER, Utc::now().timestamp().to_string())
+        .header(slack::SIGNATURE_HEADER, "v0=0123456789abcdef")
+        .body("command=%2Ftask&text=test+reject_unsigned_slash_command")
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    assert!(cs::list_tasks(vec!["reject_unsigned_slash_command"])
+        .unwrap()
+        .is_empty());
+}
+
+#[rstest]
+#[tokio::test]
+async fn create_task_from_message_action(app_address: &str) {
+    let payload = json!({
+        "type": "message_action",
+        "team": { "id": "T123", "domain": "dax" },
+        "channel": { "id": "C123", "name": "general" },
+        "user": { "id": "U123" },
+        "message": {
+            "ts": "1650000200.000100",
+            "text": "create_task_from_message_action\nwith details",
+        },
+    });
+    let body = serde_urlencoded::to_string(&[("payload", payload.to_string())]).unwrap();
+
+    let response = post_signed(app_address, "/slack/interactions", body).await;
+
+    assert!(response.status().is_success());
+    let tasks = cs::list_tasks(vec!["create_task_from_message_action"]).unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].description, "create_task_from_message_action");
+    let bookmark = &tasks[0].contextswitch.as_ref().unwrap().bookmarks[0];
+    assert_eq!(
+        bookmark.uri.to_string(),
+        "https://dax.slack.com/archives/C123/p1650000200000100"
+    );
+    assert_eq!(slack_details(&tasks[0]).author, "Dax");
+}
@@ -0,0 +1,54 @@
+use crate::helpers::app_address;
+use contextswitch::{Stats, StatsPeriod};
+use contextswitch_api::contextswitch as cs;
+use rstest::*;
+
+#[rstest]
+#[tokio::test]
+async fn get_stats_for_filter(app_address: &str) {
+    cs::add_task(vec![
+        "test",
+        "get_stats",
+        "+get_stats",
+        "project:GetStats",
+        "due:yesterday",
+    ])
+    .await
+    .unwrap();
+    cs::add_task(vec!["test", "get_stats", "+get_stats", "project:GetStats"])
+        .await
+        .unwrap();
+
+    let stats: Stats = reqwest::Client::new()
+        .get(&format!(
+            "{}/stats?filter=%2Bget_stats&period=week",
+            &app_address
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    assert_eq!(stats.period, StatsPeriod::Week);
+    assert_eq!(stats.by_status.get("pending"), Some(&2));
+    assert_eq!(stats.by_project.get("GetStats"), Some(&2));
+    assert_eq!(stats.by_tag.get("get_stats"), Some(&2));
+    assert_eq!(stats.overdue, 1);
+    assert_eq!(stats.average_completion_seconds, None);
+    assert_eq!(stats.created.last().unwrap().count, 2);
+    assert_eq!(stats.burndown.last().unwrap().pending, 2);
+}
+
+#[rstest]
+#[tokio::test]
+async fn get_stats_with_invalid_period(app_address: &str) {
+    let response = reqwest::Client::new()
+        .get(&format!("{}/stats?period=month", &app_address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,182 @@
+use crate::helpers::app_address;
+use contextswitch::{BookmarkKind, Tab, TabPanel, Task};
+use contextswitch_api::contextswitch as cs;
+use http::uri::Uri;
+use rstest::*;
+
+fn tab(url: &str, title: &str) -> Tab {
+    Tab {
+        url: url.parse::<Uri>().unwrap(),
+        title: Some(title.to_string()),
+    }
+}
+
+async fn save_tabs(app_address: &str, task: &Task, tab_panel: &TabPanel) -> Task {
+    reqwest::Client::new()
+        .post(&format!("{}/tasks/{}/tabs", &app_address, task.id))
+        .json(tab_panel)
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+#[rstest]
+#[tokio::test]
+async fn save_and_restore_tabs(app_address: &str) {
+    let task = cs::add_task(vec!["test", "save_and_restore_tabs"])
+        .await
+        .unwrap();
+    let research = TabPanel {
+        panel: "research".to_string(),
+        tabs: vec![
+            tab("https://example.com/save_and_restore_tabs/1", "First"),
+            tab("https://example.com/save_and_restore_tabs/2", "Second"),
+        ],
+    };
+    let reading = TabPanel {
+        panel: "reading".to_string(),
+        tabs: vec![tab("https://example.com/save_and_restore_tabs/3", "Third")],
+    };
+    save_tabs(app_address, &task, &research).await;
+    save_tabs(app_address, &task, &reading).await;
+    // Saving a panel again replaces its tabs
+    let research = TabPanel {
+        tabs: vec![research.tabs[1].clone()],
+        ..research
+    };
+    let task = save_tabs(app_address, &task, &research).await;
+
+    assert_eq!(task.contextswitch.unwrap().bookmarks.len(), 2);
+    let tab_panels: Vec<TabPanel> = reqwest::Client::new()
+        .get(&format!("{}/tasks/{}/tabs", &app_address, task.id))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    assert_eq!(tab_panels, vec![reading, research.clone()]);
+
+    let tab_panels: Vec<TabPanel> = reqwest::Client::new()
+        .get(&format!(
+            "{}/tasks/{}/tabs?panel=research",
+            &app_address, task.id
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    assert_eq!(tab_panels, vec![research]);
+}
+
+#[rstest]
+#[tokio::test]
+async fn classify_tabs_through_providers(app_address: &str) {
+    let task = cs::add_task(vec!["test", "classify_tabs_through_providers"])
+        .await
+        .unwrap();
+
+    let task = save_tabs(
+        app_address,
+        &task,
+        &TabPanel {
+            panel: "issues".to_string(),
+            tabs: vec![
+                tab("https://github.com/dax/contextswitch/issues/1", "Issue"),
+                tab(
+                    "https://example.com/classify_tabs_through_providers",
+                    "Page",
+                ),
+            ],
+        },
+    )
+    .await;
+
+    assert_eq!(
+        task.contextswitch
+            .unwrap()
+            .bookmarks
+            .iter()
+            .map(|bookmark| bookmark.kind)
+            .collect::<Vec<Option<BookmarkKind>>>(),
+        vec![Some(BookmarkKind::Issue), Some(BookmarkKind::Link)]
+    );
+}
+
+async fn lookup(app_address: &str, url: &str, lookup_match: &str) -> Vec<Task> {
+    reqwest::Client::new()
+        .get(&format!("{}/bookmarks/lookup", &app_address))
+        .query(&[("url", url), ("match", lookup_match)])
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result")
+}
+
+#[rstest]
+#[tokio::test]
+async fn lookup_tasks_referencing_a_page(app_address: &str) {
+    let task = cs::add_task(vec!["test", "lookup_tasks_referencing_a_page"])
+        .await
+        .unwrap();
+    save_tabs(
+        app_address,
+        &task,
+        &TabPanel {
+            panel: "lookup".to_string(),
+            tabs: vec![tab("https://lookup.example.org/docs/intro", "Lookup")],
+        },
+    )
+    .await;
+
+    let tasks = lookup(
+        app_address,
+        "https://LOOKUP.example.org/docs/intro/",
+        "exact",
+    )
+    .await;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, task.id);
+
+    let tasks = lookup(app_address, "https://lookup.example.org/docs", "prefix").await;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, task.id);
+
+    let tasks = lookup(app_address, "lookup.example.org", "host").await;
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, task.id);
+
+    assert!(
+        lookup(app_address, "https://lookup.example.org/docs", "exact")
+            .await
+            .is_empty()
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn allow_browser_extension_origins(app_address: &str) {
+    let origin = "moz-extension://0f6b4d2c-4a1b-4c3e-8f7a-2b9d1e5c6a7f";
+
+    let response = reqwest::Client::new()
+        .request(reqwest::Method::OPTIONS, &format!("{}/tasks", &app_address))
+        .header(reqwest::header::ORIGIN, origin)
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(
+        response
+            .headers()
+            .get(reqwest::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .unwrap(),
+        origin
+    );
+}
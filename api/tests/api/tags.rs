@@ -0,0 +1,119 @@
+use crate::helpers::app_address;
+use contextswitch::{Tag, TagChange, TagMerge, TagRename, Task};
+use contextswitch_api::contextswitch as cs;
+use rstest::*;
+
+mod list_tags {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn list_tags_with_counts(app_address: &str) {
+        cs::add_task(vec!["test", "list_tags", "+list_tags"])
+            .await
+            .unwrap();
+        cs::add_task(vec!["test", "list_tags", "+list_tags"])
+            .await
+            .unwrap();
+
+        let tags: Vec<Tag> = reqwest::Client::new()
+            .get(&format!("{}/tags", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+
+        assert!(tags.contains(&Tag {
+            name: "list_tags".to_string(),
+            count: 2,
+            is_virtual: false
+        }));
+        assert!(tags
+            .iter()
+            .any(|tag| tag.name == "PENDING" && tag.is_virtual && tag.count >= 2));
+    }
+}
+
+mod rename_tag {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn rename_tag(app_address: &str) {
+        let task = cs::add_task(vec!["test", "rename_tag", "+rename_tag_from"])
+            .await
+            .unwrap();
+
+        let tag_change: TagChange = reqwest::Client::new()
+            .post(&format!("{}/tags/rename_tag_from/rename", &app_address))
+            .json(&TagRename {
+                to: "rename_tag_to".to_string(),
+            })
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+
+        assert_eq!(tag_change.tasks, vec![task.id.clone()]);
+        let tasks: Vec<Task> = cs::list_tasks(vec![&task.id.to_string()]).unwrap();
+        assert_eq!(tasks[0].tags, Some(vec!["rename_tag_to".to_string()]));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn rename_tag_to_virtual_tag(app_address: &str) {
+        let response = reqwest::Client::new()
+            .post(&format!("{}/tags/rename_tag_virtual/rename", &app_address))
+            .json(&TagRename {
+                to: "OVERDUE".to_string(),
+            })
+            .send()
+            .await
+            .expect("Failed to execute request");
+
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+}
+
+mod merge_tags {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn merge_tags(app_address: &str) {
+        let task1 = cs::add_task(vec!["test", "merge_tags", "+merge_tags_a"])
+            .await
+            .unwrap();
+        let task2 = cs::add_task(vec![
+            "test",
+            "merge_tags",
+            "+merge_tags_b",
+            "+merge_tags_target",
+        ])
+        .await
+        .unwrap();
+
+        let tag_change: TagChange = reqwest::Client::new()
+            .post(&format!("{}/tags/merge", &app_address))
+            .json(&TagMerge {
+                sources: vec!["merge_tags_a".to_string(), "merge_tags_b".to_string()],
+                target: "merge_tags_target".to_string(),
+            })
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+
+        assert_eq!(tag_change.tasks.len(), 2);
+        for task in [task1, task2] {
+            let tasks: Vec<Task> = cs::list_tasks(vec![&task.id.to_string()]).unwrap();
+            assert_eq!(tasks[0].tags, Some(vec!["merge_tags_target".to_string()]));
+        }
+    }
+}
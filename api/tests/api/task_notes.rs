@@ -0,0 +1,133 @@
+use crate::helpers::app_address;
+use contextswitch::{NoteSearchResult, NotesUpdate, Task, TaskNotes};
+use contextswitch_api::contextswitch as cs;
+use rstest::*;
+
+async fn save_notes(
+    app_address: &str,
+    task: &Task,
+    notes_update: &NotesUpdate,
+) -> reqwest::Response {
+    reqwest::Client::new()
+        .put(&format!("{}/tasks/{}/notes", &app_address, task.id))
+        .json(notes_update)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+#[rstest]
+#[tokio::test]
+async fn save_task_notes_revisions(app_address: &str) {
+    let task = cs::add_task(vec!["test", "save_task_notes_revisions"])
+        .await
+        .unwrap();
+    let large_content = format!("# Large notes\n\n{}", "- item\n".repeat(1000));
+
+    let notes: TaskNotes = save_notes(
+        app_address,
+        &task,
+        &NotesUpdate {
+            content: "# Notes\n\nFirst draft".to_string(),
+            revision: Some(0),
+        },
+    )
+    .await
+    .json()
+    .await
+    .expect("Cannot parse JSON result");
+    assert_eq!(notes.revision, 1);
+
+    let notes: TaskNotes = save_notes(
+        app_address,
+        &task,
+        &NotesUpdate {
+            content: large_content.clone(),
+            revision: Some(1),
+        },
+    )
+    .await
+    .json()
+    .await
+    .expect("Cannot parse JSON result");
+    assert_eq!(notes.revision, 2);
+    // Large notes only live in the notes store
+    let task = cs::get_task(&task.id).unwrap();
+    assert_eq!(
+        task.contextswitch
+            .as_ref()
+            .unwrap()
+            .notes
+            .as_ref()
+            .unwrap()
+            .content,
+        None
+    );
+
+    let notes: TaskNotes = reqwest::Client::new()
+        .get(&format!("{}/tasks/{}/notes", &app_address, task.id))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    assert_eq!(notes.content, large_content);
+    assert_eq!(notes.revisions.len(), 2);
+
+    let first_revision: TaskNotes = reqwest::Client::new()
+        .get(&format!("{}/tasks/{}/notes", &app_address, task.id))
+        .query(&[("revision", "1")])
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    assert_eq!(first_revision.content, "# Notes\n\nFirst draft");
+
+    let response = save_notes(
+        app_address,
+        &task,
+        &NotesUpdate {
+            content: "Stale edit".to_string(),
+            revision: Some(1),
+        },
+    )
+    .await;
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+#[tokio::test]
+async fn search_task_notes(app_address: &str) {
+    let task = cs::add_task(vec!["test", "search_task_notes"])
+        .await
+        .unwrap();
+    save_notes(
+        app_address,
+        &task,
+        &NotesUpdate {
+            content: "# Migration\n\nCheck the Xylophone cluster before switching".to_string(),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let results: Vec<NoteSearchResult> = reqwest::Client::new()
+        .get(&format!("{}/notes/search", &app_address))
+        .query(&[("q", "xylophone")])
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].task_id, task.id);
+    assert_eq!(
+        results[0].snippet,
+        "Check the Xylophone cluster before switching"
+    );
+}
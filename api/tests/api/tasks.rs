@@ -118,8 +118,10 @@ mod add_task {
             &ContextswitchData {
                 bookmarks: vec![Bookmark {
                     uri: "https://example.com/path?filter=1".parse::<Uri>().unwrap(),
-                    content: None
-                }]
+                    content: None,
+                    ..Default::default()
+                }],
+                ..Default::default()
             }
         );
     }
@@ -144,6 +146,7 @@ mod update_task {
         cs_data.bookmarks.push(Bookmark {
             uri: "https://example.com/path2".parse::<Uri>().unwrap(),
             content: None,
+            ..Default::default()
         });
 
         let updated_task: Task = reqwest::Client::new()
@@ -163,13 +166,16 @@ mod update_task {
                 bookmarks: vec![
                     Bookmark {
                         uri: "https://example.com/path?filter=1".parse::<Uri>().unwrap(),
-                        content: None
+                        content: None,
+                        ..Default::default()
                     },
                     Bookmark {
                         uri: "https://example.com/path2".parse::<Uri>().unwrap(),
-                        content: None
+                        content: None,
+                        ..Default::default()
                     }
-                ]
+                ],
+                ..Default::default()
             }
         );
     }
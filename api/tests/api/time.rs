@@ -0,0 +1,123 @@
+use crate::helpers::app_address;
+use chrono::{Duration, Utc};
+use contextswitch::{ContextswitchData, Task, TimeReport, WorkSession};
+use contextswitch_api::contextswitch as cs;
+use rstest::*;
+
+async fn add_task_with_session(description: &str, minutes: i64) -> Task {
+    let task = cs::add_task(vec!["test", "project:time_report", description])
+        .await
+        .unwrap();
+    let end = Utc::now() - Duration::minutes(1);
+
+    cs::update_task(Task {
+        contextswitch: Some(ContextswitchData {
+            sessions: vec![WorkSession {
+                start: end - Duration::minutes(minutes),
+                end,
+                note: None,
+            }],
+            ..Default::default()
+        }),
+        ..task
+    })
+    .await
+    .unwrap()
+}
+
+#[rstest]
+#[tokio::test]
+async fn report_time_by_project(app_address: &str) {
+    add_task_with_session("report_time_by_project_1", 30).await;
+    add_task_with_session("report_time_by_project_2", 15).await;
+
+    let time_report: TimeReport = reqwest::Client::new()
+        .get(&format!("{}/time", &app_address))
+        .query(&[("group_by", "project")])
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    let entry = time_report
+        .entries
+        .iter()
+        .find(|entry| entry.group == "time_report")
+        .unwrap();
+    assert!(entry.seconds >= 45 * 60);
+    assert!(entry.sessions >= 2);
+}
+
+#[rstest]
+#[tokio::test]
+async fn export_time_report_as_csv(app_address: &str) {
+    let task = add_task_with_session("export_time_report_as_csv", 20).await;
+
+    let response = reqwest::Client::new()
+        .get(&format!("{}/time", &app_address))
+        .query(&[("group_by", "task"), ("format", "csv")])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(
+        response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .unwrap(),
+        "text/csv"
+    );
+    let csv = response.text().await.unwrap();
+    assert!(csv.starts_with("task,task_id,seconds,hours,sessions\n"));
+    assert!(csv.contains(&format!(
+        "export_time_report_as_csv,{},1200,0.33,1\n",
+        task.id
+    )));
+}
+
+#[rstest]
+#[tokio::test]
+async fn record_session_when_task_is_stopped(app_address: &str) {
+    let task = cs::add_task(vec!["test", "record_session_when_task_is_stopped"])
+        .await
+        .unwrap();
+    let start = Utc::now() - Duration::minutes(10);
+    let task = cs::update_task(Task {
+        start: Some(start),
+        ..task
+    })
+    .await
+    .unwrap();
+
+    let task: Task = reqwest::Client::new()
+        .put(&format!("{}/tasks/{}", &app_address, task.id))
+        .json(&Task {
+            start: None,
+            ..task
+        })
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+
+    let sessions = task.contextswitch.unwrap().sessions;
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].duration() >= Duration::minutes(10));
+}
+
+#[rstest]
+#[tokio::test]
+async fn reject_inverted_time_range(app_address: &str) {
+    let response = reqwest::Client::new()
+        .get(&format!("{}/time", &app_address))
+        .query(&[("from", "2022-01-10"), ("to", "2022-01-01")])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,179 @@
+use crate::helpers::app_address;
+use contextswitch::{Task, View};
+use contextswitch_api::contextswitch as cs;
+use rstest::*;
+
+mod list_views {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn list_builtin_views(app_address: &str) {
+        let views: Vec<View> = reqwest::Client::new()
+            .get(&format!("{}/views", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+
+        let builtin_names: Vec<&str> = views
+            .iter()
+            .filter(|view| view.builtin)
+            .map(|view| view.name.as_str())
+            .collect();
+        assert_eq!(
+            builtin_names,
+            vec!["next", "waiting", "overdue", "recurring", "completed"]
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn list_recurring_view_templates(app_address: &str) {
+        let task = cs::add_task(vec![
+            "test",
+            "list_recurring_view_templates",
+            "due:tomorrow",
+            "recur:weekly",
+        ])
+        .await
+        .unwrap();
+
+        let tasks: Vec<Task> = reqwest::Client::new()
+            .get(&format!("{}/views/recurring/tasks", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+
+        assert!(tasks
+            .iter()
+            .any(|recurring_task| recurring_task.id == task.id));
+    }
+}
+
+mod save_view {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn save_and_list_view_tasks(app_address: &str) {
+        cs::add_task(vec!["test", "save_view", "+save_view", "priority:L"])
+            .await
+            .unwrap();
+        cs::add_task(vec!["test", "save_view", "+save_view", "priority:H"])
+            .await
+            .unwrap();
+
+        let view: View = reqwest::Client::new()
+            .post(&format!("{}/views", &app_address))
+            .json(&View {
+                name: "save_view".to_string(),
+                filter: "+save_view".to_string(),
+                sort: vec!["priority-".to_string()],
+                columns: vec!["priority".to_string()],
+                group_by: Some("project".to_string()),
+                builtin: true,
+            })
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+        assert!(!view.builtin);
+
+        let tasks: Vec<Task> = reqwest::Client::new()
+            .get(&format!("{}/views/save_view/tasks", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].priority, Some(contextswitch::Priority::H));
+        assert_eq!(tasks[1].priority, Some(contextswitch::Priority::L));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn save_builtin_view(app_address: &str) {
+        let response = reqwest::Client::new()
+            .post(&format!("{}/views", &app_address))
+            .json(&View {
+                name: "next".to_string(),
+                filter: "status:pending".to_string(),
+                sort: vec![],
+                columns: vec![],
+                group_by: None,
+                builtin: false,
+            })
+            .send()
+            .await
+            .expect("Failed to execute request");
+
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn save_view_with_unknown_column(app_address: &str) {
+        let response = reqwest::Client::new()
+            .post(&format!("{}/views", &app_address))
+            .json(&View {
+                name: "save_view_with_unknown_column".to_string(),
+                filter: "status:pending".to_string(),
+                sort: vec![],
+                columns: vec!["description".to_string()],
+                group_by: Some("unknown".to_string()),
+                builtin: false,
+            })
+            .send()
+            .await
+            .expect("Failed to execute request");
+
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+}
+
+mod delete_view {
+    use super::*;
+
+    #[rstest]
+    #[tokio::test]
+    async fn delete_view(app_address: &str) {
+        reqwest::Client::new()
+            .post(&format!("{}/views", &app_address))
+            .json(&View {
+                name: "delete_view".to_string(),
+                filter: "+delete_view".to_string(),
+                sort: vec![],
+                columns: vec![],
+                group_by: None,
+                builtin: false,
+            })
+            .send()
+            .await
+            .expect("Failed to execute request");
+
+        let response = reqwest::Client::new()
+            .delete(&format!("{}/views/delete_view", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request");
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+
+        let response = reqwest::Client::new()
+            .get(&format!("{}/views/delete_view/tasks", &app_address))
+            .send()
+            .await
+            .expect("Failed to execute request");
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::helpers::app_address;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use contextswitch::{NewWebhook, Webhook, WebhookDelivery, WebhookEvent};
+use contextswitch_api::contextswitch::{self as cs, webhooks};
+use rstest::*;
+use std::net::TcpListener;
+use std::sync::Mutex;
+use std::time::Duration;
+
+struct ReceivedRequest {
+    event: Option<String>,
+    signature: Option<String>,
+    body: String,
+}
+
+#[derive(Default)]
+struct ReceivedRequests {
+    requests: Mutex<Vec<ReceivedRequest>>,
+}
+
+fn header(request: &HttpRequest, name: &str) -> Option<String> {
+    request
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+// Fail the first delivery of each payload to exercise retries
+async fn receive(
+    request: HttpRequest,
+    body: String,
+    received: web::Data<ReceivedRequests>,
+) -> HttpResponse {
+    let mut requests = received.requests.lock().unwrap();
+    let first_attempt = !requests.iter().any(|received| received.body == body);
+    requests.push(ReceivedRequest {
+        event: header(&request, webhooks::EVENT_HEADER),
+        signature: header(&request, webhooks::SIGNATURE_HEADER),
+        body,
+    });
+
+    if first_attempt {
+        HttpResponse::InternalServerError().finish()
+    } else {
+        HttpResponse::Ok().finish()
+    }
+}
+
+fn setup_receiver() -> (String, web::Data<ReceivedRequests>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind random port");
+    let port = listener.local_addr().unwrap().port();
+    let received = web::Data::new(ReceivedRequests::default());
+    let app_received = received.clone();
+
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(app_received.clone())
+            .route("/hook", web::post().to(receive))
+    })
+    .workers(1)
+    .listen(listener)
+    .expect("Failed to bind address")
+    .run();
+    tokio::spawn(server);
+
+    (format!("http://127.0.0.1:{}/hook", port), received)
+}
+
+async fn save_webhook(app_address: &str, new_webhook: &NewWebhook) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(&format!("{}/webhooks", &app_address))
+        .json(new_webhook)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+#[rstest]
+#[tokio::test]
+async fn deliver_signed_webhook_with_retry(app_address: &str) {
+    let (receiver_url, received) = setup_receiver();
+    let webhook: Webhook = save_webhook(
+        app_address,
+        &NewWebhook {
+            name: "deliver_signed_webhook".to_string(),
+            url: receiver_url,
+            secret: "secret".to_string(),
+            events: vec![WebhookEvent::Created],
+        },
+    )
+    .await
+    .json()
+    .await
+    .expect("Cannot parse JSON result");
+    assert!(!webhook.configured);
+
+    let task = cs::add_task(vec!["test", "deliver_signed_webhook"])
+        .await
+        .unwrap();
+
+    let mut delivery: Option<WebhookDelivery> = None;
+    for _ in 0..100 {
+        let deliveries: Vec<WebhookDelivery> = reqwest::Client::new()
+            .get(&format!(
+                "{}/webhooks/deliver_signed_webhook/deliveries",
+                &app_address
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request")
+            .json()
+            .await
+            .expect("Cannot parse JSON result");
+        delivery = deliveries
+            .into_iter()
+            .find(|delivery| delivery.task_id == task.id);
+        if delivery.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    let delivery = delivery.expect("Webhook was not delivered");
+    assert!(delivery.delivered);
+    assert_eq!(delivery.attempts, 2);
+    assert_eq!(delivery.status_code, Some(200));
+    assert_eq!(delivery.event, WebhookEvent::Created);
+
+    let requests = received.requests.lock().unwrap();
+    let request = requests
+        .iter()
+        .find(|received| received.body.contains(&task.id.to_string()))
+        .unwrap();
+    assert_eq!(request.event, Some("created".to_string()));
+    assert_eq!(
+        request.signature,
+        Some(webhooks::sign("secret", request.body.as_bytes()))
+    );
+}
+
+#[rstest]
+#[tokio::test]
+async fn reject_webhook_with_invalid_url(app_address: &str) {
+    let response = save_webhook(
+        app_address,
+        &NewWebhook {
+            name: "invalid_url".to_string(),
+            url: "ftp://example.com/hook".to_string(),
+            secret: "secret".to_string(),
+            events: vec![],
+        },
+    )
+    .await;
+
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[rstest]
+#[tokio::test]
+async fn delete_webhook(app_address: &str) {
+    save_webhook(
+        app_address,
+        &NewWebhook {
+            name: "delete_webhook".to_string(),
+            url: "http://127.0.0.1:1/hook".to_string(),
+            secret: "secret".to_string(),
+            events: vec![WebhookEvent::Deleted],
+        },
+    )
+    .await;
+
+    let response = reqwest::Client::new()
+        .delete(&format!("{}/webhooks/delete_webhook", &app_address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+
+    let webhooks: Vec<Webhook> = reqwest::Client::new()
+        .get(&format!("{}/webhooks", &app_address))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .expect("Cannot parse JSON result");
+    assert!(!webhooks
+        .iter()
+        .any(|webhook| webhook.name == "delete_webhook"));
+}
@@ -0,0 +1,227 @@
+use http::uri::Uri;
+
+// Parameters ending with * match any parameter starting with the same prefix
+pub const DEFAULT_TRACKING_PARAMS: [&str; 9] = [
+    "utm_*", "fbclid", "gclid", "dclid", "msclkid", "mc_cid", "mc_eid", "igshid", "_hsenc",
+];
+
+pub fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "http" => Some(80),
+        "https" => Some(443),
+        _ => None,
+    }
+}
+
+pub fn canonical_host(uri: &Uri) -> Option<String> {
+    uri.host().map(|host| host.to_lowercase())
+}
+
+// Canonical form of URLs, the same page is bookmarked once whatever the way
+// its URL was written
+#[derive(Debug, PartialEq, Clone, Eq)]
+pub struct UrlCanonicalizer {
+    tracking_params: Vec<String>,
+}
+
+impl Default for UrlCanonicalizer {
+    fn default() -> Self {
+        UrlCanonicalizer::new(
+            DEFAULT_TRACKING_PARAMS
+                .iter()
+                .map(|param| param.to_string())
+                .collect(),
+        )
+    }
+}
+
+impl UrlCanonicalizer {
+    pub fn new(tracking_params: Vec<String>) -> Self {
+        UrlCanonicalizer {
+            tracking_params: tracking_params
+                .into_iter()
+                .map(|param| param.to_lowercase())
+                .collect(),
+        }
+    }
+
+    pub fn is_tracking_param(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.tracking_params
+            .iter()
+            .any(|param| match param.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => &name == param,
+            })
+    }
+
+    fn canonical_query(&self, query: &str) -> String {
+        let params: Vec<&str> = query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .filter(|param| {
+                let name = param.split('=').next().unwrap_or_default();
+                !self.is_tracking_param(name)
+            })
+            .collect();
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
+
+    // Scheme and host are case insensitive, default ports, trailing slashes
+    // and tracking parameters do not change the page. Fragments are already
+    // dropped when the URI is parsed.
+    pub fn canonical_url(&self, uri: &Uri) -> String {
+        let scheme = uri
+            .scheme_str()
+            .map(|scheme| scheme.to_lowercase())
+            .unwrap_or_default();
+        let port = uri
+            .port_u16()
+            .filter(|port| Some(*port) != default_port(&scheme))
+            .map(|port| format!(":{}", port))
+            .unwrap_or_default();
+        let path = uri.path().trim_end_matches('/');
+        let query = uri
+            .query()
+            .map(|query| self.canonical_query(query))
+            .unwrap_or_default();
+
+        format!(
+            "{}://{}{}{}{}",
+            scheme,
+            canonical_host(uri).unwrap_or_default(),
+            port,
+            path,
+            query
+        )
+    }
+
+    pub fn same_url(&self, uri: &Uri, other: &Uri) -> bool {
+        self.canonical_url(uri) == self.canonical_url(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical_url(canonicalizer: &UrlCanonicalizer, url: &str) -> String {
+        canonicalizer.canonical_url(&url.parse::<Uri>().unwrap())
+    }
+
+    #[test]
+    fn test_canonical_url_lowercases_scheme_and_host() {
+        let canonicalizer = UrlCanonicalizer::default();
+
+        assert_eq!(
+            canonical_url(&canonicalizer, "HTTPS://Example.COM/Path"),
+            "https://example.com/Path"
+        );
+    }
+
+    #[test]
+    fn test_canonical_url_strips_default_ports() {
+        let canonicalizer = UrlCanonicalizer::default();
+
+        assert_eq!(
+            canonical_url(&canonicalizer, "https://example.com:443/a"),
+            "https://example.com/a"
+        );
+        assert_eq!(
+            canonical_url(&canonicalizer, "http://example.com:80/a"),
+            "http://example.com/a"
+        );
+        assert_eq!(
+            canonical_url(&canonicalizer, "http://example.com:443/a"),
+            "http://example.com:443/a"
+        );
+    }
+
+    #[test]
+    fn test_canonical_url_trims_trailing_slashes() {
+        let canonicalizer = UrlCanonicalizer::default();
+
+        assert_eq!(
+            canonical_url(&canonicalizer, "https://example.com/a/"),
+            "https://example.com/a"
+        );
+        assert_eq!(
+            canonical_url(&canonicalizer, "https://example.com/"),
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn test_canonical_url_removes_tracking_params() {
+        let canonicalizer = UrlCanonicalizer::default();
+
+        assert_eq!(
+            canonical_url(
+                &canonicalizer,
+                "https://example.com/a?id=1&UTM_Source=news&utm_campaign=x&fbclid=abc"
+            ),
+            "https://example.com/a?id=1"
+        );
+        assert_eq!(
+            canonical_url(&canonicalizer, "https://example.com/a?utm_source=news"),
+            "https://example.com/a"
+        );
+    }
+
+    #[test]
+    fn test_canonical_url_removes_configured_tracking_params() {
+        let canonicalizer = UrlCanonicalizer::new(vec!["ref".to_string(), "src_*".to_string()]);
+
+        assert_eq!(
+            canonical_url(
+                &canonicalizer,
+                "https://example.com/a?ref=home&src_page=1&source=x&utm_source=news"
+            ),
+            "https://example.com/a?source=x&utm_source=news"
+        );
+    }
+
+    #[test]
+    fn test_canonical_url_drops_fragment() {
+        let canonicalizer = UrlCanonicalizer::default();
+
+        assert_eq!(
+            canonical_url(&canonicalizer, "https://example.com/a?id=1#section"),
+            "https://example.com/a?id=1"
+        );
+    }
+
+    #[test]
+    fn test_canonical_url_is_idempotent() {
+        let canonicalizer = UrlCanonicalizer::default();
+
+        for url in [
+            "HTTPS://Example.com:443/a/?utm_source=news&id=1#top",
+            "http://example.com:8080/",
+            "https://example.com",
+        ] {
+            let canonical = canonical_url(&canonicalizer, url);
+            assert_eq!(canonical_url(&canonicalizer, &canonical), canonical);
+        }
+    }
+
+    #[test]
+    fn test_same_url() {
+        let canonicalizer = UrlCanonicalizer::default();
+
+        assert!(canonicalizer.same_url(
+            &"https://EXAMPLE.com:443/a/?utm_source=news"
+                .parse::<Uri>()
+                .unwrap(),
+            &"https://example.com/a".parse::<Uri>().unwrap(),
+        ));
+        assert!(!canonicalizer.same_url(
+            &"https://example.com/a?id=1".parse::<Uri>().unwrap(),
+            &"https://example.com/a?id=2".parse::<Uri>().unwrap(),
+        ));
+    }
+}
@@ -1,9 +1,15 @@
-use chrono::{DateTime, Utc};
+use canonical::UrlCanonicalizer;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use http::uri::Uri;
+use migrations::DataVersion;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 use uuid::Uuid;
 
+pub mod canonical;
+pub mod migrations;
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Recurrence {
@@ -47,18 +53,214 @@ impl fmt::Display for Priority {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BookmarkKind {
+    Link,
+    Note,
+    Issue,
+    Chat,
+    File,
+}
+
+impl BookmarkKind {
+    // Without a provider to tell better, local files are the only known kind
+    pub fn from_uri(uri: &Uri) -> Self {
+        match uri.scheme_str() {
+            Some("file") => BookmarkKind::File,
+            _ => BookmarkKind::Link,
+        }
+    }
+}
+
+// Fields added after the first version are optional, so that data saved
+// before them can still be read
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq, Default)]
 pub struct Bookmark {
     #[serde(with = "uri")]
     pub uri: Uri,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content: Option<BookmarkContent>,
+    // Browser panel the bookmark was saved from, as a tab
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panel: Option<String>,
+    // Shown instead of the title or the URI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<BookmarkKind>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "opt_tw_date_format"
+    )]
+    pub added_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    // Manual position, bookmarks without one come after the others
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct NewBookmark {
+    #[serde(with = "uri")]
+    pub uri: Uri,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BookmarkPin {
+    #[serde(with = "uri")]
+    pub uri: Uri,
+    pub pinned: bool,
+}
+
+// URIs of every bookmark of the task, in their new order
+#[derive(Deserialize, Serialize)]
+pub struct BookmarkOrder {
+    pub uris: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
 pub struct BookmarkContent {
     pub title: String,
     pub content_preview: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<BookmarkDetails>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub badge: Option<BookmarkBadge>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct Tab {
+    #[serde(with = "uri")]
+    pub url: Uri,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct TabPanel {
+    pub panel: String,
+    pub tabs: Vec<Tab>,
+}
+
+// Working context left behind when switching away from a task
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct ContextSnapshot {
+    #[serde(with = "tw_date_format")]
+    pub date: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tabs: Vec<Tab>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq, Default)]
+pub struct FocusRequest {
+    #[serde(default)]
+    pub tabs: Vec<Tab>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct BookmarkBadge {
+    pub provider: String,
+    pub icon: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct ProviderInfo {
+    pub name: String,
+    pub icon: String,
+    pub uri_patterns: Vec<String>,
+    pub kinds: Vec<String>,
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum BookmarkDetails {
+    Github(GithubDetails),
+    Slack(SlackDetails),
+    Note(NoteDetails),
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GithubKind {
+    Issue,
+    PullRequest,
+    Discussion,
+}
+
+impl fmt::Display for GithubKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GithubKind::Issue => write!(f, "issue"),
+            GithubKind::PullRequest => write!(f, "pull_request"),
+            GithubKind::Discussion => write!(f, "discussion"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct GithubDetails {
+    pub kind: GithubKind,
+    pub repository: String,
+    pub number: u64,
+    pub state: String,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub assignees: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ci_status: Option<String>,
+    #[serde(default)]
+    pub state_history: Vec<StateChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct StateChange {
+    pub state: String,
+    #[serde(with = "tw_date_format")]
+    pub date: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct SlackDetails {
+    pub workspace: String,
+    pub channel_id: String,
+    pub channel: String,
+    pub author: String,
+    pub ts: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread_ts: Option<String>,
+    #[serde(default)]
+    pub reply_count: u64,
+    // Replies already there the last time the bookmark was marked as seen
+    #[serde(default)]
+    pub seen_reply_count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_reply: Option<String>,
+    // The thread parent got a check mark reaction
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub resolved: bool,
+}
+
+impl SlackDetails {
+    pub fn new_replies(&self) -> u64 {
+        self.reply_count.saturating_sub(self.seen_reply_count)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct NoteDetails {
+    pub path: String,
 }
 
 pub mod uri {
@@ -82,9 +284,254 @@ pub mod uri {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq, Default)]
 pub struct ContextswitchData {
+    #[serde(default)]
+    pub version: DataVersion,
     pub bookmarks: Vec<Bookmark>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<HistoryEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<ContextSnapshot>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sessions: Vec<WorkSession>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pomodoros: Vec<Pomodoro>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checklist: Vec<ChecklistItem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<NotesData>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+}
+
+impl ContextswitchData {
+    // Number of done items and total number of items
+    pub fn checklist_progress(&self) -> (usize, usize) {
+        (
+            self.checklist.iter().filter(|item| item.done).count(),
+            self.checklist.len(),
+        )
+    }
+
+    // Pinned bookmarks first, then by position
+    pub fn sorted_bookmarks(&self) -> Vec<&Bookmark> {
+        let mut bookmarks: Vec<&Bookmark> = self.bookmarks.iter().collect();
+        bookmarks.sort_by_key(|bookmark| (!bookmark.pinned, bookmark.position.unwrap_or(u32::MAX)));
+        bookmarks
+    }
+
+    pub fn find_bookmark(&self, uri: &Uri, canonicalizer: &UrlCanonicalizer) -> Option<&Bookmark> {
+        self.bookmarks
+            .iter()
+            .find(|bookmark| canonicalizer.same_url(&bookmark.uri, uri))
+    }
+
+    // A page already bookmarked keeps its first bookmark and URI, returns
+    // whether the bookmark was added
+    pub fn add_bookmark(&mut self, bookmark: Bookmark, canonicalizer: &UrlCanonicalizer) -> bool {
+        if self.find_bookmark(&bookmark.uri, canonicalizer).is_some() {
+            return false;
+        }
+        self.bookmarks.push(bookmark);
+        true
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct ChecklistItem {
+    pub id: Uuid,
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "opt_tw_date_format"
+    )]
+    pub done_at: Option<DateTime<Utc>>,
+    // Child task the item was promoted to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<TaskId>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct NewChecklistItem {
+    pub text: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ChecklistOrder {
+    pub items: Vec<Uuid>,
+}
+
+// Files are stored once per content, under their SHA-256 hash
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub name: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub hash: String,
+    #[serde(with = "tw_date_format")]
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq, Default)]
+pub struct AttachmentsCleanup {
+    pub dry_run: bool,
+    pub removed: Vec<String>,
+    pub freed_bytes: u64,
+}
+
+// Large notes are kept out of the Taskwarrior UDA, in the notes store only
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct NotesData {
+    pub revision: u32,
+    #[serde(with = "tw_date_format")]
+    pub modified: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct NoteRevision {
+    pub revision: u32,
+    #[serde(with = "tw_date_format")]
+    pub date: DateTime<Utc>,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct NoteRevisionInfo {
+    pub revision: u32,
+    #[serde(with = "tw_date_format")]
+    pub date: DateTime<Utc>,
+    pub length: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct TaskNotes {
+    pub task_id: TaskId,
+    pub revision: u32,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "opt_tw_date_format"
+    )]
+    pub modified: Option<DateTime<Utc>>,
+    pub content: String,
+    pub revisions: Vec<NoteRevisionInfo>,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+pub struct NotesUpdate {
+    pub content: String,
+    // Revision the update was based on, to detect concurrent edits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct NoteSearchResult {
+    pub task_id: TaskId,
+    pub description: String,
+    pub revision: u32,
+    pub snippet: String,
+}
+
+// Time spent on a task between a start and a stop
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct WorkSession {
+    #[serde(with = "tw_date_format")]
+    pub start: DateTime<Utc>,
+    #[serde(with = "tw_date_format")]
+    pub end: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl WorkSession {
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct Pomodoro {
+    #[serde(with = "tw_date_format")]
+    pub start: DateTime<Utc>,
+    pub work_seconds: u32,
+    pub break_seconds: u32,
+    #[serde(default)]
+    pub completed: bool,
+}
+
+impl Pomodoro {
+    pub fn work_end(&self) -> DateTime<Utc> {
+        self.start + Duration::seconds(self.work_seconds.into())
+    }
+
+    pub fn break_end(&self) -> DateTime<Utc> {
+        self.work_end() + Duration::seconds(self.break_seconds.into())
+    }
+}
+
+// Lengths default to the configured ones
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq, Default)]
+pub struct NewPomodoro {
+    #[serde(default)]
+    pub work_minutes: Option<u32>,
+    #[serde(default)]
+    pub break_minutes: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct BookmarkEvent {
+    #[serde(with = "uri")]
+    pub uri: Uri,
+    pub provider: String,
+    pub kind: String,
+    pub state: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum RuleAction {
+    Done,
+    Start,
+    Annotate { text: String },
+    Tag { tag: String },
+    Wait { until: String },
+}
+
+// Automatic change applied by a rule
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct HistoryEntry {
+    #[serde(with = "tw_date_format")]
+    pub date: DateTime<Utc>,
+    pub rule: String,
+    pub event: BookmarkEvent,
+    pub actions: Vec<RuleAction>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct Rule {
+    pub name: String,
+    pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    pub state: String,
+    pub actions: Vec<RuleAction>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct RuleMatch {
+    pub task_id: TaskId,
+    pub description: String,
+    pub rule: String,
+    pub event: BookmarkEvent,
+    pub actions: Vec<RuleAction>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
@@ -142,6 +589,165 @@ pub struct Task {
     pub tags: Option<Vec<String>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub contextswitch: Option<ContextswitchData>,
+    // Contextswitch data that cannot be read, kept as is until it is repaired
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invalid_contextswitch: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq, Default)]
+pub struct MigrationReport {
+    pub dry_run: bool,
+    pub version: u32,
+    pub migrated: Vec<TaskId>,
+    pub invalid: Vec<TaskId>,
+    pub up_to_date: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    InvalidData,
+    InvalidBookmarkUri,
+    DuplicateBookmark,
+    MissingParent,
+    MissingDependency,
+    OrphanedRecurrence,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct IntegrityIssue {
+    pub task_id: TaskId,
+    pub kind: IntegrityIssueKind,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq, Default)]
+pub struct IntegrityReport {
+    pub checked: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq, Default)]
+pub struct RepairRequest {
+    pub fixes: Vec<IntegrityIssueKind>,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+// Skipped issues cannot be repaired with the selected fixes alone
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq, Default)]
+pub struct RepairReport {
+    pub dry_run: bool,
+    pub repaired: Vec<IntegrityIssue>,
+    pub skipped: Vec<IntegrityIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "type", content = "task", rename_all = "snake_case")]
+pub enum TaskEvent {
+    Created(Task),
+    Updated(Task),
+    Deleted(Task),
+    PomodoroCompleted(Task),
+}
+
+impl TaskEvent {
+    pub fn task(&self) -> &Task {
+        match self {
+            TaskEvent::Created(task)
+            | TaskEvent::Updated(task)
+            | TaskEvent::Deleted(task)
+            | TaskEvent::PomodoroCompleted(task) => task,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    Created,
+    Updated,
+    Completed,
+    Deleted,
+    PomodoroCompleted,
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            WebhookEvent::Created => "created",
+            WebhookEvent::Updated => "updated",
+            WebhookEvent::Completed => "completed",
+            WebhookEvent::Deleted => "deleted",
+            WebhookEvent::PomodoroCompleted => "pomodoro_completed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl From<&TaskEvent> for WebhookEvent {
+    fn from(task_event: &TaskEvent) -> Self {
+        match task_event {
+            TaskEvent::Created(_) => WebhookEvent::Created,
+            TaskEvent::Updated(task) if task.status == Status::Completed => WebhookEvent::Completed,
+            TaskEvent::Updated(_) => WebhookEvent::Updated,
+            TaskEvent::Deleted(_) => WebhookEvent::Deleted,
+            TaskEvent::PomodoroCompleted(_) => WebhookEvent::PomodoroCompleted,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct Webhook {
+    pub name: String,
+    pub url: String,
+    // Empty means all events
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default)]
+    pub configured: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct NewWebhook {
+    pub name: String,
+    pub url: String,
+    pub secret: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    #[serde(with = "tw_date_format")]
+    pub timestamp: DateTime<Utc>,
+    pub task: Task,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct WebhookDelivery {
+    pub webhook: String,
+    pub event: WebhookEvent,
+    pub task_id: TaskId,
+    #[serde(with = "tw_date_format")]
+    pub timestamp: DateTime<Utc>,
+    pub attempts: u32,
+    pub delivered: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Focus {
+    pub task: Task,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_task: Option<Task>,
+    // Snapshot saved when the task was last switched away from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<ContextSnapshot>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -149,6 +755,170 @@ pub struct NewTask {
     pub definition: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct Context {
+    pub name: String,
+    pub read_filter: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_filter: Option<String>,
+    #[serde(default)]
+    pub active: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct NewContext {
+    pub name: String,
+    pub read_filter: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_filter: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct View {
+    pub name: String,
+    pub filter: String,
+    #[serde(default)]
+    pub sort: Vec<String>,
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<String>,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Project {
+    pub name: String,
+    pub pending: usize,
+    pub completed: usize,
+    pub progress: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contextswitch: Option<ContextswitchData>,
+    #[serde(default)]
+    pub children: Vec<Project>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct Tag {
+    pub name: String,
+    pub count: usize,
+    #[serde(default, rename = "virtual")]
+    pub is_virtual: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TagRename {
+    pub to: String,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TagMerge {
+    pub sources: Vec<String>,
+    pub target: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct TagChange {
+    pub sources: Vec<String>,
+    pub target: String,
+    pub tasks: Vec<TaskId>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsPeriod {
+    #[default]
+    Day,
+    Week,
+}
+
+impl fmt::Display for StatsPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct DateCount {
+    pub date: NaiveDate,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct BurndownPoint {
+    pub date: NaiveDate,
+    pub pending: usize,
+    pub completed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct Stats {
+    pub period: StatsPeriod,
+    pub by_status: BTreeMap<String, usize>,
+    pub by_project: BTreeMap<String, usize>,
+    pub by_tag: BTreeMap<String, usize>,
+    pub created: Vec<DateCount>,
+    pub completed: Vec<DateCount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_completion_seconds: Option<i64>,
+    pub overdue: usize,
+    pub burndown: Vec<BurndownPoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeGroupBy {
+    #[default]
+    Task,
+    Project,
+    Tag,
+}
+
+impl fmt::Display for TimeGroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct TimeEntry {
+    pub group: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<TaskId>,
+    pub seconds: i64,
+    pub sessions: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct TimeReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub group_by: TimeGroupBy,
+    pub entries: Vec<TimeEntry>,
+    pub total_seconds: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct PomodoroCount {
+    pub task_id: TaskId,
+    pub description: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Eq)]
+pub struct PomodoroStats {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub by_day: Vec<DateCount>,
+    pub by_task: Vec<PomodoroCount>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Eq)]
+pub struct ActiveContext {
+    pub name: Option<String>,
+}
+
 pub mod tw_date_format {
     use chrono::{DateTime, TimeZone, Utc};
     use serde::{self, Deserialize, Deserializer, Serializer};
@@ -0,0 +1,170 @@
+use crate::ContextswitchData;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+
+pub const CURRENT_VERSION: u32 = 1;
+
+// Schema version of the Contextswitch data, data without a version predates
+// versioning
+#[derive(Debug, Serialize, Deserialize, PartialEq, PartialOrd, Clone, Copy, Eq)]
+#[serde(transparent)]
+pub struct DataVersion(pub u32);
+
+impl Default for DataVersion {
+    fn default() -> Self {
+        DataVersion(CURRENT_VERSION)
+    }
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    InvalidJson(serde_json::Error),
+    NotAnObject,
+    InvalidVersion(Value),
+    // Written by a more recent Contextswitch, migrating it would lose data
+    UnsupportedVersion(u32),
+    InvalidData(serde_json::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MigrationError::InvalidJson(e) => write!(f, "Invalid JSON: {}", e),
+            MigrationError::NotAnObject => write!(f, "Not a JSON object"),
+            MigrationError::InvalidVersion(version) => write!(f, "Invalid version {}", version),
+            MigrationError::UnsupportedVersion(version) => write!(
+                f,
+                "Unsupported version {}, latest known version is {}",
+                version, CURRENT_VERSION
+            ),
+            MigrationError::InvalidData(e) => write!(f, "Invalid data: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+#[derive(Debug, PartialEq)]
+pub struct Migrated {
+    pub data: ContextswitchData,
+    pub from_version: u32,
+}
+
+impl Migrated {
+    pub fn was_migrated(&self) -> bool {
+        self.from_version < CURRENT_VERSION
+    }
+}
+
+type Migration = fn(&mut Map<String, Value>);
+
+// MIGRATIONS[n] upgrades data from version n to version n + 1
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [migrate_v0_to_v1];
+
+// Taskwarrior defaults the UDA to an empty object, without bookmarks
+fn migrate_v0_to_v1(data: &mut Map<String, Value>) {
+    let bookmarks = data
+        .entry("bookmarks")
+        .or_insert_with(|| Value::Array(vec![]));
+    if bookmarks.is_null() {
+        *bookmarks = Value::Array(vec![]);
+    }
+}
+
+fn data_version(data: &Map<String, Value>) -> Result<u32, MigrationError> {
+    match data.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| MigrationError::InvalidVersion(version.clone())),
+    }
+}
+
+pub fn migrate(value: Value) -> Result<Migrated, MigrationError> {
+    let mut data = match value {
+        Value::Object(data) => data,
+        _ => return Err(MigrationError::NotAnObject),
+    };
+    let from_version = data_version(&data)?;
+    if from_version > CURRENT_VERSION {
+        return Err(MigrationError::UnsupportedVersion(from_version));
+    }
+    for migration in MIGRATIONS[from_version as usize..].iter() {
+        migration(&mut data);
+    }
+    data.insert("version".to_string(), CURRENT_VERSION.into());
+
+    Ok(Migrated {
+        data: serde_json::from_value(Value::Object(data)).map_err(MigrationError::InvalidData)?,
+        from_version,
+    })
+}
+
+pub fn from_json(json: &str) -> Result<Migrated, MigrationError> {
+    migrate(serde_json::from_str(json).map_err(MigrationError::InvalidJson)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bookmark;
+
+    #[test]
+    fn test_migrate_unversioned_data() {
+        for json in ["{}", r#"{"bookmarks": null}"#] {
+            let migrated = from_json(json).unwrap();
+
+            assert_eq!(migrated.from_version, 0);
+            assert!(migrated.was_migrated());
+            assert_eq!(migrated.data.version, DataVersion(CURRENT_VERSION));
+            assert!(migrated.data.bookmarks.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_reject_future_version() {
+        let json = format!(r#"{{"version": {}, "bookmarks": []}}"#, CURRENT_VERSION + 1);
+
+        assert!(matches!(
+            from_json(&json),
+            Err(MigrationError::UnsupportedVersion(version)) if version == CURRENT_VERSION + 1
+        ));
+        assert!(matches!(
+            from_json(r#"{"version": "1", "bookmarks": []}"#),
+            Err(MigrationError::InvalidVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_reject_unparseable_data() {
+        assert!(matches!(
+            from_json("{not json"),
+            Err(MigrationError::InvalidJson(_))
+        ));
+        assert!(matches!(from_json("[]"), Err(MigrationError::NotAnObject)));
+        assert!(matches!(
+            from_json(r#"{"bookmarks": 42}"#),
+            Err(MigrationError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_current_data_round_trip() {
+        let data = ContextswitchData {
+            bookmarks: vec![Bookmark {
+                uri: "https://github.com/dax/contextswitch".parse().unwrap(),
+                content: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let migrated = from_json(&serde_json::to_string(&data).unwrap()).unwrap();
+
+        assert_eq!(migrated.from_version, CURRENT_VERSION);
+        assert!(!migrated.was_migrated());
+        assert_eq!(migrated.data, data);
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use contextswitch::Task;
+use uikit_rs as uk;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use yew::{
+    classes, function_component, html, use_effect_with_deps, use_state, Callback, Html, MouseEvent,
+    Properties,
+};
+
+const TICK_MILLISECONDS: i32 = 1000;
+
+#[derive(Properties, PartialEq)]
+pub struct ActiveTaskProps {
+    #[prop_or_default]
+    pub task: Option<Task>,
+    #[prop_or_default]
+    pub on_pomodoro_start: Callback<Task>,
+}
+
+fn countdown(end: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let remaining = (end - now).num_seconds().max(0);
+    format!("{:02}:{:02}", remaining / 60, remaining % 60)
+}
+
+#[function_component(ActiveTask)]
+pub fn active_task(
+    ActiveTaskProps {
+        task,
+        on_pomodoro_start,
+    }: &ActiveTaskProps,
+) -> Html {
+    let now = use_state(Utc::now);
+    {
+        let now = now.clone();
+        use_effect_with_deps(
+            move |_| {
+                let tick = Closure::<dyn FnMut()>::wrap(Box::new(move || now.set(Utc::now())));
+                let window = web_sys::window().unwrap(); // TODO
+                let interval = window
+                    .set_interval_with_callback_and_timeout_and_arguments_0(
+                        tick.as_ref().unchecked_ref(),
+                        TICK_MILLISECONDS,
+                    )
+                    .unwrap(); // TODO
+
+                move || {
+                    window.clear_interval_with_handle(interval);
+                    drop(tick);
+                }
+            },
+            (),
+        );
+    }
+    let task = match task {
+        Some(task) => task,
+        None => return html! {},
+    };
+    let started = task
+        .start
+        .map(|start| format!("since {}", start.format("%H:%M")))
+        .unwrap_or_default();
+    let note = task
+        .contextswitch
+        .as_ref()
+        .and_then(|contextswitch| contextswitch.snapshot.as_ref())
+        .and_then(|snapshot| snapshot.note.as_ref());
+    let note = match note {
+        Some(note) => html! {
+            <p class={classes!(uk::Text::Small, uk::Margin::SmallTop)}>
+              { format!("Left off: {}", note) }
+            </p>
+        },
+        None => html! {},
+    };
+    let running_pomodoro = task
+        .contextswitch
+        .as_ref()
+        .and_then(|contextswitch| contextswitch.pomodoros.last())
+        .filter(|pomodoro| !pomodoro.completed);
+    let pomodoro = match running_pomodoro {
+        Some(pomodoro) => html! {
+            <span class={classes!("uk-text-large")} title="Pomodoro">
+              { countdown(pomodoro.work_end(), *now) }
+            </span>
+        },
+        None => {
+            let onclick = {
+                let task = task.clone();
+                let on_pomodoro_start = on_pomodoro_start.clone();
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    on_pomodoro_start.emit(task.clone())
+                })
+            };
+            html! {
+                <a href="#" uk-icon="icon: clock" title="Start a pomodoro" onclick={onclick}></a>
+            }
+        }
+    };
+
+    html! {
+        <div class={classes!("uk-card", "uk-card-primary", "uk-card-small", "uk-card-body",
+                             uk::Margin::Default, "active-task")}>
+          <uk::Grid gap_size={uk::GridGapSize::Small}
+                    vertical_alignement={uk::FlexVerticalAlignement::Middle}>
+            <span uk-icon="icon: play-circle; ratio: 1.5"></span>
+            <uk::Flex width={uk::Width::_Expand}>
+              <div>
+                <uk::CardTitle>{task.description.clone()}</uk::CardTitle>
+                <span class={classes!(uk::Text::Meta)}>{started}</span>
+              </div>
+            </uk::Flex>
+            {pomodoro}
+          </uk::Grid>
+          {note}
+        </div>
+    }
+}
@@ -0,0 +1,47 @@
+use contextswitch::Context;
+use web_sys::HtmlSelectElement;
+use yew::{classes, function_component, html, Callback, Event, Html, Properties, TargetCast};
+
+#[derive(Properties, PartialEq)]
+pub struct ContextPickerProps {
+    #[prop_or_default]
+    pub contexts: Vec<Context>,
+    #[prop_or_default]
+    pub on_context_select: Callback<Option<String>>,
+}
+
+#[function_component(ContextPicker)]
+pub fn context_picker(
+    ContextPickerProps {
+        contexts,
+        on_context_select,
+    }: &ContextPickerProps,
+) -> Html {
+    let onchange = {
+        let on_context_select = on_context_select.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let value = select.value();
+            on_context_select.emit(if value.is_empty() { None } else { Some(value) });
+        })
+    };
+    let no_active_context = !contexts.iter().any(|context| context.active);
+
+    html! {
+        <select class={classes!("uk-select", "uk-form-small", "uk-form-width-medium")}
+                onchange={onchange}>
+          <option value="" selected={no_active_context}>{"no context"}</option>
+          {
+              contexts.iter().map(|context| {
+                  html! {
+                      <option value={context.name.clone()}
+                              title={context.read_filter.clone()}
+                              selected={context.active}>
+                        {context.name.clone()}
+                      </option>
+                  }
+              }).collect::<Html>()
+          }
+        </select>
+    }
+}
@@ -0,0 +1,170 @@
+use contextswitch::{BurndownPoint, DateCount, Stats, StatsPeriod};
+use std::collections::BTreeMap;
+use yew::{classes, function_component, html, Callback, Html, MouseEvent, Properties};
+
+#[derive(Properties, PartialEq)]
+pub struct DashboardProps {
+    pub stats: Option<Stats>,
+    #[prop_or_default]
+    pub period: StatsPeriod,
+    #[prop_or_default]
+    pub on_period_select: Callback<StatsPeriod>,
+}
+
+fn format_duration(seconds: i64) -> String {
+    let hours = seconds / 3600;
+    if hours >= 24 {
+        format!("{}d {}h", hours / 24, hours % 24)
+    } else {
+        format!("{}h {}m", hours, (seconds % 3600) / 60)
+    }
+}
+
+fn period_tabs(period: StatsPeriod, on_period_select: &Callback<StatsPeriod>) -> Html {
+    [StatsPeriod::Day, StatsPeriod::Week]
+        .iter()
+        .map(|tab_period| {
+            let onclick = {
+                let on_period_select = on_period_select.clone();
+                let tab_period = *tab_period;
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    on_period_select.emit(tab_period)
+                })
+            };
+            let label = match tab_period {
+                StatsPeriod::Day => "per day",
+                StatsPeriod::Week => "per week",
+            };
+
+            html! {
+                <li class={classes!((*tab_period == period).then(|| "uk-active"))}>
+                  <a href="#" onclick={onclick}>{label}</a>
+                </li>
+            }
+        })
+        .collect::<Html>()
+}
+
+fn summary(label: &str, value: String) -> Html {
+    html! {
+        <div>
+          <div class={classes!("uk-card", "uk-card-default", "uk-card-small", "uk-card-body", "uk-text-center")}>
+            <div class={classes!("uk-text-large")}>{value}</div>
+            <div class={classes!("uk-text-meta")}>{label}</div>
+          </div>
+        </div>
+    }
+}
+
+fn counts_table(title: &str, counts: &BTreeMap<String, usize>) -> Html {
+    let mut counts: Vec<(&String, &usize)> = counts.iter().collect();
+    counts.sort_by(|(_, count1), (_, count2)| count2.cmp(count1));
+
+    html! {
+        <div>
+          <h5>{title}</h5>
+          <table class={classes!("uk-table", "uk-table-small", "uk-table-divider")}>
+            <tbody>
+              {
+                  counts.iter().map(|(name, count)| html! {
+                      <tr>
+                        <td>{name.to_string()}</td>
+                        <td class={classes!("uk-text-right")}>{count.to_string()}</td>
+                      </tr>
+                  }).collect::<Html>()
+              }
+            </tbody>
+          </table>
+        </div>
+    }
+}
+
+fn bar(value: usize, max: usize, class: &'static str) -> Html {
+    let height = if max == 0 { 0 } else { value * 100 / max };
+
+    html! {
+        <div class={classes!(class)}
+             style={format!("height: {}%; min-height: 1px;", height)}></div>
+    }
+}
+
+fn burndown_chart(burndown: &[BurndownPoint]) -> Html {
+    let max = burndown
+        .iter()
+        .map(|point| point.pending + point.completed)
+        .max()
+        .unwrap_or(0);
+
+    html! {
+        <div class={classes!("uk-flex", "uk-flex-bottom")} style="height: 150px;">
+          {
+              burndown.iter().map(|point| html! {
+                  <div class={classes!("uk-flex", "uk-flex-column", "uk-flex-bottom", "uk-width-expand")}
+                       style="height: 100%;"
+                       title={format!("{}: {} pending, {} completed", point.date, point.pending, point.completed)}>
+                    { bar(point.pending, max, "uk-background-primary") }
+                    { bar(point.completed, max, "uk-background-muted") }
+                  </div>
+              }).collect::<Html>()
+          }
+        </div>
+    }
+}
+
+fn activity_table(created: &[DateCount], completed: &[DateCount]) -> Html {
+    html! {
+        <table class={classes!("uk-table", "uk-table-small", "uk-table-divider")}>
+          <thead>
+            <tr><th>{"date"}</th><th>{"created"}</th><th>{"completed"}</th></tr>
+          </thead>
+          <tbody>
+            {
+                created.iter().zip(completed.iter()).rev().map(|(created, completed)| html! {
+                    <tr>
+                      <td>{created.date.to_string()}</td>
+                      <td>{created.count.to_string()}</td>
+                      <td>{completed.count.to_string()}</td>
+                    </tr>
+                }).collect::<Html>()
+            }
+          </tbody>
+        </table>
+    }
+}
+
+#[function_component(Dashboard)]
+pub fn dashboard(
+    DashboardProps {
+        stats,
+        period,
+        on_period_select,
+    }: &DashboardProps,
+) -> Html {
+    let stats = match stats {
+        Some(stats) => stats,
+        None => return html! { <div uk-spinner=""></div> },
+    };
+    let status_count = |status: &str| stats.by_status.get(status).cloned().unwrap_or(0);
+
+    html! {
+        <div>
+          <ul class={classes!("uk-subnav", "uk-subnav-pill")}>
+            { period_tabs(*period, on_period_select) }
+          </ul>
+          <div class={classes!("uk-child-width-1-4", "uk-grid-small", "uk-margin")} uk-grid="">
+            { summary("pending", status_count("pending").to_string()) }
+            { summary("completed", status_count("completed").to_string()) }
+            { summary("overdue", stats.overdue.to_string()) }
+            { summary("average completion", stats.average_completion_seconds.map(format_duration).unwrap_or_else(|| "-".to_string())) }
+          </div>
+          <h5>{"Burndown"}</h5>
+          { burndown_chart(&stats.burndown) }
+          <div class={classes!("uk-child-width-1-2", "uk-grid-small", "uk-margin")} uk-grid="">
+            { counts_table("Projects", &stats.by_project) }
+            { counts_table("Tags", &stats.by_tag) }
+          </div>
+          { activity_table(&stats.created, &stats.completed) }
+        </div>
+    }
+}
@@ -1,2 +1,9 @@
+pub mod active_task;
+pub mod context_picker;
+pub mod dashboard;
+pub mod notes_editor;
+pub mod tag_filter;
 pub mod task;
 pub mod tasks_list;
+pub mod timesheet;
+pub mod view_tabs;
@@ -0,0 +1,130 @@
+use contextswitch::{NotesUpdate, TaskNotes};
+use pulldown_cmark::{html::push_html, Options, Parser};
+use uikit_rs as uk;
+use web_sys::HtmlTextAreaElement;
+use yew::{
+    classes, function_component, html, use_state, Callback, Html, InputEvent, MouseEvent,
+    Properties, TargetCast,
+};
+
+#[derive(Properties, PartialEq)]
+pub struct NotesEditorProps {
+    #[prop_or_default]
+    pub notes: Option<TaskNotes>,
+    #[prop_or_default]
+    pub on_notes_save: Callback<NotesUpdate>,
+}
+
+// Rendered Markdown is inserted as is, as notes only come from the user
+fn markdown_preview(content: &str) -> Html {
+    let mut rendered = String::new();
+    push_html(
+        &mut rendered,
+        Parser::new_ext(content, Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS),
+    );
+    let preview = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("div").ok())
+        .unwrap(); // TODO
+    preview.set_inner_html(&rendered);
+
+    Html::VRef(preview.into())
+}
+
+#[function_component(NotesEditor)]
+pub fn notes_editor(
+    NotesEditorProps {
+        notes,
+        on_notes_save,
+    }: &NotesEditorProps,
+) -> Html {
+    let draft = use_state(|| None);
+    let notes = match notes {
+        Some(notes) => notes,
+        None => return html! {},
+    };
+
+    let edit = {
+        let draft = draft.clone();
+        let content = notes.content.clone();
+        Callback::from(move |_: MouseEvent| draft.set(Some(content.clone())))
+    };
+    let cancel = {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| draft.set(None))
+    };
+    let oninput = {
+        let draft = draft.clone();
+        Callback::from(move |e: InputEvent| {
+            let textarea: HtmlTextAreaElement = e.target_unchecked_into();
+            draft.set(Some(textarea.value()));
+        })
+    };
+    let save = {
+        let draft = draft.clone();
+        let revision = notes.revision;
+        let on_notes_save = on_notes_save.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(content) = (*draft).clone() {
+                on_notes_save.emit(NotesUpdate {
+                    content,
+                    revision: Some(revision),
+                });
+                draft.set(None);
+            }
+        })
+    };
+    let revision_label = notes
+        .modified
+        .map(|modified| {
+            format!(
+                "revision {} · {}",
+                notes.revision,
+                modified.format("%Y-%m-%d %H:%M")
+            )
+        })
+        .unwrap_or_else(|| "no notes yet".to_string());
+
+    html! {
+        <div class={classes!(uk::Margin::Small)}>
+          <uk::Grid gap_size={uk::GridGapSize::Small}
+                    vertical_alignement={uk::FlexVerticalAlignement::Middle}>
+            <span class={classes!(uk::Width::_Expand, uk::Text::Meta)}>
+              { revision_label }
+            </span>
+            {
+                if draft.is_some() {
+                    html! {
+                        <>
+                          <button class="uk-button uk-button-primary uk-button-small" onclick={save}>
+                            {"Save"}
+                          </button>
+                          <button class="uk-button uk-button-default uk-button-small" onclick={cancel}>
+                            {"Cancel"}
+                          </button>
+                        </>
+                    }
+                } else {
+                    html! {
+                        <uk::Icon icon_type={uk::IconType::FileEdit} href="#" onclick={edit} />
+                    }
+                }
+            }
+          </uk::Grid>
+          {
+              match &*draft {
+                  Some(content) => html! {
+                      <div class="uk-grid-small uk-child-width-1-2" uk-grid="">
+                        <textarea class="uk-textarea"
+                                  rows="12"
+                                  value={content.clone()}
+                                  oninput={oninput} />
+                        <div>{ markdown_preview(content) }</div>
+                      </div>
+                  },
+                  None => markdown_preview(&notes.content),
+              }
+          }
+        </div>
+    }
+}
@@ -0,0 +1,54 @@
+use contextswitch::Tag;
+use web_sys::HtmlInputElement;
+use yew::{classes, function_component, html, Callback, Event, Html, Properties, TargetCast};
+
+#[derive(Properties, PartialEq)]
+pub struct TagFilterProps {
+    #[prop_or_default]
+    pub tags: Vec<Tag>,
+    #[prop_or_default]
+    pub on_tag_filter: Callback<Option<String>>,
+}
+
+#[function_component(TagFilter)]
+pub fn tag_filter(
+    TagFilterProps {
+        tags,
+        on_tag_filter,
+    }: &TagFilterProps,
+) -> Html {
+    let onchange = {
+        let on_tag_filter = on_tag_filter.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let value = input.value();
+            let tag = value.trim().trim_start_matches('+');
+            on_tag_filter.emit(if tag.is_empty() {
+                None
+            } else {
+                Some(tag.to_string())
+            });
+        })
+    };
+
+    html! {
+        <>
+          <input class={classes!("uk-input", "uk-form-small", "uk-form-width-small")}
+                 type="search"
+                 placeholder="+tag"
+                 list="tags"
+                 onchange={onchange} />
+          <datalist id="tags">
+            {
+                tags.iter().filter(|tag| !tag.is_virtual).map(|tag| {
+                    html! {
+                        <option value={tag.name.clone()}>
+                          { format!("{} ({})", tag.name, tag.count) }
+                        </option>
+                    }
+                }).collect::<Html>()
+            }
+          </datalist>
+        </>
+    }
+}
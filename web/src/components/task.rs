@@ -1,3 +1,4 @@
+use crate::components::notes_editor::NotesEditor;
 use contextswitch;
 use uikit_rs as uk;
 use yew::{classes, function_component, html, Callback, Classes, Html, MouseEvent, Properties};
@@ -8,7 +9,43 @@ pub struct TaskProps {
     #[prop_or_default]
     pub selected: bool,
     #[prop_or_default]
+    pub columns: Vec<String>,
+    #[prop_or_default]
+    pub project_bookmarks: Vec<contextswitch::Bookmark>,
+    #[prop_or_default]
     pub on_task_select: Callback<Option<contextswitch::Task>>,
+    #[prop_or_default]
+    pub on_task_note: Callback<contextswitch::Task>,
+    #[prop_or_default]
+    pub on_task_focus: Callback<contextswitch::Task>,
+    #[prop_or_default]
+    pub notes: Option<contextswitch::TaskNotes>,
+    #[prop_or_default]
+    pub on_notes_save: Callback<(contextswitch::Task, contextswitch::NotesUpdate)>,
+}
+
+pub fn column_value(task: &contextswitch::Task, column: &str) -> Option<String> {
+    let format_date = |date: chrono::DateTime<chrono::Utc>| date.format("%Y-%m-%d").to_string();
+    match column {
+        "description" => Some(task.description.clone()),
+        "status" => Some(task.status.to_string()),
+        "urgency" => Some(format!("{:.1}", task.urgency)),
+        "project" => task.project.clone(),
+        "priority" => task.priority.map(|priority| priority.to_string()),
+        "recur" => task.recur.map(|recur| recur.to_string()),
+        "tags" => task
+            .tags
+            .as_ref()
+            .filter(|tags| !tags.is_empty())
+            .map(|tags| tags.join(" ")),
+        "entry" => Some(format_date(task.entry)),
+        "modified" => Some(format_date(task.modified)),
+        "due" => task.due.map(format_date),
+        "start" => task.start.map(format_date),
+        "end" => task.end.map(format_date),
+        "wait" => task.wait.map(format_date),
+        _ => None,
+    }
 }
 
 #[function_component(Task)]
@@ -16,9 +53,30 @@ pub fn task(
     TaskProps {
         task,
         selected,
+        columns,
+        project_bookmarks,
         on_task_select,
+        on_task_note,
+        on_task_focus,
+        notes,
+        on_notes_save,
     }: &TaskProps,
 ) -> Html {
+    let open_note = {
+        let task = task.clone();
+        let on_task_note = on_task_note.clone();
+        Callback::from(move |_| on_task_note.emit(task.clone()))
+    };
+    let focus = {
+        let task = task.clone();
+        let on_task_focus = on_task_focus.clone();
+        Callback::from(move |_| on_task_focus.emit(task.clone()))
+    };
+    let save_notes = {
+        let task = task.clone();
+        let on_notes_save = on_notes_save.clone();
+        Callback::from(move |notes_update| on_notes_save.emit((task.clone(), notes_update)))
+    };
     let toggle_details = {
         let task = task.clone();
         let on_task_select = on_task_select.clone();
@@ -49,6 +107,11 @@ pub fn task(
     } else {
         0
     };
+    let (checklist_done, checklist_total) = task
+        .contextswitch
+        .as_ref()
+        .map(|contextswitch| contextswitch.checklist_progress())
+        .unwrap_or_default();
 
     html! {
         <uk::Card size={uk::CardSize::Small}
@@ -63,10 +126,14 @@ pub fn task(
               <uk::Icon icon_type={uk::IconType::Check}
                         text_style={vec![text_style]} />
               <TaskDescription task={task.clone()}
+                               columns={columns.clone()}
                                onclick={toggle_details.clone()} />
               <uk::IconNav>
                 <li>
-                  <uk::Icon icon_type={uk::IconType::FileEdit} href="#" />
+                  <a href="#" uk-icon="icon: play-circle" title="Switch to this task" onclick={focus}></a>
+                </li>
+                <li>
+                  <uk::Icon icon_type={uk::IconType::FileEdit} href="#" onclick={open_note} />
                 </li>
                 <li>
                   <uk::Link href="#" onclick={toggle_details.clone()}>
@@ -74,13 +141,30 @@ pub fn task(
                     <span> {bookmark_count}</span>
                   </uk::Link>
                 </li>
+                {
+                    if checklist_total > 0 {
+                        html! {
+                            <li>
+                              <uk::Link href="#" onclick={toggle_details.clone()}>
+                                <span uk-icon="icon: list" title="Checklist"></span>
+                                <span> {format!("{}/{}", checklist_done, checklist_total)}</span>
+                              </uk::Link>
+                            </li>
+                        }
+                    } else {
+                        html! {}
+                    }
+                }
               </uk::IconNav>
               <uk::Icon icon_type={arrow} href="#" onclick={toggle_details} />
             </uk::Grid>
             {
                 if *selected {
                     html! {
-                        <TaskDetails task={task.clone()} />
+                        <TaskDetails task={task.clone()}
+                                     project_bookmarks={project_bookmarks.clone()}
+                                     notes={notes.clone()}
+                                     on_notes_save={save_notes} />
                     }
                 } else { html! {} }
             }
@@ -93,16 +177,45 @@ pub fn task(
 pub struct TaskDescriptionProps {
     pub task: contextswitch::Task,
     #[prop_or_default]
+    pub columns: Vec<String>,
+    #[prop_or_default]
     pub onclick: Callback<MouseEvent>,
 }
 
 #[function_component(TaskDescription)]
-pub fn task_description(TaskDescriptionProps { task, onclick }: &TaskDescriptionProps) -> Html {
+pub fn task_description(
+    TaskDescriptionProps {
+        task,
+        columns,
+        onclick,
+    }: &TaskDescriptionProps,
+) -> Html {
+    let column_values: Vec<String> = columns
+        .iter()
+        .filter(|column| *column != "description")
+        .filter_map(|column| {
+            column_value(task, column).map(|value| format!("{}: {}", column, value))
+        })
+        .collect();
+
     html! {
         <uk::Flex width={uk::Width::_Expand} onclick={onclick}>
-          <uk::CardTitle text_style={vec![uk::Text::Lighter]}>
-          {task.description.clone()}
-          </uk::CardTitle>
+          <div>
+            <uk::CardTitle text_style={vec![uk::Text::Lighter]}>
+            {task.description.clone()}
+            </uk::CardTitle>
+            {
+                if column_values.is_empty() {
+                    html! {}
+                } else {
+                    html! {
+                        <span class={classes!(uk::Text::Meta)}>
+                          { column_values.join(" · ") }
+                        </span>
+                    }
+                }
+            }
+          </div>
         </uk::Flex>
     }
 }
@@ -110,10 +223,23 @@ pub fn task_description(TaskDescriptionProps { task, onclick }: &TaskDescription
 #[derive(Properties, PartialEq)]
 pub struct TaskDetailsProps {
     pub task: contextswitch::Task,
+    #[prop_or_default]
+    pub project_bookmarks: Vec<contextswitch::Bookmark>,
+    #[prop_or_default]
+    pub notes: Option<contextswitch::TaskNotes>,
+    #[prop_or_default]
+    pub on_notes_save: Callback<contextswitch::NotesUpdate>,
 }
 
 #[function_component(TaskDetails)]
-pub fn task_details(TaskDetailsProps { task }: &TaskDetailsProps) -> Html {
+pub fn task_details(
+    TaskDetailsProps {
+        task,
+        project_bookmarks,
+        notes,
+        on_notes_save,
+    }: &TaskDetailsProps,
+) -> Html {
     let priority = task
         .priority
         .as_ref()
@@ -135,6 +261,26 @@ pub fn task_details(TaskDetailsProps { task }: &TaskDetailsProps) -> Html {
                     }
                 } else { html! {} }
             }
+            {
+                if project_bookmarks.is_empty() {
+                    html! {}
+                } else {
+                    html! {
+                        <uk::Grid gap_size={uk::GridGapSize::Small} height_match={true}>
+                          <span class={classes!(uk::Width::_1_1, uk::Text::Meta)}>
+                            { format!("from project {}", project) }
+                          </span>
+                          {
+                              project_bookmarks.iter().map(|bookmark| {
+                                  html! {
+                                      <TaskBookmark bookmark={bookmark.clone()} />
+                                  }
+                              }).collect::<Html>()
+                          }
+                        </uk::Grid>
+                    }
+                }
+            }
           <uk::Grid gap_size={uk::GridGapSize::Small}
                     child_width={uk::ChildWidth::_Expand}>
             <span class={classes!(uk::Text::Meta)}>
@@ -144,6 +290,8 @@ pub fn task_details(TaskDetailsProps { task }: &TaskDetailsProps) -> Html {
               { format!("project: {}", project) }
             </span>
           </uk::Grid>
+          <NotesEditor notes={notes.clone()}
+                       on_notes_save={on_notes_save} />
         </div>
     }
 }
@@ -160,7 +308,7 @@ pub fn task_contextswitch(
     html! {
         <uk::Grid gap_size={uk::GridGapSize::Small} height_match={true}>
           {
-              contextswitch.bookmarks.iter().map(|bookmark| {
+              contextswitch.sorted_bookmarks().into_iter().map(|bookmark| {
                   html! {
                       <TaskBookmark bookmark={bookmark.clone()} />
                   }
@@ -179,11 +327,96 @@ pub struct TaskBookmarkProps {
 
 #[function_component(TaskBookmark)]
 pub fn task_bookmark(TaskBookmarkProps { bookmark }: &TaskBookmarkProps) -> Html {
+    let title = bookmark
+        .label
+        .clone()
+        .or_else(|| {
+            bookmark
+                .content
+                .as_ref()
+                .map(|content| content.title.clone())
+        })
+        .unwrap_or_else(|| bookmark.uri.to_string());
+    let pin = if bookmark.pinned {
+        html! {
+            <span uk-icon="icon: star" title="Pinned"></span>
+        }
+    } else {
+        html! {}
+    };
+    let details = match bookmark
+        .content
+        .as_ref()
+        .and_then(|content| content.details.as_ref())
+    {
+        Some(contextswitch::BookmarkDetails::Github(details)) => html! {
+            <>
+              <span class={classes!("uk-label", (details.state != "open").then(|| "uk-label-success"))}>
+                {details.state.clone()}
+              </span>
+              {
+                  details.ci_status.iter().map(|ci_status| html! {
+                      <span class={classes!("uk-label", (ci_status != "success").then(|| "uk-label-warning"))}>
+                        {format!("CI {}", ci_status)}
+                      </span>
+                  }).collect::<Html>()
+              }
+              {
+                  details.labels.iter().map(|label| html! {
+                      <span class={classes!("uk-text-meta")}>{label.clone()}</span>
+                  }).collect::<Html>()
+              }
+            </>
+        },
+        Some(contextswitch::BookmarkDetails::Slack(details)) => {
+            let new_replies = if details.new_replies() > 0 {
+                html! {
+                    <span class={classes!("uk-label", "uk-label-warning")}>
+                      {format!("{} new", details.new_replies())}
+                    </span>
+                }
+            } else {
+                html! {}
+            };
+            html! {
+                <>
+                  <span class={classes!("uk-text-meta")}>
+                    {format!("{} replies", details.reply_count)}
+                  </span>
+                  {new_replies}
+                </>
+            }
+        }
+        Some(contextswitch::BookmarkDetails::Note(_)) => html! {},
+        None => html! {},
+    };
+    // Provider icons are UIkit icon names, rendered by UIkit itself
+    let badge = match bookmark
+        .content
+        .as_ref()
+        .and_then(|content| content.badge.as_ref())
+    {
+        Some(badge) => html! {
+            <>
+              <span uk-icon={format!("icon: {}", badge.icon)} title={badge.provider.clone()}></span>
+              <uk::Link href={bookmark.uri.to_string()}>{title}</uk::Link>
+              <span class={classes!("uk-label")}>{badge.kind.replace('_', " ")}</span>
+            </>
+        },
+        None => html! {
+            <>
+              <uk::Icon icon_type={uk::IconType::Bookmark} />
+              <uk::Link href={bookmark.uri.to_string()}>{title}</uk::Link>
+            </>
+        },
+    };
+
     html! {
         <div class={classes!(uk::Width::_1_1, uk::Text::Small, uk::Margin::Remove)}>
             <uk::Grid gap_size={uk::GridGapSize::Small} vertical_alignement={uk::FlexVerticalAlignement::Middle}>
-              <uk::Icon icon_type={uk::IconType::Bookmark} />
-              <uk::Link href={bookmark.uri.to_string()}>{bookmark.uri.to_string()}</uk::Link>
+              {pin}
+              {badge}
+              {details}
             </uk::Grid>
         </div>
     }
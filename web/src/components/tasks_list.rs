@@ -1,5 +1,7 @@
 use crate::components::task;
-use contextswitch::Task;
+use contextswitch::{Bookmark, NotesUpdate, Project, Task, TaskNotes, View};
+use std::collections::BTreeMap;
+use uikit_rs as uk;
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq)]
@@ -7,32 +9,126 @@ pub struct TasksListProps {
     #[prop_or_default]
     pub tasks: Vec<Task>,
     #[prop_or_default]
+    pub view: Option<View>,
+    #[prop_or_default]
+    pub projects: Vec<Project>,
+    #[prop_or_default]
     pub selected_task: Option<Task>,
     #[prop_or_default]
     pub on_task_select: Callback<Option<Task>>,
+    #[prop_or_default]
+    pub on_task_note: Callback<Task>,
+    #[prop_or_default]
+    pub on_task_focus: Callback<Task>,
+    // Notes of the selected task
+    #[prop_or_default]
+    pub task_notes: Option<TaskNotes>,
+    #[prop_or_default]
+    pub on_notes_save: Callback<(Task, NotesUpdate)>,
+}
+
+fn find_project<'a>(projects: &'a [Project], name: &str) -> Option<&'a Project> {
+    projects.iter().find_map(|project| {
+        if project.name == name {
+            Some(project)
+        } else if name.starts_with(&format!("{}.", project.name)) {
+            find_project(&project.children, name)
+        } else {
+            None
+        }
+    })
+}
+
+// Bookmarks of a project and of its parent projects are inherited by its tasks
+fn project_bookmarks(projects: &[Project], task: &Task) -> Vec<Bookmark> {
+    let project_name = match &task.project {
+        Some(project_name) => project_name,
+        None => return vec![],
+    };
+    let mut ancestors: Vec<&str> = project_name
+        .match_indices('.')
+        .map(|(index, _)| &project_name[..index])
+        .collect();
+    ancestors.push(project_name);
+
+    ancestors
+        .iter()
+        .filter_map(|name| find_project(projects, name))
+        .filter_map(|project| project.contextswitch.as_ref())
+        .flat_map(|contextswitch| contextswitch.bookmarks.clone())
+        .collect()
+}
+
+fn group_key(task: &Task, group_by: &str) -> String {
+    task::column_value(task, group_by).unwrap_or_else(|| "-".to_string())
 }
 
 #[function_component(TasksList)]
 pub fn tasks_list(
     TasksListProps {
         tasks,
+        view,
+        projects,
         selected_task,
         on_task_select,
+        on_task_note,
+        on_task_focus,
+        task_notes,
+        on_notes_save,
     }: &TasksListProps,
 ) -> Html {
-    tasks
-        .iter()
-        .map(|task| {
-            let task_is_selected = selected_task
-                .clone()
-                .map(|t| t.id == task.id)
-                .unwrap_or(false);
-
-            html! {
-                <task::Task selected={task_is_selected}
-                            on_task_select={on_task_select}
-                            task={task.clone()} />
+    let columns = view
+        .as_ref()
+        .map(|view| view.columns.clone())
+        .unwrap_or_default();
+    let render_task = |task: &Task| {
+        let task_is_selected = selected_task
+            .clone()
+            .map(|t| t.id == task.id)
+            .unwrap_or(false);
+        let notes = task_notes
+            .clone()
+            .filter(|notes| task_is_selected && notes.task_id == task.id);
+
+        html! {
+            <task::Task selected={task_is_selected}
+                        on_task_select={on_task_select}
+                        on_task_note={on_task_note}
+                        on_task_focus={on_task_focus}
+                        notes={notes}
+                        on_notes_save={on_notes_save}
+                        columns={columns.clone()}
+                        project_bookmarks={project_bookmarks(projects, task)}
+                        task={task.clone()} />
+        }
+    };
+
+    match view.as_ref().and_then(|view| view.group_by.as_ref()) {
+        Some(group_by) => {
+            let mut groups: BTreeMap<String, Vec<&Task>> = BTreeMap::new();
+            for task in tasks {
+                groups
+                    .entry(group_key(task, group_by))
+                    .or_default()
+                    .push(task);
             }
-        })
-        .collect()
+
+            groups
+                .iter()
+                .map(|(group, group_tasks)| {
+                    html! {
+                        <>
+                          <div class={classes!(uk::Width::_1_1)}>
+                            <h5 class={classes!(uk::Margin::Remove, uk::Text::Meta)}>
+                              { format!("{}: {}", group_by, group) }
+                            </h5>
+                          </div>
+                          { group_tasks.iter().copied().map(&render_task).collect::<Html>() }
+                        </>
+                    }
+                })
+                .collect()
+        }
+        None => tasks.iter().map(render_task).collect(),
+    }
 }
@@ -0,0 +1,92 @@
+use contextswitch::{TimeGroupBy, TimeReport};
+use yew::{classes, function_component, html, Callback, Html, MouseEvent, Properties};
+
+#[derive(Properties, PartialEq)]
+pub struct TimesheetProps {
+    pub time_report: Option<TimeReport>,
+    #[prop_or_default]
+    pub group_by: TimeGroupBy,
+    #[prop_or_default]
+    pub on_group_by_select: Callback<TimeGroupBy>,
+    #[prop_or_default]
+    pub csv_url: String,
+}
+
+fn format_duration(seconds: i64) -> String {
+    format!("{}h {:02}m", seconds / 3600, (seconds % 3600) / 60)
+}
+
+fn group_by_tabs(group_by: TimeGroupBy, on_group_by_select: &Callback<TimeGroupBy>) -> Html {
+    [TimeGroupBy::Task, TimeGroupBy::Project, TimeGroupBy::Tag]
+        .iter()
+        .map(|tab_group_by| {
+            let onclick = {
+                let on_group_by_select = on_group_by_select.clone();
+                let tab_group_by = *tab_group_by;
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    on_group_by_select.emit(tab_group_by)
+                })
+            };
+
+            html! {
+                <li class={classes!((*tab_group_by == group_by).then(|| "uk-active"))}>
+                  <a href="#" onclick={onclick}>{format!("per {}", tab_group_by)}</a>
+                </li>
+            }
+        })
+        .collect::<Html>()
+}
+
+#[function_component(Timesheet)]
+pub fn timesheet(
+    TimesheetProps {
+        time_report,
+        group_by,
+        on_group_by_select,
+        csv_url,
+    }: &TimesheetProps,
+) -> Html {
+    let time_report = match time_report {
+        Some(time_report) => time_report,
+        None => return html! { <div uk-spinner=""></div> },
+    };
+
+    html! {
+        <div>
+          <div class={classes!("uk-flex", "uk-flex-middle")}>
+            <ul class={classes!("uk-subnav", "uk-subnav-pill", "uk-width-expand")}>
+              { group_by_tabs(*group_by, on_group_by_select) }
+            </ul>
+            <a class={classes!("uk-button", "uk-button-default", "uk-button-small")} href={csv_url.clone()}>
+              {"CSV"}
+            </a>
+          </div>
+          <span class={classes!("uk-text-meta")}>
+            { format!("{} to {}", time_report.from, time_report.to) }
+          </span>
+          <table class={classes!("uk-table", "uk-table-small", "uk-table-divider")}>
+            <thead>
+              <tr><th>{group_by.to_string()}</th><th>{"sessions"}</th><th class={classes!("uk-text-right")}>{"time"}</th></tr>
+            </thead>
+            <tbody>
+              {
+                  time_report.entries.iter().map(|entry| html! {
+                      <tr>
+                        <td>{entry.group.clone()}</td>
+                        <td>{entry.sessions.to_string()}</td>
+                        <td class={classes!("uk-text-right")}>{format_duration(entry.seconds)}</td>
+                      </tr>
+                  }).collect::<Html>()
+              }
+            </tbody>
+            <tfoot>
+              <tr>
+                <td colspan="2">{"total"}</td>
+                <td class={classes!("uk-text-right")}>{format_duration(time_report.total_seconds)}</td>
+              </tr>
+            </tfoot>
+          </table>
+        </div>
+    }
+}
@@ -0,0 +1,80 @@
+use contextswitch::View;
+use yew::{classes, function_component, html, Callback, Html, MouseEvent, Properties};
+
+#[derive(Properties, PartialEq)]
+pub struct ViewTabsProps {
+    #[prop_or_default]
+    pub views: Vec<View>,
+    #[prop_or_default]
+    pub selected_view: Option<String>,
+    #[prop_or_default]
+    pub on_view_select: Callback<String>,
+    #[prop_or_default]
+    pub dashboard_selected: bool,
+    #[prop_or_default]
+    pub on_dashboard_select: Callback<()>,
+    #[prop_or_default]
+    pub timesheet_selected: bool,
+    #[prop_or_default]
+    pub on_timesheet_select: Callback<()>,
+}
+
+#[function_component(ViewTabs)]
+pub fn view_tabs(
+    ViewTabsProps {
+        views,
+        selected_view,
+        on_view_select,
+        dashboard_selected,
+        on_dashboard_select,
+        timesheet_selected,
+        on_timesheet_select,
+    }: &ViewTabsProps,
+) -> Html {
+    let on_dashboard_click = {
+        let on_dashboard_select = on_dashboard_select.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            on_dashboard_select.emit(())
+        })
+    };
+    let on_timesheet_click = {
+        let on_timesheet_select = on_timesheet_select.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            on_timesheet_select.emit(())
+        })
+    };
+
+    html! {
+        <ul class={classes!("uk-tab")}>
+          {
+              views.iter().map(|view| {
+                  let onclick = {
+                      let on_view_select = on_view_select.clone();
+                      let name = view.name.clone();
+                      Callback::from(move |e: MouseEvent| {
+                          e.prevent_default();
+                          on_view_select.emit(name.clone())
+                      })
+                  };
+                  let active = !dashboard_selected && !timesheet_selected && selected_view.as_ref() == Some(&view.name);
+
+                  html! {
+                      <li class={classes!(active.then(|| "uk-active"))}>
+                        <a href="#" title={view.filter.clone()} onclick={onclick}>
+                          {view.name.clone()}
+                        </a>
+                      </li>
+                  }
+              }).collect::<Html>()
+          }
+          <li class={classes!("uk-margin-auto-left", dashboard_selected.then(|| "uk-active"))}>
+            <a href="#" onclick={on_dashboard_click}>{"dashboard"}</a>
+          </li>
+          <li class={classes!(timesheet_selected.then(|| "uk-active"))}>
+            <a href="#" onclick={on_timesheet_click}>{"timesheet"}</a>
+          </li>
+        </ul>
+    }
+}
@@ -1,8 +1,20 @@
+use components::active_task::ActiveTask;
+use components::context_picker::ContextPicker;
+use components::dashboard::Dashboard;
+use components::tag_filter::TagFilter;
 use components::tasks_list::TasksList;
-use contextswitch::Task;
+use components::timesheet::Timesheet;
+use components::view_tabs::ViewTabs;
+use contextswitch::{
+    ActiveContext, Context, FocusRequest, NewPomodoro, NotesUpdate, Project, Stats, StatsPeriod,
+    Status, Tag, Task, TaskEvent, TaskId, TaskNotes, TimeGroupBy, TimeReport, View,
+};
 use reqwasm::http::Request;
+use std::rc::Rc;
 use uikit_rs as uk;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{EventSource, MessageEvent};
 use yew::prelude::*;
 
 mod components;
@@ -12,29 +24,346 @@ extern "C" {
     fn get_api_base_url() -> String;
 }
 
+#[derive(PartialEq, Default)]
+struct TasksState {
+    tasks: Vec<Task>,
+}
+
+enum TasksAction {
+    Set(Vec<Task>),
+    Apply(TaskEvent),
+}
+
+impl Reducible for TasksState {
+    type Action = TasksAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let tasks = match action {
+            TasksAction::Set(tasks) => tasks,
+            TasksAction::Apply(TaskEvent::Created(task)) => {
+                let mut tasks = self.tasks.clone();
+                tasks.push(task);
+                tasks
+            }
+            TasksAction::Apply(TaskEvent::Updated(task))
+            | TasksAction::Apply(TaskEvent::PomodoroCompleted(task)) => self
+                .tasks
+                .iter()
+                .map(|current_task| {
+                    if current_task.id == task.id {
+                        task.clone()
+                    } else {
+                        current_task.clone()
+                    }
+                })
+                .collect(),
+            TasksAction::Apply(TaskEvent::Deleted(task)) => self
+                .tasks
+                .iter()
+                .filter(|current_task| current_task.id != task.id)
+                .cloned()
+                .collect(),
+        };
+
+        TasksState { tasks }.into()
+    }
+}
+
 #[function_component(App)]
 pub fn app() -> Html {
-    let tasks = use_state(Vec::new);
+    let contexts = use_state(Vec::new);
+    let active_context = use_state(|| None);
     {
-        let tasks = tasks.clone();
+        let contexts = contexts.clone();
+        let active_context = active_context.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let fetched_contexts: Vec<Context> =
+                        Request::get(&format!("{}/contexts", get_api_base_url()))
+                            .send()
+                            .await
+                            .unwrap() // TODO
+                            .json()
+                            .await
+                            .unwrap(); // TODO
+                    active_context.set(
+                        fetched_contexts
+                            .iter()
+                            .find(|context| context.active)
+                            .map(|context| context.name.clone()),
+                    );
+                    contexts.set(fetched_contexts);
+                });
+                || ()
+            },
+            (),
+        );
+    }
+    let on_context_select = {
+        let contexts = contexts.clone();
+        let active_context = active_context.clone();
+        Callback::from(move |name: Option<String>| {
+            let contexts = contexts.clone();
+            let active_context = active_context.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let body = serde_json::to_string(&ActiveContext { name: name.clone() }).unwrap();
+                Request::put(&format!("{}/contexts/active", get_api_base_url()))
+                    .header("Content-Type", "application/json")
+                    .body(body)
+                    .send()
+                    .await
+                    .unwrap(); // TODO
+                contexts.set(
+                    (*contexts)
+                        .iter()
+                        .map(|context| Context {
+                            active: Some(&context.name) == name.as_ref(),
+                            ..context.clone()
+                        })
+                        .collect(),
+                );
+                active_context.set(name);
+            });
+        })
+    };
+
+    let views = use_state(Vec::new);
+    let selected_view = use_state(|| "next".to_string());
+    {
+        let views = views.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let fetched_views: Vec<View> =
+                        Request::get(&format!("{}/views", get_api_base_url()))
+                            .send()
+                            .await
+                            .unwrap() // TODO
+                            .json()
+                            .await
+                            .unwrap(); // TODO
+                    views.set(fetched_views);
+                });
+                || ()
+            },
+            (),
+        );
+    }
+    let show_dashboard = use_state(|| false);
+    let show_timesheet = use_state(|| false);
+    let on_view_select = {
+        let selected_view = selected_view.clone();
+        let show_dashboard = show_dashboard.clone();
+        let show_timesheet = show_timesheet.clone();
+        Callback::from(move |name: String| {
+            show_dashboard.set(false);
+            show_timesheet.set(false);
+            selected_view.set(name);
+        })
+    };
+    let on_dashboard_select = {
+        let show_dashboard = show_dashboard.clone();
+        let show_timesheet = show_timesheet.clone();
+        Callback::from(move |_| {
+            show_timesheet.set(false);
+            show_dashboard.set(true);
+        })
+    };
+    let on_timesheet_select = {
+        let show_dashboard = show_dashboard.clone();
+        let show_timesheet = show_timesheet.clone();
+        Callback::from(move |_| {
+            show_dashboard.set(false);
+            show_timesheet.set(true);
+        })
+    };
+    let view = views
+        .iter()
+        .find(|view| view.name == *selected_view)
+        .cloned();
+
+    let projects = use_state(Vec::new);
+    {
+        let projects = projects.clone();
+        use_effect_with_deps(
+            move |_| {
+                wasm_bindgen_futures::spawn_local(async move {
+                    let fetched_projects: Vec<Project> =
+                        Request::get(&format!("{}/projects", get_api_base_url()))
+                            .send()
+                            .await
+                            .unwrap() // TODO
+                            .json()
+                            .await
+                            .unwrap(); // TODO
+                    projects.set(fetched_projects);
+                });
+                || ()
+            },
+            (*active_context).clone(),
+        );
+    }
+
+    let tags = use_state(Vec::new);
+    {
+        let tags = tags.clone();
         use_effect_with_deps(
             move |_| {
                 wasm_bindgen_futures::spawn_local(async move {
-                    let fetched_tasks: Vec<Task> =
-                        Request::get(&format!("{}/tasks?filter=task", get_api_base_url()))
+                    let fetched_tags: Vec<Tag> =
+                        Request::get(&format!("{}/tags", get_api_base_url()))
                             .send()
                             .await
                             .unwrap() // TODO
                             .json()
                             .await
                             .unwrap(); // TODO
-                    tasks.set(fetched_tasks);
+                    tags.set(fetched_tags);
                 });
                 || ()
             },
+            (*active_context).clone(),
+        );
+    }
+    let tag_filter = use_state(|| None);
+    let on_tag_filter = {
+        let tag_filter = tag_filter.clone();
+        Callback::from(move |tag: Option<String>| {
+            tag_filter.set(tag);
+        })
+    };
+
+    let tasks = use_reducer(TasksState::default);
+    {
+        let tasks = tasks.clone();
+        use_effect_with_deps(
+            move |(_, selected_view)| {
+                let selected_view = selected_view.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let fetched_tasks: Vec<Task> = Request::get(&format!(
+                        "{}/views/{}/tasks",
+                        get_api_base_url(),
+                        selected_view
+                    ))
+                    .send()
+                    .await
+                    .unwrap() // TODO
+                    .json()
+                    .await
+                    .unwrap(); // TODO
+                    tasks.dispatch(TasksAction::Set(fetched_tasks));
+                });
+                || ()
+            },
+            ((*active_context).clone(), (*selected_view).clone()),
+        );
+    }
+    {
+        let tasks = tasks.clone();
+        use_effect_with_deps(
+            move |_| {
+                let event_source =
+                    EventSource::new(&format!("{}/events", get_api_base_url())).unwrap(); // TODO
+                let on_message = Closure::<dyn FnMut(MessageEvent)>::wrap(Box::new(
+                    move |message: MessageEvent| {
+                        if let Some(data) = message.data().as_string() {
+                            if let Ok(task_event) = serde_json::from_str::<TaskEvent>(&data) {
+                                tasks.dispatch(TasksAction::Apply(task_event));
+                            }
+                        }
+                    },
+                ));
+                event_source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+                move || {
+                    event_source.close();
+                    drop(on_message);
+                }
+            },
             (),
         );
     }
+    let filtered_tasks: Vec<Task> = match &*tag_filter {
+        Some(tag) => tasks
+            .tasks
+            .iter()
+            .filter(|task| task.tags.iter().flatten().any(|task_tag| task_tag == tag))
+            .cloned()
+            .collect(),
+        None => tasks.tasks.clone(),
+    };
+    let stats_period = use_state(StatsPeriod::default);
+    let stats = use_state(|| None);
+    {
+        let stats = stats.clone();
+        use_effect_with_deps(
+            move |(_, show_dashboard, stats_period)| {
+                if *show_dashboard {
+                    let stats_period = *stats_period;
+                    stats.set(None);
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let fetched_stats: Stats = Request::get(&format!(
+                            "{}/stats?period={}",
+                            get_api_base_url(),
+                            stats_period
+                        ))
+                        .send()
+                        .await
+                        .unwrap() // TODO
+                        .json()
+                        .await
+                        .unwrap(); // TODO
+                        stats.set(Some(fetched_stats));
+                    });
+                }
+                || ()
+            },
+            ((*active_context).clone(), *show_dashboard, *stats_period),
+        );
+    }
+    let time_group_by = use_state(TimeGroupBy::default);
+    let time_report = use_state(|| None);
+    {
+        let time_report = time_report.clone();
+        use_effect_with_deps(
+            move |(_, show_timesheet, time_group_by)| {
+                if *show_timesheet {
+                    let time_group_by = *time_group_by;
+                    time_report.set(None);
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let fetched_time_report: TimeReport = Request::get(&format!(
+                            "{}/time?group_by={}",
+                            get_api_base_url(),
+                            time_group_by
+                        ))
+                        .send()
+                        .await
+                        .unwrap() // TODO
+                        .json()
+                        .await
+                        .unwrap(); // TODO
+                        time_report.set(Some(fetched_time_report));
+                    });
+                }
+                || ()
+            },
+            ((*active_context).clone(), *show_timesheet, *time_group_by),
+        );
+    }
+    let on_time_group_by_select = {
+        let time_group_by = time_group_by.clone();
+        Callback::from(move |group_by: TimeGroupBy| {
+            time_group_by.set(group_by);
+        })
+    };
+    let on_period_select = {
+        let stats_period = stats_period.clone();
+        Callback::from(move |period: StatsPeriod| {
+            stats_period.set(period);
+        })
+    };
+
     let selected_task = use_state(|| None);
     let on_task_select = {
         let selected_task = selected_task.clone();
@@ -42,10 +371,113 @@ pub fn app() -> Html {
             selected_task.set(task);
         })
     };
+    let task_notes = use_state(|| None);
+    {
+        let task_notes = task_notes.clone();
+        use_effect_with_deps(
+            move |selected_task_id: &Option<TaskId>| {
+                task_notes.set(None);
+                if let Some(task_id) = selected_task_id.clone() {
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let fetched_task_notes: TaskNotes = Request::get(&format!(
+                            "{}/tasks/{}/notes",
+                            get_api_base_url(),
+                            task_id
+                        ))
+                        .send()
+                        .await
+                        .unwrap() // TODO
+                        .json()
+                        .await
+                        .unwrap(); // TODO
+                        task_notes.set(Some(fetched_task_notes));
+                    });
+                }
+                || ()
+            },
+            (*selected_task).as_ref().map(|task: &Task| task.id.clone()),
+        );
+    }
+    let on_notes_save = {
+        let task_notes = task_notes.clone();
+        Callback::from(move |(task, notes_update): (Task, NotesUpdate)| {
+            let task_notes = task_notes.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let body = serde_json::to_string(&notes_update).unwrap();
+                let saved_task_notes: TaskNotes =
+                    Request::put(&format!("{}/tasks/{}/notes", get_api_base_url(), task.id))
+                        .header("Content-Type", "application/json")
+                        .body(body)
+                        .send()
+                        .await
+                        .unwrap() // TODO
+                        .json()
+                        .await
+                        .unwrap(); // TODO
+                task_notes.set(Some(saved_task_notes));
+            });
+        })
+    };
 
-    html! {
-        <uk::Section style={uk::SectionStyle::Default}>
-          <uk::Container size={uk::ContainerSize::Small}>
+    // The updated task is received through the events stream
+    let on_task_note = Callback::from(move |task: Task| {
+        wasm_bindgen_futures::spawn_local(async move {
+            Request::post(&format!("{}/tasks/{}/note", get_api_base_url(), task.id))
+                .send()
+                .await
+                .unwrap(); // TODO
+        });
+    });
+
+    // Stopped and started tasks are received through the events stream
+    let on_task_focus = Callback::from(move |task: Task| {
+        wasm_bindgen_futures::spawn_local(async move {
+            let body = serde_json::to_string(&FocusRequest::default()).unwrap();
+            Request::post(&format!("{}/focus/{}", get_api_base_url(), task.id))
+                .header("Content-Type", "application/json")
+                .body(body)
+                .send()
+                .await
+                .unwrap(); // TODO
+        });
+    });
+    let on_pomodoro_start = Callback::from(move |task: Task| {
+        wasm_bindgen_futures::spawn_local(async move {
+            let body = serde_json::to_string(&NewPomodoro::default()).unwrap();
+            Request::post(&format!(
+                "{}/tasks/{}/pomodoro",
+                get_api_base_url(),
+                task.id
+            ))
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .unwrap(); // TODO
+        });
+    });
+    let active_task = tasks
+        .tasks
+        .iter()
+        .filter(|task| task.status == Status::Pending && task.start.is_some())
+        .max_by_key(|task| task.start)
+        .cloned();
+
+    let content = if *show_dashboard {
+        html! {
+            <Dashboard stats={(*stats).clone()}
+                       period={*stats_period}
+                       on_period_select={on_period_select} />
+        }
+    } else if *show_timesheet {
+        html! {
+            <Timesheet time_report={(*time_report).clone()}
+                       group_by={*time_group_by}
+                       on_group_by_select={on_time_group_by_select}
+                       csv_url={format!("{}/time?group_by={}&format=csv", get_api_base_url(), *time_group_by)} />
+        }
+    } else {
+        html! {
             <uk::Filter target=".status-filter"
               filter_width={uk::Width::_Expand}
               filter_component={uk::UIKitComponent::SubNav}
@@ -57,11 +489,43 @@ pub fn app() -> Html {
                         margin={vec![uk::Margin::Default]}
                         height_match={true}
                         class={"status-filter"}>
-                <TasksList tasks={(*tasks).clone()}
+                <TasksList tasks={filtered_tasks}
+                           view={view}
+                           projects={(*projects).clone()}
                            selected_task={(*selected_task).clone()}
-                           on_task_select={on_task_select} />
+                           on_task_select={on_task_select}
+                           on_task_note={on_task_note}
+                           on_task_focus={on_task_focus}
+                           task_notes={(*task_notes).clone()}
+                           on_notes_save={on_notes_save} />
               </uk::Grid>
             </uk::Filter>
+        }
+    };
+
+    html! {
+        <uk::Section style={uk::SectionStyle::Default}>
+          <uk::Container size={uk::ContainerSize::Small}>
+            <uk::Grid gap_size={uk::GridGapSize::Small}
+                      vertical_alignement={uk::FlexVerticalAlignement::Middle}>
+              <uk::Flex width={uk::Width::_Expand}>
+                <h3 class={classes!(uk::Margin::Remove)}>{"Contextswitch"}</h3>
+              </uk::Flex>
+              <TagFilter tags={(*tags).clone()}
+                         on_tag_filter={on_tag_filter} />
+              <ContextPicker contexts={(*contexts).clone()}
+                             on_context_select={on_context_select} />
+            </uk::Grid>
+            <ViewTabs views={(*views).clone()}
+                      selected_view={Some((*selected_view).clone())}
+                      on_view_select={on_view_select}
+                      dashboard_selected={*show_dashboard}
+                      on_dashboard_select={on_dashboard_select}
+                      timesheet_selected={*show_timesheet}
+                      on_timesheet_select={on_timesheet_select} />
+            <ActiveTask task={active_task}
+                        on_pomodoro_start={on_pomodoro_start} />
+            { content }
           </uk::Container>
         </uk::Section>
     }